@@ -0,0 +1,176 @@
+//! Description of the files contained in a downloaded archive.
+//!
+//! The download API does not describe its archives, so [`crate::download`] builds the
+//! manifest from the entries of the zip archive itself (see [`ArtifactManifest::from_zip`]).
+
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::path::Path;
+use zip::ZipArchive;
+
+/// What a file in the downloaded archive is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactRole {
+    /// The obfuscated binary.
+    Binary,
+    /// Map of original to obfuscated addresses.
+    Map,
+    /// Debug symbols (PDB) matching the obfuscated binary.
+    Symbols,
+    /// A file with a role this client does not know about.
+    #[serde(other)]
+    Other,
+}
+
+impl ArtifactRole {
+    /// Guesses the role of a file from its extension.
+    fn from_file_name(name: &str) -> Self {
+        let extension = Path::new(name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("exe" | "dll" | "sys") => ArtifactRole::Binary,
+            Some("map") => ArtifactRole::Map,
+            Some("pdb") => ArtifactRole::Symbols,
+            _ => ArtifactRole::Other,
+        }
+    }
+}
+
+/// A single file in the downloaded archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Artifact {
+    /// Path of the file inside the archive.
+    pub name: String,
+    /// What the file is used for.
+    pub role: ArtifactRole,
+}
+
+/// List of the files contained in a downloaded archive.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactManifest {
+    /// Files in the archive, in archive order.
+    pub files: Vec<Artifact>,
+}
+
+impl ArtifactManifest {
+    /// Parses a manifest from its JSON representation.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Lists the files of a zip archive, in archive order, skipping directories. `None` if
+    /// `bytes` is not a readable zip archive.
+    ///
+    /// Roles are guessed from file extensions: `.exe`, `.dll` and `.sys` files are
+    /// [`ArtifactRole::Binary`], `.map` files [`ArtifactRole::Map`] and `.pdb` files
+    /// [`ArtifactRole::Symbols`].
+    pub fn from_zip(bytes: &[u8]) -> Option<Self> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes)).ok()?;
+        let mut files = Vec::with_capacity(archive.len());
+        for i in 0..archive.len() {
+            let entry = archive.by_index_raw(i).ok()?;
+            if entry.is_dir() {
+                continue;
+            }
+            files.push(Artifact {
+                name: entry.name().to_owned(),
+                role: ArtifactRole::from_file_name(entry.name()),
+            });
+        }
+        Some(Self { files })
+    }
+
+    /// Returns the first file with the given role, if any.
+    pub fn find(&self, role: ArtifactRole) -> Option<&Artifact> {
+        self.files.iter().find(|artifact| artifact.role == role)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_manifest_and_finds_files_by_role() {
+        let manifest = ArtifactManifest::from_json(
+            r#"{"files": [
+                {"name": "app.exe", "role": "binary"},
+                {"name": "app.map", "role": "map"},
+                {"name": "app.pdb", "role": "symbols"},
+                {"name": "notes.txt", "role": "readme"}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.files.len(), 4);
+        assert_eq!(manifest.find(ArtifactRole::Binary).unwrap().name, "app.exe");
+        assert_eq!(
+            manifest.find(ArtifactRole::Symbols).unwrap().name,
+            "app.pdb"
+        );
+        assert_eq!(manifest.files[3].role, ArtifactRole::Other);
+    }
+
+    fn zip(names: &[&str]) -> Vec<u8> {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for name in names {
+            if name.ends_with('/') {
+                writer.add_directory(*name, options).unwrap();
+            } else {
+                writer.start_file(*name, options).unwrap();
+                writer.write_all(b"contents").unwrap();
+            }
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn lists_the_files_of_a_zip_archive() {
+        let archive = zip(&["out/", "out/App.EXE", "out/app.pdb", "app.map", "README"]);
+
+        let manifest = ArtifactManifest::from_zip(&archive).unwrap();
+
+        assert_eq!(
+            manifest.files,
+            [
+                Artifact {
+                    name: "out/App.EXE".to_owned(),
+                    role: ArtifactRole::Binary
+                },
+                Artifact {
+                    name: "out/app.pdb".to_owned(),
+                    role: ArtifactRole::Symbols
+                },
+                Artifact {
+                    name: "app.map".to_owned(),
+                    role: ArtifactRole::Map
+                },
+                Artifact {
+                    name: "README".to_owned(),
+                    role: ArtifactRole::Other
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn non_zip_bytes_have_no_manifest() {
+        assert_eq!(ArtifactManifest::from_zip(b"MZ not a zip"), None);
+        assert_eq!(ArtifactManifest::from_zip(b""), None);
+    }
+
+    #[test]
+    fn missing_roles_are_not_found() {
+        let manifest = ArtifactManifest::from_json(r#"{"files": []}"#).unwrap();
+        assert_eq!(manifest.find(ArtifactRole::Map), None);
+        assert!(ArtifactManifest::from_json("not json").is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//! Optional logging of every HTTP exchange, for debugging the server integration.
+//!
+//! When enabled with [`set_debug_http`] or the [`DEBUG_HTTP_ENV`] environment variable,
+//! requests (method, URL, headers) and responses (status, headers, body) are logged with
+//! `log::debug!` under the `codedefender_api::http` target. Credentials in the
+//! `Authorization` and `Proxy-Authorization` headers are always redacted.
+
+use crate::ApiError;
+use reqwest::blocking::{RequestBuilder, Response};
+use reqwest::header::{AUTHORIZATION, HeaderMap, PROXY_AUTHORIZATION};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Environment variable enabling HTTP debug logging when set to anything but `0` or an
+/// empty string.
+pub const DEBUG_HTTP_ENV: &str = "CODEDEFENDER_DEBUG_HTTP";
+
+const TARGET: &str = "codedefender_api::http";
+
+/// Longest body logged, in bytes. Longer bodies are truncated.
+const MAX_LOGGED_BODY: usize = 4096;
+
+static DEBUG_HTTP: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables HTTP debug logging, on top of [`DEBUG_HTTP_ENV`].
+pub fn set_debug_http(enabled: bool) {
+    DEBUG_HTTP.store(enabled, Ordering::Relaxed);
+}
+
+/// The environment variable is read on every request, so it can be toggled at runtime.
+/// That is cheap next to the request itself.
+fn enabled() -> bool {
+    DEBUG_HTTP.load(Ordering::Relaxed)
+        || std::env::var(DEBUG_HTTP_ENV).is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Sends a request, logging it and the response status and headers when enabled.
+pub(crate) fn send(request: RequestBuilder) -> Result<Response, ApiError> {
+    if !enabled() {
+        return Ok(request.send()?);
+    }
+
+    let (client, request) = request.build_split();
+    let request = request?;
+    log::debug!(
+        target: TARGET,
+        "--> {} {} {}",
+        request.method(),
+        request.url(),
+        format_headers(request.headers())
+    );
+    let response = client.execute(request)?;
+    log::debug!(
+        target: TARGET,
+        "<-- {} {} {}",
+        response.status(),
+        response.url(),
+        format_headers(response.headers())
+    );
+    Ok(response)
+}
+
+/// Logs a response body when enabled. Binary bodies are logged by size only.
+pub(crate) fn log_body(body: &[u8]) {
+    if !enabled() {
+        return;
+    }
+    match std::str::from_utf8(body) {
+        Ok(text) if text.len() > MAX_LOGGED_BODY => log::debug!(
+            target: TARGET,
+            "<-- body ({} bytes, truncated): {}",
+            text.len(),
+            &text[..text.floor_char_boundary(MAX_LOGGED_BODY)]
+        ),
+        Ok(text) => log::debug!(target: TARGET, "<-- body: {}", text),
+        Err(_) => log::debug!(target: TARGET, "<-- body: {} bytes of binary data", body.len()),
+    }
+}
+
+/// Formats headers as `{name: value, ...}`, redacting credentials.
+fn format_headers(headers: &HeaderMap) -> String {
+    let fields: Vec<String> = headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if name == AUTHORIZATION || name == PROXY_AUTHORIZATION {
+                "<redacted>"
+            } else {
+                value.to_str().unwrap_or("<binary>")
+            };
+            format!("{}: {}", name, value)
+        })
+        .collect();
+    format!("{{{}}}", fields.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{CONTENT_TYPE, HeaderValue};
+
+    #[test]
+    fn credentials_are_redacted() {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("ApiKey secret-key"));
+        headers.insert(
+            PROXY_AUTHORIZATION,
+            HeaderValue::from_static("Basic cHJveHk6c2VjcmV0"),
+        );
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        let formatted = format_headers(&headers);
+
+        assert!(!formatted.contains("ApiKey"), "{formatted}");
+        assert!(!formatted.contains("secret"), "{formatted}");
+        assert!(!formatted.contains("cHJveHk6c2VjcmV0"), "{formatted}");
+        assert!(
+            formatted.contains("authorization: <redacted>"),
+            "{formatted}"
+        );
+        assert!(
+            formatted.contains("proxy-authorization: <redacted>"),
+            "{formatted}"
+        );
+        assert!(
+            formatted.contains("content-type: application/json"),
+            "{formatted}"
+        );
+    }
+
+    #[test]
+    fn binary_header_values_are_not_logged() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-raw", HeaderValue::from_bytes(b"\xff\xfe").unwrap());
+
+        assert_eq!(format_headers(&headers), "{x-raw: <binary>}");
+    }
+
+    #[test]
+    fn env_var_is_read_on_every_call() {
+        // SAFETY: no other unit test reads the environment or sends requests.
+        unsafe { std::env::set_var(DEBUG_HTTP_ENV, "1") };
+        assert!(enabled());
+        unsafe { std::env::set_var(DEBUG_HTTP_ENV, "0") };
+        assert!(!enabled());
+        unsafe { std::env::remove_var(DEBUG_HTTP_ENV) };
+        assert!(!enabled());
+    }
+}
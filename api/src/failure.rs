@@ -0,0 +1,71 @@
+//! Structured reasons for failed obfuscations.
+//!
+//! When an obfuscation cannot be started or fails on the server, the error response of
+//! [`crate::defend`] or [`crate::download`] may be a JSON [`DefendError`], such as
+//! `{"code": "unsupported_instruction", "message": "..."}`. For a 400, 422 or 500 status it
+//! is returned as [`crate::ApiError::Defend`] instead of a bare status code and body; other
+//! statuses, such as 401 or 429, stay [`crate::ApiError::Status`].
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Category of a server-side obfuscation failure.
+///
+/// New variants may be added in minor releases, so matches outside this crate need a
+/// wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum DefendErrorCode {
+    /// A protected function contains an instruction the obfuscator cannot handle.
+    UnsupportedInstruction,
+    /// Profiles apply conflicting passes or settings to the same symbol.
+    SymbolConflict,
+    /// The config was rejected, e.g. a pass is not available for the binary's environment.
+    InvalidConfig,
+    /// The obfuscator failed unexpectedly; retrying or reporting the execution ID may help.
+    InternalError,
+    /// A code this client does not know about. The message still describes the failure.
+    #[serde(other)]
+    Other,
+}
+
+impl fmt::Display for DefendErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DefendErrorCode::UnsupportedInstruction => "unsupported instruction",
+            DefendErrorCode::SymbolConflict => "symbol conflict",
+            DefendErrorCode::InvalidConfig => "invalid config",
+            DefendErrorCode::InternalError => "internal error",
+            DefendErrorCode::Other => "other error",
+        })
+    }
+}
+
+/// Why the server failed to obfuscate a binary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DefendError {
+    /// Category of the failure.
+    pub code: DefendErrorCode,
+    /// Human-readable details, e.g. the offending instruction and function.
+    pub message: String,
+}
+
+impl DefendError {
+    /// Parses a failure from an error response body, `None` if it is not a [`DefendError`].
+    pub fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok()
+    }
+}
+
+impl fmt::Display for DefendError {
+    /// Formats the error as `code: message`, or just the message for unknown codes.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.code {
+            DefendErrorCode::Other => write!(f, "{}", self.message.trim()),
+            code => write!(f, "{}: {}", code, self.message.trim()),
+        }
+    }
+}
+
+impl std::error::Error for DefendError {}
@@ -0,0 +1,172 @@
+//! Typed identifiers for uploaded files, obfuscation executions and idempotency keys.
+//!
+//! All are UUIDs on the wire, so keeping them apart in the type system stops a file ID from
+//! being passed where an execution ID is expected and vice versa.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Error returned when parsing a [`FileId`], [`ExecutionId`] or [`IdempotencyKey`] from a string that is not a
+/// UUID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseIdError(String);
+
+impl fmt::Display for ParseIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` is not a valid UUID", self.0)
+    }
+}
+
+impl std::error::Error for ParseIdError {}
+
+/// Returns whether `s` has the hyphenated `8-4-4-4-12` hex layout of a UUID.
+fn is_uuid(s: &str) -> bool {
+    const GROUP_LENS: [usize; 5] = [8, 4, 4, 4, 12];
+    let groups: Vec<&str> = s.split('-').collect();
+    groups.len() == GROUP_LENS.len()
+        && groups
+            .iter()
+            .zip(GROUP_LENS)
+            .all(|(group, len)| group.len() == len && group.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+macro_rules! uuid_newtype {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            /// Returns the ID as a string slice.
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseIdError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let s = s.trim();
+                if is_uuid(s) {
+                    Ok(Self(s.to_owned()))
+                } else {
+                    Err(ParseIdError(s.to_owned()))
+                }
+            }
+        }
+    };
+}
+
+uuid_newtype!(
+    /// UUID of a file uploaded with [`upload_file`](crate::upload_file).
+    FileId
+);
+
+uuid_newtype!(
+    /// UUID of an obfuscation started with [`defend`](crate::defend).
+    ExecutionId
+);
+
+uuid_newtype!(
+    /// Client-chosen key making [`defend_with`](crate::defend_with) safe to retry, see
+    /// [`DefendOptions::idempotency_key`](crate::DefendOptions::idempotency_key).
+    IdempotencyKey
+);
+
+impl FileId {
+    /// Wraps an ID returned by the server without validating it.
+    pub(crate) fn from_response(id: String) -> Self {
+        Self(id.trim().to_owned())
+    }
+}
+
+impl ExecutionId {
+    /// Wraps an ID returned by the server without validating it.
+    pub(crate) fn from_response(id: String) -> Self {
+        Self(id.trim().to_owned())
+    }
+}
+
+impl IdempotencyKey {
+    /// Generates a random (version 4) UUID to use as a key for a new job.
+    pub fn generate() -> Self {
+        Self(uuid::Uuid::new_v4().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_keys_are_distinct_v4_uuids() {
+        let first = IdempotencyKey::generate();
+        let second = IdempotencyKey::generate();
+        assert_ne!(first, second);
+        for key in [&first, &second] {
+            assert_eq!(key.as_str().parse::<IdempotencyKey>().as_ref(), Ok(key));
+            assert_eq!(&key.as_str()[14..15], "4");
+        }
+    }
+
+    const UUID: &str = "3f2504e0-4f89-11d3-9a0c-0305e82c3301";
+
+    #[test]
+    fn ids_serialize_as_plain_strings() {
+        let file: FileId = UUID.parse().unwrap();
+        let execution: ExecutionId = UUID.parse().unwrap();
+
+        let json = serde_json::to_string(&(&file, &execution)).unwrap();
+
+        assert_eq!(json, format!("[\"{UUID}\",\"{UUID}\"]"));
+        let (file_back, execution_back): (FileId, ExecutionId) =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(file_back, file);
+        assert_eq!(execution_back, execution);
+        assert_eq!(file_back.to_string(), UUID);
+    }
+
+    #[test]
+    fn ids_parse_trimmed_uuids_only() {
+        assert_eq!(
+            format!(" {UUID}\n")
+                .parse::<ExecutionId>()
+                .unwrap()
+                .as_str(),
+            UUID
+        );
+        for invalid in [
+            "",
+            "not-a-uuid",
+            "3f2504e0-4f89-11d3-9a0c-0305e82c330",
+            "3f2504e0_4f89_11d3_9a0c_0305e82c3301",
+        ] {
+            let error = invalid.parse::<FileId>().unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("`{invalid}` is not a valid UUID")
+            );
+        }
+    }
+
+    #[test]
+    fn equal_ids_share_a_map_entry() {
+        let mut executions: std::collections::HashMap<ExecutionId, FileId> = Default::default();
+        let file: FileId = UUID.parse().unwrap();
+
+        executions.insert(UUID.parse().unwrap(), file.clone());
+        executions.insert(format!(" {UUID} ").parse().unwrap(), file.clone());
+
+        assert_eq!(executions.len(), 1);
+        assert_eq!(executions.get(&UUID.parse().unwrap()), Some(&file));
+    }
+}
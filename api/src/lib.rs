@@ -5,65 +5,575 @@
 //!
 //! All endpoints require a valid API key, passed via the `Authorization` header
 //! using the `ApiKey` scheme.
+//!
+//! Requests go to [`DEFAULT_BASE_URL`] unless [`ClientConfig::base_url`] or the
+//! `CODEDEFENDER_API_URL` environment variable points elsewhere.
+//!
+//! With the `tracing` feature, uploads, analyses, obfuscation requests and download polls
+//! each run in a `tracing` span (`upload`, `analyze`, `defend`, `download`).
+//!
+//! Every HTTP exchange can be logged at debug level for troubleshooting, with credentials
+//! redacted; see [`set_debug_http`] and [`DEBUG_HTTP_ENV`].
 
-use codedefender_config::{AnalysisResult, CDConfig};
-use reqwest::{StatusCode, blocking::Client};
+use codedefender_config::{AnalysisResult, CDConfig, UploadedFile, VerifyResult};
+use reqwest::{
+    Certificate, Proxy, StatusCode,
+    blocking::{Client, RequestBuilder, Response},
+};
+use serde::de::DeserializeOwned;
 use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+mod artifact;
+mod debug;
+mod failure;
+mod ids;
+mod poll;
+
+pub use artifact::{Artifact, ArtifactManifest, ArtifactRole};
+pub use debug::{DEBUG_HTTP_ENV, set_debug_http};
+pub use failure::{DefendError, DefendErrorCode};
+pub use ids::{ExecutionId, FileId, IdempotencyKey, ParseIdError};
+pub use poll::{
+    Clock, MIN_POLL_INTERVAL, PollError, PollStrategy, SystemClock, poll_until_ready,
+    poll_until_ready_with,
+};
+
+/// Request header carrying [`DefendOptions::idempotency_key`].
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
 
-const UPLOAD_EP: &str = "https://app.codedefender.io/api/upload";
-const ANALYZE_EP: &str = "https://app.codedefender.io/api/analyze";
-const DEFEND_EP: &str = "https://app.codedefender.io/api/defend";
-const DOWNLOAD_EP: &str = "https://app.codedefender.io/api/download";
+/// Base URL of the CodeDefender SaaS API.
+pub const DEFAULT_BASE_URL: &str = "https://app.codedefender.io";
+
+/// Environment variable overriding [`DEFAULT_BASE_URL`], e.g. to target a staging or mock
+/// server. It is read by [`build_client`] when [`ClientConfig::base_url`] is not set.
+pub const BASE_URL_ENV: &str = "CODEDEFENDER_API_URL";
+
+const UPLOAD_EP: &str = "/api/upload";
+const ANALYZE_EP: &str = "/api/analyze";
+const DEFEND_EP: &str = "/api/defend";
+const DOWNLOAD_EP: &str = "/api/download";
+const CANCEL_EP: &str = "/api/cancel";
+const FILES_EP: &str = "/api/files";
+const VERIFY_EP: &str = "/api/verify";
+
+/// Proxy used for all requests made by a client built with [`build_client`].
+#[derive(Debug, Clone)]
+pub struct ProxyConfig {
+    /// Proxy URL. Both HTTP(S) (`http://host:port`) and SOCKS5 (`socks5://host:port`)
+    /// schemes are supported.
+    pub url: String,
+    /// Optional `(username, password)` pair used to authenticate with the proxy.
+    pub auth: Option<(String, String)>,
+}
+
+/// Settings used by [`build_client`] to construct an [`ApiClient`].
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    /// URL of the server to send requests to, e.g. a staging or mock server. When `None`,
+    /// the [`BASE_URL_ENV`] environment variable is used if set, else [`DEFAULT_BASE_URL`].
+    pub base_url: Option<String>,
+    /// Explicit proxy to route requests through. When `None`, the proxy settings from
+    /// the environment (`HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY`, `NO_PROXY`) are used.
+    pub proxy: Option<ProxyConfig>,
+    /// PEM file with an additional root certificate to trust, e.g. an internal CA used by
+    /// a TLS-intercepting proxy. The system roots stay trusted.
+    pub ca_cert: Option<PathBuf>,
+    /// **Dangerous.** Accept any TLS certificate, including expired, self-signed and
+    /// wrong-host ones. This makes connections open to interception and exposes the API
+    /// key; only use it for debugging. Prefer [`ClientConfig::ca_cert`].
+    pub danger_accept_invalid_certs: bool,
+}
+
+/// HTTP client passed to the API functions in this crate, bound to a server URL.
+///
+/// Build one with [`build_client`], or with [`ApiClient::new`] to reuse an existing
+/// `reqwest::blocking::Client`. Cloning is cheap and shares the connection pool.
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    http: Client,
+    base_url: String,
+}
+
+impl ApiClient {
+    /// Wraps `http`, sending requests to `base_url` (e.g. [`DEFAULT_BASE_URL`]).
+    pub fn new(http: Client, base_url: impl Into<String>) -> Self {
+        Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_owned(),
+        }
+    }
+
+    /// URL of the server requests are sent to, without a trailing slash.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// The underlying `reqwest` client.
+    pub fn http(&self) -> &Client {
+        &self.http
+    }
+
+    fn get(&self, path: &str) -> RequestBuilder {
+        self.http.get(self.endpoint(path))
+    }
+
+    fn put(&self, path: &str) -> RequestBuilder {
+        self.http.put(self.endpoint(path))
+    }
+
+    fn post(&self, path: &str) -> RequestBuilder {
+        self.http.post(self.endpoint(path))
+    }
+
+    /// Returns the full URL of an API endpoint.
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+}
+
+/// Options for [`analyze_program_with`]. The default analyzes like [`analyze_program`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnalyzeOptions {
+    /// Also report import and jump thunks as functions.
+    pub include_thunks: bool,
+    /// Leave out functions smaller than this many bytes.
+    pub min_function_size: Option<u32>,
+    /// Only analyze functions in these sections. Empty analyzes every section.
+    pub sections: Vec<String>,
+}
+
+impl AnalyzeOptions {
+    /// Returns the query parameters sent for the options that differ from the default, in
+    /// a fixed order. Equal options give equal parameters, so they can be used as a key.
+    pub fn query_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if self.include_thunks {
+            params.push(("includeThunks", "true".to_owned()));
+        }
+        if let Some(size) = self.min_function_size {
+            params.push(("minFunctionSize", size.to_string()));
+        }
+        if !self.sections.is_empty() {
+            params.push(("sections", self.sections.join(",")));
+        }
+        params
+    }
+}
+
+/// Options for [`defend_with`]. The default submits like [`defend`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DefendOptions {
+    /// Key identifying the logical job, sent in the [`IDEMPOTENCY_KEY_HEADER`] header.
+    ///
+    /// When a request with a key the server has already seen is received, the server
+    /// returns the execution ID of the first request instead of starting another
+    /// obfuscation. This makes it safe to retry a [`defend_with`] call that failed after
+    /// the server may have accepted it, e.g. on a client-side timeout, without running
+    /// (and paying for) the job twice.
+    ///
+    /// Generate a new key with [`IdempotencyKey::generate`] for every job, and reuse it
+    /// only to retry that job with the same file and config. The server forgets keys after
+    /// a while, so a key is only meant to cover retries shortly after the first attempt,
+    /// not to look up old executions.
+    pub idempotency_key: Option<IdempotencyKey>,
+}
+
+/// Client-side default for the maximum size of a file uploaded with [`upload_file`], in
+/// bytes (1 GiB).
+///
+/// This is not the server's limit, which is not documented; it only stops obviously
+/// oversized uploads before they are sent. The server may still reject smaller files with
+/// an [`ApiError::Status`] (usually 413), and [`upload_file_with_limit`] takes another limit.
+pub const DEFAULT_MAX_UPLOAD_SIZE: u64 = 1 << 30;
+
+/// Default maximum time [`wait_for_download`] waits for an obfuscation to complete.
+pub const DEFAULT_MAX_WAIT: Duration = Duration::from_secs(300);
 
 /// Represents the result of a call to [`download`].
+///
+/// New variants may be added in minor releases, so matches outside this crate need a
+/// wildcard arm. Without one, a match does not compile:
+///
+/// ```compile_fail,E0004
+/// # use codedefender_api::DownloadStatus;
+/// fn is_done(status: &DownloadStatus) -> bool {
+///     match status {
+///         DownloadStatus::Ready { .. } | DownloadStatus::Failed(_) => true,
+///         DownloadStatus::Processing { .. } => false,
+///     }
+/// }
+/// ```
+#[non_exhaustive]
 pub enum DownloadStatus {
-    /// The obfuscated file is ready and contains the returned bytes.
-    Ready(Vec<u8>),
+    /// The obfuscation is complete.
+    Ready {
+        /// Raw bytes of the archive returned by the server.
+        archive: Vec<u8>,
+        /// Description of the files in `archive`. [`download`] lists the entries of the
+        /// zip archive, and returns `None` if the archive is not a readable zip.
+        manifest: Option<ArtifactManifest>,
+    },
 
     /// The obfuscation is still in progress.
-    Processing,
+    Processing {
+        /// Completion percentage (0–100), if known. Always `None` from [`download`], since
+        /// the server does not report progress yet.
+        progress: Option<u8>,
+    },
+
+    /// The download failed due to a network or server error. If the obfuscation itself
+    /// failed, this is an [`ApiError::Defend`] with the server's reason.
+    Failed(ApiError),
+}
+
+impl DownloadStatus {
+    /// Returns the raw bytes of the archive if the obfuscation is complete.
+    pub fn archive(&self) -> Option<&[u8]> {
+        match self {
+            DownloadStatus::Ready { archive, .. } => Some(archive),
+            _ => None,
+        }
+    }
+}
+
+/// Errors returned by the API functions in this crate.
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request could not be sent or its response could not be read.
+    Http(reqwest::Error),
+    /// The response body is not the JSON the API documents.
+    Decode(serde_json::Error),
+    /// A local file needed to make the request, such as a CA certificate, could not be
+    /// read.
+    Io(std::io::Error),
+    /// The server responded with a non-success status code.
+    Status {
+        /// Status code of the response.
+        status: StatusCode,
+        /// Body of the response, which usually explains the failure (e.g. an invalid API
+        /// key or an unsupported file format). Empty if the server sent none.
+        body: String,
+    },
+    /// The file to upload is larger than the client-side maximum upload size, so it was
+    /// not sent.
+    TooLarge {
+        /// Size of the file, in bytes.
+        size: u64,
+        /// Maximum upload size, in bytes.
+        limit: u64,
+    },
+    /// The server could not obfuscate the binary, for the given reason.
+    Defend {
+        /// Status code of the response.
+        status: StatusCode,
+        /// Reason reported by the server.
+        error: DefendError,
+    },
+    /// The thread uploading the PDB panicked, so its upload may not have completed.
+    UploadPanicked,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Http(e) => write!(f, "request failed: {}", e),
+            ApiError::Decode(e) => write!(f, "unexpected response from server: {}", e),
+            ApiError::Io(e) => write!(f, "failed to read file: {}", e),
+            ApiError::Status { status, body } if body.trim().is_empty() => {
+                write!(f, "server responded with {}", status)
+            }
+            ApiError::Status { status, body } => {
+                write!(f, "server responded with {}: {}", status, body.trim())
+            }
+            ApiError::TooLarge { size, limit } => write!(
+                f,
+                "file is {} bytes, larger than the {} byte client-side upload limit",
+                size, limit
+            ),
+            ApiError::Defend { error, .. } => write!(f, "obfuscation failed: {}", error),
+            ApiError::UploadPanicked => write!(f, "PDB upload thread panicked"),
+        }
+    }
+}
 
-    /// The download failed due to a network or server error.
-    Failed(reqwest::Error),
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ApiError::Http(e) => Some(e),
+            ApiError::Decode(e) => Some(e),
+            ApiError::Io(e) => Some(e),
+            ApiError::Defend { error, .. } => Some(error),
+            ApiError::Status { .. } | ApiError::TooLarge { .. } | ApiError::UploadPanicked => None,
+        }
+    }
 }
 
-/// Uploads a binary file to CodeDefender and returns a UUID representing the uploaded file.
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        ApiError::Http(e)
+    }
+}
+
+impl From<std::io::Error> for ApiError {
+    fn from(e: std::io::Error) -> Self {
+        ApiError::Io(e)
+    }
+}
+
+/// Returns the response unchanged if its status is a success, otherwise an
+/// [`ApiError::Status`] carrying the response body.
+fn check_status(response: Response) -> Result<Response, ApiError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    // The status is the primary error; a body that can't be read is reported as empty.
+    let body = response.text().unwrap_or_default();
+    debug::log_body(body.as_bytes());
+    Err(ApiError::Status { status, body })
+}
+
+/// Turns an [`ApiError::Status`] whose body is a [`DefendError`] into an
+/// [`ApiError::Defend`], leaving other errors unchanged.
+///
+/// Only statuses reporting a rejected config or a failed obfuscation (400, 422 and 500)
+/// are parsed, so e.g. an invalid API key or a rate limit is never reported as a failed
+/// obfuscation, whatever its body.
+fn parse_defend_error(error: ApiError) -> ApiError {
+    match error {
+        ApiError::Status { status, body } if is_defend_failure(status) => {
+            match DefendError::from_json(&body) {
+                Some(error) => ApiError::Defend { status, error },
+                None => ApiError::Status { status, body },
+            }
+        }
+        error => error,
+    }
+}
+
+/// Whether `status` can carry a [`DefendError`].
+fn is_defend_failure(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_REQUEST
+            | StatusCode::UNPROCESSABLE_ENTITY
+            | StatusCode::INTERNAL_SERVER_ERROR
+    )
+}
+
+/// Reads a text response body.
+fn read_text(response: Response) -> Result<String, ApiError> {
+    let body = response.text()?;
+    debug::log_body(body.as_bytes());
+    Ok(body)
+}
+
+/// Reads and parses a JSON response body.
+fn read_json<T: DeserializeOwned>(response: Response) -> Result<T, ApiError> {
+    let body = response.bytes()?;
+    debug::log_body(&body);
+    serde_json::from_slice(&body).map_err(ApiError::Decode)
+}
+
+/// Builds a blocking client suitable for passing to the API functions in this crate.
+///
+/// # Arguments
+///
+/// * `config` - Client settings such as the server URL and the proxy to use.
+///
+/// # Errors
+///
+/// Returns an error if the proxy URL cannot be parsed, the CA certificate cannot be read
+/// or parsed, or the client fails to initialize.
+pub fn build_client(config: &ClientConfig) -> Result<ApiClient, ApiError> {
+    let mut builder = Client::builder();
+    if let Some(proxy_config) = &config.proxy {
+        let mut proxy = Proxy::all(&proxy_config.url)?;
+        if let Some((username, password)) = &proxy_config.auth {
+            proxy = proxy.basic_auth(username, password);
+        }
+        builder = builder.proxy(proxy);
+    }
+    if let Some(path) = &config.ca_cert {
+        let pem = std::fs::read(path)?;
+        builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+    }
+    if config.danger_accept_invalid_certs {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    let base_url = match &config.base_url {
+        Some(url) => url.clone(),
+        None => std::env::var(BASE_URL_ENV).unwrap_or_else(|_| DEFAULT_BASE_URL.to_owned()),
+    };
+    Ok(ApiClient::new(builder.build()?, base_url))
+}
+
+/// Uploads a binary file to CodeDefender and returns the [`FileId`] of the uploaded file.
+///
+/// Files larger than [`DEFAULT_MAX_UPLOAD_SIZE`] are rejected before sending; use
+/// [`upload_file_with_limit`] to change the limit.
 ///
 /// # Arguments
 ///
 /// * `file_bytes` - The raw contents of the binary file to upload.
-/// * `client` - A preconfigured `reqwest::blocking::Client`.
+/// * `client` - A client built with [`build_client`].
 /// * `api_key` - Your CodeDefender API key.
 ///
 /// # Returns
 ///
-/// A `Result<String, reqwest::Error>` containing the UUID on success, or an error if the upload failed.
+/// A `Result<FileId, ApiError>` containing the file ID on success, or an error if the upload failed.
 ///
 /// # Errors
 ///
 /// Returns an error if the request fails or if the server responds with a non-success status code (not in 200..=299).
+/// In the latter case the error is an [`ApiError::Status`] holding the server's explanation.
+/// Returns [`ApiError::TooLarge`] without sending anything if the file is over the limit.
 pub fn upload_file(
     file_bytes: Vec<u8>,
-    client: &Client,
+    client: &ApiClient,
     api_key: &str,
-) -> Result<String, reqwest::Error> {
-    let response = client
-        .put(UPLOAD_EP)
-        .header("Authorization", format!("ApiKey {}", api_key))
-        .header("Content-Type", "application/octet-stream")
-        .body(file_bytes)
-        .send()?;
+) -> Result<FileId, ApiError> {
+    upload_file_with_limit(file_bytes, DEFAULT_MAX_UPLOAD_SIZE, client, api_key)
+}
+
+/// Same as [`upload_file`], rejecting files larger than `max_size` bytes before sending
+/// them.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "upload", skip_all, fields(file_size = file_bytes.len()))
+)]
+pub fn upload_file_with_limit(
+    file_bytes: Vec<u8>,
+    max_size: u64,
+    client: &ApiClient,
+    api_key: &str,
+) -> Result<FileId, ApiError> {
+    let size = file_bytes.len() as u64;
+    if size > max_size {
+        return Err(ApiError::TooLarge {
+            size,
+            limit: max_size,
+        });
+    }
+
+    let response = debug::send(
+        client
+            .put(UPLOAD_EP)
+            .header("Authorization", format!("ApiKey {}", api_key))
+            .header("Content-Type", "application/octet-stream")
+            .body(file_bytes),
+    )?;
+
+    Ok(FileId::from_response(read_text(check_status(response)?)?))
+}
+
+/// Uploads a binary file and its optional PDB file concurrently.
+///
+/// Both uploads run on separate threads and this function returns once both have
+/// completed, so the total wait is roughly that of the larger file.
+///
+/// # Arguments
+///
+/// * `file_bytes` - The raw contents of the binary file to upload.
+/// * `pdb_bytes` - The raw contents of the associated PDB file, if any.
+/// * `client` - A client built with [`build_client`].
+/// * `api_key` - Your CodeDefender API key.
+///
+/// # Returns
+///
+/// A tuple of the binary's file ID and, if a PDB was given, the PDB's file ID.
+///
+/// # Errors
+///
+/// Returns the binary upload's error if it failed, otherwise the PDB upload's error.
+/// Both uploads are always awaited before returning.
+pub fn upload_files(
+    file_bytes: Vec<u8>,
+    pdb_bytes: Option<Vec<u8>>,
+    client: &ApiClient,
+    api_key: &str,
+) -> Result<(FileId, Option<FileId>), ApiError> {
+    std::thread::scope(|scope| {
+        let pdb_upload =
+            pdb_bytes.map(|bytes| scope.spawn(move || upload_file(bytes, client, api_key)));
+        let file_result = upload_file(file_bytes, client, api_key);
+        let pdb_result = pdb_upload
+            .map(|handle| handle.join().unwrap_or(Err(ApiError::UploadPanicked)))
+            .transpose();
+
+        Ok((file_result?, pdb_result?))
+    })
+}
+
+/// Asks the server whether an uploaded file is a PE image it can decode, which is much
+/// cheaper than finding out from [`analyze_program`].
+///
+/// # Arguments
+///
+/// * `file_id` - ID of the uploaded binary file.
+/// * `client` - A client built with [`build_client`].
+/// * `api_key` - Your CodeDefender API key.
+///
+/// # Returns
+///
+/// A [`VerifyResult`] with the detected environment and basic metadata, or the reason the
+/// file was rejected.
+///
+/// # Errors
+///
+/// Returns an error if the request fails, the server responds with a non-success status,
+/// or the response cannot be parsed. A file the server rejects is not an error; check
+/// [`VerifyResult::valid`]. A server without this endpoint responds with a 404
+/// [`ApiError::Status`], which callers can treat as "not supported".
+pub fn verify_upload(
+    file_id: &FileId,
+    client: &ApiClient,
+    api_key: &str,
+) -> Result<VerifyResult, ApiError> {
+    let response = debug::send(
+        client
+            .get(VERIFY_EP)
+            .header("Authorization", format!("ApiKey {}", api_key))
+            .query(&[("fileId", file_id.as_str())]),
+    )?;
+
+    read_json(check_status(response)?)
+}
+
+/// Lists the files previously uploaded with this API key.
+///
+/// The returned IDs can be passed to [`analyze_program`] and [`defend`] to reuse an
+/// upload instead of sending the same binary again.
+///
+/// # Arguments
+///
+/// * `client` - A client built with [`build_client`].
+/// * `api_key` - Your CodeDefender API key.
+///
+/// # Errors
+///
+/// Returns an error if the request fails, the server responds with a non-success status,
+/// or the response cannot be parsed.
+pub fn list_files(client: &ApiClient, api_key: &str) -> Result<Vec<UploadedFile>, ApiError> {
+    let response = debug::send(
+        client
+            .get(FILES_EP)
+            .header("Authorization", format!("ApiKey {}", api_key)),
+    )?;
 
-    response.text()
+    read_json(check_status(response)?)
 }
 
 /// Analyzes a previously uploaded binary file and optionally its PDB file.
 ///
 /// # Arguments
 ///
-/// * `file_id` - UUID of the uploaded binary file.
-/// * `pdb_file_id` - Optional UUID of the associated PDB file.
-/// * `client` - A preconfigured `reqwest::blocking::Client`.
+/// * `file_id` - ID of the uploaded binary file.
+/// * `pdb_file_id` - Optional ID of the associated PDB file.
+/// * `client` - A client built with [`build_client`].
 /// * `api_key` - Your CodeDefender API key.
 ///
 /// # Returns
@@ -72,70 +582,116 @@ pub fn upload_file(
 ///
 /// # Errors
 ///
-/// Returns an error if the request fails or the server responds with a non-success status.
-/// Panics if JSON deserialization fails (future versions should return a custom error instead).
+/// Returns an error if the request fails, the server responds with a non-success status, or
+/// the response does not match the [`AnalysisResult`] shape.
 pub fn analyze_program(
-    file_id: String,
-    pdb_file_id: Option<String>,
-    client: &Client,
+    file_id: FileId,
+    pdb_file_id: Option<FileId>,
+    client: &ApiClient,
+    api_key: &str,
+) -> Result<AnalysisResult, ApiError> {
+    analyze_program_with(
+        file_id,
+        pdb_file_id,
+        &AnalyzeOptions::default(),
+        client,
+        api_key,
+    )
+}
+
+/// Same as [`analyze_program`], with options tuning what gets analyzed.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "analyze", skip_all, fields(file_id = %file_id))
+)]
+pub fn analyze_program_with(
+    file_id: FileId,
+    pdb_file_id: Option<FileId>,
+    options: &AnalyzeOptions,
+    client: &ApiClient,
     api_key: &str,
-) -> Result<AnalysisResult, reqwest::Error> {
+) -> Result<AnalysisResult, ApiError> {
     let mut query_params = HashMap::new();
-    query_params.insert("fileId", file_id);
+    query_params.insert("fileId", file_id.to_string());
     if let Some(pdb_id) = pdb_file_id {
-        query_params.insert("pdbFileId", pdb_id);
+        query_params.insert("pdbFileId", pdb_id.to_string());
     }
+    query_params.extend(options.query_params());
 
-    let response = client
-        .put(ANALYZE_EP)
-        .header("Authorization", format!("ApiKey {}", api_key))
-        .query(&query_params)
-        .send()?
-        .error_for_status()?;
-
-    let result_bytes = response.bytes()?;
-    let analysis_result: AnalysisResult =
-        serde_json::from_slice(&result_bytes).expect("Failed to deserialize analysis result");
+    let response = debug::send(
+        client
+            .put(ANALYZE_EP)
+            .header("Authorization", format!("ApiKey {}", api_key))
+            .query(&query_params),
+    )?;
 
-    Ok(analysis_result)
+    read_json(check_status(response)?)
 }
 
 /// Starts the obfuscation process for a given file using the provided configuration.
 ///
 /// # Arguments
 ///
-/// * `uuid` - UUID of the uploaded binary file (not the PDB).
+/// * `file_id` - ID of the uploaded binary file (not the PDB).
 /// * `config` - Obfuscation configuration as a `CDConfig`.
-/// * `client` - A preconfigured `reqwest::blocking::Client`.
+/// * `client` - A client built with [`build_client`].
 /// * `api_key` - Your CodeDefender API key.
 ///
 /// # Returns
 ///
-/// A `Result<String, reqwest::Error>` containing the `execution_id` used for polling.
+/// A `Result<ExecutionId, ApiError>` containing the execution ID used for polling.
 ///
 /// # Errors
 ///
-/// Returns an error if the request fails or the server returns a non-success status.
+/// Returns an error if the request fails or the server returns a non-success status, as
+/// an [`ApiError::Defend`] when the server explains why it cannot obfuscate the binary.
 pub fn defend(
-    uuid: String,
+    file_id: FileId,
+    config: CDConfig,
+    client: &ApiClient,
+    api_key: &str,
+) -> Result<ExecutionId, ApiError> {
+    defend_with(file_id, config, &DefendOptions::default(), client, api_key)
+}
+
+/// Same as [`defend`], with options such as an idempotency key that makes retries safe.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "defend",
+        skip_all,
+        fields(
+            file_id = %file_id,
+            profile_count = config.profiles.len(),
+            symbol_count = config.profiles.iter().map(|p| p.symbols.len()).sum::<usize>(),
+        )
+    )
+)]
+pub fn defend_with(
+    file_id: FileId,
     config: CDConfig,
-    client: &Client,
+    options: &DefendOptions,
+    client: &ApiClient,
     api_key: &str,
-) -> Result<String, reqwest::Error> {
+) -> Result<ExecutionId, ApiError> {
     let body = serde_json::to_string(&config).expect("Failed to serialize CDConfig");
     let mut query_params = HashMap::new();
-    query_params.insert("fileId", uuid);
+    query_params.insert("fileId", file_id.to_string());
 
-    let response = client
+    let mut request = client
         .post(DEFEND_EP)
         .header("Authorization", format!("ApiKey {}", api_key))
         .header("Content-Type", "application/json")
         .query(&query_params)
-        .body(body)
-        .send()?
-        .error_for_status()?;
+        .body(body);
+    if let Some(key) = &options.idempotency_key {
+        request = request.header(IDEMPOTENCY_KEY_HEADER, key.as_str());
+    }
 
-    response.text()
+    let response = debug::send(request)?;
+    let response = check_status(response).map_err(parse_defend_error)?;
+
+    Ok(ExecutionId::from_response(read_text(response)?))
 }
 
 /// Polls the obfuscation status or retrieves the obfuscated file.
@@ -146,37 +702,117 @@ pub fn defend(
 ///
 /// # Arguments
 ///
-/// * `uuid` - The execution ID returned by [`defend`].
-/// * `client` - A preconfigured `reqwest::blocking::Client`.
+/// * `execution_id` - The execution ID returned by [`defend`].
+/// * `client` - A client built with [`build_client`].
 /// * `api_key` - Your CodeDefender API key.
 ///
 /// # Returns
 ///
 /// A [`DownloadStatus`] enum indicating whether the file is ready, still processing, or failed.
-pub fn download(uuid: String, client: &Client, api_key: &str) -> DownloadStatus {
+/// A failed obfuscation is reported as [`ApiError::Defend`] when the server gives a reason.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "download", skip_all, fields(execution_id = %execution_id))
+)]
+pub fn download(execution_id: ExecutionId, client: &ApiClient, api_key: &str) -> DownloadStatus {
     let mut query_params = HashMap::new();
-    query_params.insert("executionId", uuid);
+    query_params.insert("executionId", execution_id.to_string());
 
-    let response = client
-        .get(DOWNLOAD_EP)
-        .header("Authorization", format!("ApiKey {}", api_key))
-        .query(&query_params)
-        .send();
-
-    match response {
-        Ok(resp) => match resp.error_for_status() {
-            Ok(resp) => {
-                if resp.status() == StatusCode::ACCEPTED {
-                    DownloadStatus::Processing
-                } else {
-                    match resp.bytes() {
-                        Ok(bytes) => DownloadStatus::Ready(bytes.to_vec()),
-                        Err(e) => DownloadStatus::Failed(e),
+    let response = debug::send(
+        client
+            .get(DOWNLOAD_EP)
+            .header("Authorization", format!("ApiKey {}", api_key))
+            .query(&query_params),
+    );
+
+    match response.and_then(check_status).map_err(parse_defend_error) {
+        Ok(resp) => {
+            if resp.status() == StatusCode::ACCEPTED {
+                DownloadStatus::Processing { progress: None }
+            } else {
+                match resp.bytes() {
+                    Ok(bytes) => {
+                        debug::log_body(&bytes);
+                        DownloadStatus::Ready {
+                            manifest: ArtifactManifest::from_zip(&bytes),
+                            archive: bytes.to_vec(),
+                        }
                     }
+                    Err(e) => DownloadStatus::Failed(e.into()),
                 }
             }
-            Err(e) => DownloadStatus::Failed(e),
-        },
+        }
         Err(e) => DownloadStatus::Failed(e),
     }
 }
+
+/// Cancels an in-progress obfuscation so it stops consuming quota.
+///
+/// Cancelling an execution that has already finished is not an error.
+///
+/// # Arguments
+///
+/// * `execution_id` - The execution ID returned by [`defend`].
+/// * `client` - A client built with [`build_client`].
+/// * `api_key` - Your CodeDefender API key.
+///
+/// # Errors
+///
+/// Returns an error if the request fails or the server responds with a non-success status
+/// other than `409 Conflict` (execution already finished).
+pub fn cancel(
+    execution_id: ExecutionId,
+    client: &ApiClient,
+    api_key: &str,
+) -> Result<(), ApiError> {
+    let mut query_params = HashMap::new();
+    query_params.insert("executionId", execution_id.to_string());
+
+    let response = debug::send(
+        client
+            .post(CANCEL_EP)
+            .header("Authorization", format!("ApiKey {}", api_key))
+            .query(&query_params),
+    )?;
+
+    if response.status() == StatusCode::CONFLICT {
+        return Ok(());
+    }
+    check_status(response)?;
+    Ok(())
+}
+
+/// Polls [`download`] at a fixed interval until the obfuscated file is ready.
+///
+/// Use [`poll_until_ready`] directly for backoff and jitter.
+///
+/// # Arguments
+///
+/// * `execution_id` - The execution ID returned by [`defend`].
+/// * `poll_interval` - Delay between two polls. Should not be lower than 500 milliseconds.
+/// * `max_wait` - Total time to wait for the obfuscation before giving up,
+///   usually [`DEFAULT_MAX_WAIT`].
+/// * `client` - A client built with [`build_client`].
+/// * `api_key` - Your CodeDefender API key.
+///
+/// # Returns
+///
+/// The bytes of the archive returned by the server, with the list of its files (`None` if
+/// it is not a readable zip archive).
+///
+/// # Errors
+///
+/// Returns [`PollError::Failed`] if a poll fails, or [`PollError::TimedOut`] once `max_wait`
+/// has elapsed without the file becoming ready.
+pub fn wait_for_download(
+    execution_id: ExecutionId,
+    poll_interval: Duration,
+    max_wait: Duration,
+    client: &ApiClient,
+    api_key: &str,
+) -> Result<(Vec<u8>, Option<ArtifactManifest>), PollError> {
+    poll_until_ready(
+        || download(execution_id.clone(), client, api_key),
+        &PollStrategy::fixed(poll_interval, max_wait),
+    )
+}
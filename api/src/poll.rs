@@ -0,0 +1,488 @@
+//! Polling of long-running obfuscations.
+//!
+//! [`poll_until_ready`] drives any function returning a [`DownloadStatus`] until it reports
+//! the file as ready, sleeping between polls as configured by a [`PollStrategy`]. Time is
+//! read and spent through a [`Clock`], so callers can substitute their own (e.g. a fake clock
+//! that advances instantly).
+
+use crate::{ApiError, ArtifactManifest, DEFAULT_MAX_WAIT, DownloadStatus};
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::time::{Duration, Instant};
+
+/// How often and for how long [`poll_until_ready`] polls.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PollStrategy {
+    /// Delay before the second poll. Should not be lower than 500 milliseconds, as the
+    /// download endpoint is rate-limited.
+    pub interval: Duration,
+    /// Factor the delay is multiplied by after each poll. `1.0` polls at a fixed interval.
+    pub backoff: f64,
+    /// Upper bound for the delay between two polls once backoff has been applied.
+    pub max_interval: Duration,
+    /// Total time to wait for the file before giving up.
+    pub max_wait: Duration,
+    /// Fraction (`0.0..=1.0`) by which each delay is randomly lengthened or shortened, so
+    /// that many clients started together do not poll in lockstep.
+    pub jitter: f64,
+    /// Derive the delay from the progress the server reports, see
+    /// [`PollStrategy::adaptive`]. Polls without reported progress use the delay above.
+    ///
+    /// The server does not report progress yet, so this has no effect with
+    /// [`crate::download`].
+    pub adaptive: bool,
+}
+
+/// Shortest delay between two polls of an adaptive [`PollStrategy`], as required by the
+/// rate limit of the download endpoint.
+pub const MIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+impl Default for PollStrategy {
+    /// Polls every second, without backoff or jitter, for up to [`DEFAULT_MAX_WAIT`].
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            backoff: 1.0,
+            max_interval: Duration::from_secs(1),
+            max_wait: DEFAULT_MAX_WAIT,
+            jitter: 0.0,
+            adaptive: false,
+        }
+    }
+}
+
+impl PollStrategy {
+    /// Polls every `interval`, without backoff or jitter, for up to `max_wait`.
+    pub fn fixed(interval: Duration, max_wait: Duration) -> Self {
+        Self {
+            interval,
+            backoff: 1.0,
+            max_interval: interval,
+            max_wait,
+            jitter: 0.0,
+            adaptive: false,
+        }
+    }
+
+    /// Polls slowly while the server reports little progress and faster as it nears
+    /// completion, for up to `max_wait`.
+    ///
+    /// **This currently has no effect with [`crate::download`].** The server does not
+    /// report progress yet, so `download` always returns
+    /// `DownloadStatus::Processing { progress: None }` and this strategy polls every 2
+    /// seconds, like `PollStrategy::fixed(Duration::from_secs(2), max_wait)`. It only
+    /// adapts for poll functions that fill in [`DownloadStatus::Processing`]'s progress.
+    ///
+    /// Once two polls have reported progress, the next delay is half of the estimated
+    /// remaining time at the observed rate, between [`MIN_POLL_INTERVAL`] and 30 seconds.
+    /// Until then, or if no progress is reported, it polls every 2 seconds.
+    pub fn adaptive(max_wait: Duration) -> Self {
+        Self {
+            interval: Duration::from_secs(2),
+            backoff: 1.0,
+            max_interval: Duration::from_secs(30),
+            max_wait,
+            jitter: 0.0,
+            adaptive: true,
+        }
+    }
+
+    /// Returns the delay estimated from two progress samples, `None` if progress did not
+    /// advance between them.
+    fn adaptive_delay(
+        &self,
+        previous: (u8, Duration),
+        current: (u8, Duration),
+    ) -> Option<Duration> {
+        let advanced = current.0.checked_sub(previous.0).filter(|&d| d > 0)?;
+        let elapsed = current.1.saturating_sub(previous.1);
+        let remaining = elapsed.mul_f64(f64::from(100 - current.0) / f64::from(advanced));
+        Some(
+            (remaining / 2)
+                .min(self.max_interval.max(MIN_POLL_INTERVAL))
+                .max(MIN_POLL_INTERVAL),
+        )
+    }
+
+    /// Returns the delay to wait after the `attempt`-th poll (starting at 0), before jitter.
+    fn base_delay(&self, attempt: u32) -> Duration {
+        let factor = self
+            .backoff
+            .max(1.0)
+            .powi(attempt.min(i32::MAX as u32) as i32);
+        self.interval
+            .mul_f64(factor.min(u32::MAX as f64))
+            .min(self.max_interval.max(self.interval))
+    }
+}
+
+/// Source of time for [`poll_until_ready_with`].
+pub trait Clock {
+    /// Time elapsed since an arbitrary, fixed origin.
+    fn now(&self) -> Duration;
+    /// Blocks (or pretends to) for `duration`.
+    fn sleep(&mut self, duration: Duration);
+}
+
+/// [`Clock`] backed by [`Instant`] and [`std::thread::sleep`].
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// Errors returned by [`poll_until_ready`].
+#[derive(Debug)]
+pub enum PollError {
+    /// The download failed due to a network or server error, or the obfuscation itself
+    /// failed ([`ApiError::Defend`]).
+    Failed(ApiError),
+    /// The obfuscation did not complete within the given maximum wait.
+    TimedOut(Duration),
+}
+
+impl fmt::Display for PollError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PollError::Failed(e @ ApiError::Defend { .. }) => write!(f, "{}", e),
+            PollError::Failed(e) => write!(f, "download failed: {}", e),
+            PollError::TimedOut(max_wait) => write!(
+                f,
+                "obfuscation did not complete within {} seconds",
+                max_wait.as_secs()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PollError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PollError::Failed(e) => Some(e),
+            PollError::TimedOut(_) => None,
+        }
+    }
+}
+
+/// Calls `poll` until it returns [`DownloadStatus::Ready`], sleeping between calls as
+/// configured by `strategy`.
+///
+/// # Arguments
+///
+/// * `poll` - Function performing a single poll, usually a closure around [`crate::download`].
+/// * `strategy` - Interval, backoff, jitter and maximum wait to use.
+///
+/// # Returns
+///
+/// The bytes of the archive and its manifest, as returned in [`DownloadStatus::Ready`].
+///
+/// # Errors
+///
+/// Returns [`PollError::Failed`] if a poll fails, or [`PollError::TimedOut`] once
+/// `strategy.max_wait` has elapsed without the file becoming ready.
+pub fn poll_until_ready<F>(
+    poll: F,
+    strategy: &PollStrategy,
+) -> Result<(Vec<u8>, Option<ArtifactManifest>), PollError>
+where
+    F: FnMut() -> DownloadStatus,
+{
+    poll_until_ready_with(poll, strategy, &mut SystemClock::default())
+}
+
+/// Same as [`poll_until_ready`], reading and spending time through `clock`.
+pub fn poll_until_ready_with<F, C>(
+    mut poll: F,
+    strategy: &PollStrategy,
+    clock: &mut C,
+) -> Result<(Vec<u8>, Option<ArtifactManifest>), PollError>
+where
+    F: FnMut() -> DownloadStatus,
+    C: Clock + ?Sized,
+{
+    let start = clock.now();
+    let jitter_seed = RandomState::new();
+    let mut attempt = 0u32;
+    let mut last_progress: Option<(u8, Duration)> = None;
+    loop {
+        let progress = match poll() {
+            DownloadStatus::Ready { archive, manifest } => return Ok((archive, manifest)),
+            DownloadStatus::Failed(e) => return Err(PollError::Failed(e)),
+            DownloadStatus::Processing { progress } => progress,
+        };
+
+        let sample = progress.map(|progress| (progress, clock.now()));
+        let adaptive_delay = match (strategy.adaptive, last_progress, sample) {
+            (true, Some(previous), Some(current)) => strategy.adaptive_delay(previous, current),
+            _ => None,
+        };
+        // Keep the oldest sample of a progress value, so stalls lower the observed rate.
+        if sample.map(|(p, _)| p) != last_progress.map(|(p, _)| p) {
+            last_progress = sample.or(last_progress);
+        }
+
+        let mut delay = apply_jitter(
+            adaptive_delay.unwrap_or_else(|| strategy.base_delay(attempt)),
+            strategy.jitter,
+            jitter_seed.hash_one(attempt),
+        );
+        if strategy.adaptive {
+            delay = delay.max(MIN_POLL_INTERVAL);
+        }
+        if clock.now().saturating_sub(start) + delay > strategy.max_wait {
+            return Err(PollError::TimedOut(strategy.max_wait));
+        }
+        clock.sleep(delay);
+        attempt = attempt.saturating_add(1);
+    }
+}
+
+/// Scales `delay` by a factor in `1 - jitter..1 + jitter` derived from `random`. A jitter
+/// that is not finite is treated as `0.0`.
+fn apply_jitter(delay: Duration, jitter: f64, random: u64) -> Duration {
+    let jitter = if jitter.is_finite() {
+        jitter.clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    if jitter == 0.0 {
+        return delay;
+    }
+    let unit = random as f64 / u64::MAX as f64;
+    delay.mul_f64(1.0 + jitter * (2.0 * unit - 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Clock advancing only when slept on, recording every sleep.
+    #[derive(Default)]
+    struct FakeClock {
+        now: Duration,
+        sleeps: Vec<Duration>,
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Duration {
+            self.now
+        }
+
+        fn sleep(&mut self, duration: Duration) {
+            self.now += duration;
+            self.sleeps.push(duration);
+        }
+    }
+
+    /// Polls reporting `progress` in turn, then a ready archive.
+    fn progress_sequence(progress: &[Option<u8>]) -> impl FnMut() -> DownloadStatus + '_ {
+        let mut polls = progress.iter();
+        move || match polls.next() {
+            Some(&progress) => DownloadStatus::Processing { progress },
+            None => DownloadStatus::Ready {
+                archive: b"archive".to_vec(),
+                manifest: None,
+            },
+        }
+    }
+
+    fn secs(secs: f64) -> Duration {
+        Duration::from_secs_f64(secs)
+    }
+
+    #[test]
+    fn processing_then_ready_returns_the_archive() {
+        let mut clock = FakeClock::default();
+        let strategy = PollStrategy::fixed(Duration::from_secs(1), Duration::from_secs(60));
+
+        let (archive, manifest) =
+            poll_until_ready_with(progress_sequence(&[None, None]), &strategy, &mut clock).unwrap();
+
+        assert_eq!(archive, b"archive");
+        assert_eq!(manifest, None);
+        assert_eq!(clock.sleeps, [secs(1.0); 2]);
+    }
+
+    #[test]
+    fn processing_past_max_wait_times_out() {
+        let mut clock = FakeClock::default();
+        let strategy = PollStrategy::fixed(Duration::from_secs(1), Duration::from_secs(3));
+
+        let error = poll_until_ready_with(
+            || DownloadStatus::Processing { progress: None },
+            &strategy,
+            &mut clock,
+        )
+        .unwrap_err();
+
+        assert!(matches!(error, PollError::TimedOut(max_wait) if max_wait == secs(3.0)));
+        assert_eq!(clock.sleeps, [secs(1.0); 3]);
+    }
+
+    #[test]
+    fn failed_poll_stops_immediately() {
+        let mut clock = FakeClock::default();
+
+        let error = poll_until_ready_with(
+            || DownloadStatus::Failed(ApiError::UploadPanicked),
+            &PollStrategy::default(),
+            &mut clock,
+        )
+        .unwrap_err();
+
+        assert!(matches!(error, PollError::Failed(_)));
+        assert!(clock.sleeps.is_empty());
+    }
+
+    #[test]
+    fn backoff_grows_the_delay_up_to_max_interval() {
+        let mut clock = FakeClock::default();
+        let strategy = PollStrategy {
+            interval: Duration::from_secs(1),
+            backoff: 2.0,
+            max_interval: Duration::from_secs(5),
+            ..PollStrategy::default()
+        };
+
+        poll_until_ready_with(progress_sequence(&[None; 5]), &strategy, &mut clock).unwrap();
+
+        assert_eq!(
+            clock.sleeps,
+            [secs(1.0), secs(2.0), secs(4.0), secs(5.0), secs(5.0)]
+        );
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let delay = Duration::from_secs(10);
+        for random in [0, u64::MAX / 3, u64::MAX / 2, u64::MAX] {
+            let jittered = apply_jitter(delay, 0.2, random);
+            assert!(
+                jittered >= secs(8.0) && jittered <= secs(12.0),
+                "{jittered:?}"
+            );
+        }
+        assert_eq!(apply_jitter(delay, 0.0, u64::MAX), delay);
+    }
+
+    #[test]
+    fn non_finite_jitter_is_ignored() {
+        let delay = Duration::from_secs(10);
+        for jitter in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(apply_jitter(delay, jitter, u64::MAX), delay);
+        }
+
+        let mut clock = FakeClock::default();
+        let strategy = PollStrategy {
+            jitter: f64::NAN,
+            ..PollStrategy::fixed(Duration::from_secs(1), Duration::from_secs(60))
+        };
+        poll_until_ready_with(progress_sequence(&[None]), &strategy, &mut clock).unwrap();
+        assert_eq!(clock.sleeps, [secs(1.0)]);
+    }
+
+    #[test]
+    fn adaptive_polls_slowly_early_and_quickly_near_completion() {
+        let mut clock = FakeClock::default();
+        let strategy = PollStrategy::adaptive(Duration::from_secs(600));
+
+        let (archive, _) = poll_until_ready_with(
+            progress_sequence(&[Some(10), Some(20), Some(60), Some(95)]),
+            &strategy,
+            &mut clock,
+        )
+        .unwrap();
+
+        assert_eq!(archive, b"archive");
+        // 10% per 2s leaves 16s, 40% per 8s leaves 8s, 35% per 4s leaves ~0.6s; half of
+        // each, floored at the minimum interval.
+        assert_eq!(
+            clock.sleeps,
+            [secs(2.0), secs(8.0), secs(4.0), MIN_POLL_INTERVAL]
+        );
+    }
+
+    #[test]
+    fn adaptive_falls_back_to_the_interval_without_progress() {
+        let mut clock = FakeClock::default();
+        let strategy = PollStrategy::adaptive(Duration::from_secs(600));
+
+        poll_until_ready_with(
+            progress_sequence(&[None, None, None]),
+            &strategy,
+            &mut clock,
+        )
+        .unwrap();
+
+        assert_eq!(clock.sleeps, [secs(2.0); 3]);
+    }
+
+    #[test]
+    fn adaptive_without_progress_polls_like_a_fixed_strategy() {
+        let max_wait = Duration::from_secs(5);
+        let sleeps = |strategy: PollStrategy| {
+            let mut clock = FakeClock::default();
+            let result = poll_until_ready_with(
+                || DownloadStatus::Processing { progress: None },
+                &strategy,
+                &mut clock,
+            );
+            assert!(matches!(result, Err(PollError::TimedOut(_))));
+            clock.sleeps
+        };
+
+        assert_eq!(
+            sleeps(PollStrategy::adaptive(max_wait)),
+            sleeps(PollStrategy::fixed(secs(2.0), max_wait))
+        );
+    }
+
+    #[test]
+    fn adaptive_measures_stalls_from_the_first_sample() {
+        let mut clock = FakeClock::default();
+        let strategy = PollStrategy::adaptive(Duration::from_secs(600));
+
+        poll_until_ready_with(
+            progress_sequence(&[Some(10), Some(10), Some(20)]),
+            &strategy,
+            &mut clock,
+        )
+        .unwrap();
+
+        // 10% took 4s including the stall, leaving 32s.
+        assert_eq!(clock.sleeps, [secs(2.0), secs(2.0), secs(16.0)]);
+    }
+
+    #[test]
+    fn adaptive_delay_is_capped() {
+        let mut clock = FakeClock::default();
+        let strategy = PollStrategy::adaptive(Duration::from_secs(600));
+
+        poll_until_ready_with(
+            progress_sequence(&[Some(1), Some(2)]),
+            &strategy,
+            &mut clock,
+        )
+        .unwrap();
+
+        assert_eq!(clock.sleeps, [secs(2.0), secs(30.0)]);
+    }
+}
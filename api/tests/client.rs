@@ -0,0 +1,124 @@
+mod common;
+
+use codedefender_api::{ApiError, ClientConfig, ProxyConfig, build_client, list_files};
+use common::{API_KEY, MockResponse, MockServer};
+use std::path::PathBuf;
+
+fn config(url: &str, auth: Option<(&str, &str)>) -> ClientConfig {
+    ClientConfig {
+        base_url: Some("http://codedefender.invalid".to_owned()),
+        proxy: Some(ProxyConfig {
+            url: url.to_owned(),
+            auth: auth.map(|(user, password)| (user.to_owned(), password.to_owned())),
+        }),
+        ..ClientConfig::default()
+    }
+}
+
+// The mock server stands in for the proxy: a request routed through an HTTP proxy
+// carries the absolute URL of the real server.
+fn request_through_proxy(auth: Option<(&str, &str)>) -> common::Recorded {
+    let proxy = MockServer::start(|_| MockResponse::json(200, &serde_json::json!([])));
+    let client = build_client(&config(proxy.url(), auth)).unwrap();
+
+    list_files(&client, API_KEY).unwrap();
+
+    let requests = proxy.requests();
+    assert_eq!(requests.len(), 1);
+    requests[0].clone()
+}
+
+#[test]
+fn http_proxy_receives_the_request() {
+    let request = request_through_proxy(None);
+
+    assert_eq!(request.path, "http://codedefender.invalid/api/files");
+    assert_eq!(request.header("Proxy-Authorization"), None);
+}
+
+#[test]
+fn http_proxy_credentials_are_sent() {
+    let request = request_through_proxy(Some(("user", "secret")));
+
+    // base64("user:secret")
+    assert_eq!(
+        request.header("Proxy-Authorization"),
+        Some("Basic dXNlcjpzZWNyZXQ=")
+    );
+}
+
+#[test]
+fn socks5_proxy_builds() {
+    assert!(build_client(&config("socks5://127.0.0.1:1080", None)).is_ok());
+    assert!(build_client(&config("socks5://127.0.0.1:1080", Some(("user", "secret")))).is_ok());
+}
+
+#[test]
+fn invalid_proxy_url_is_an_error() {
+    let result = build_client(&config("http://[::1", None));
+
+    assert!(matches!(result, Err(ApiError::Http(_))), "{result:?}");
+}
+
+/// A self-signed CA certificate in PEM form.
+fn test_ca() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/test-ca.pem")
+}
+
+fn config_with_ca(ca_cert: PathBuf) -> ClientConfig {
+    ClientConfig {
+        ca_cert: Some(ca_cert),
+        ..ClientConfig::default()
+    }
+}
+
+#[test]
+fn custom_ca_cert_is_loaded() {
+    let server = MockServer::start(|_| MockResponse::json(200, &serde_json::json!([])));
+    let client = build_client(&ClientConfig {
+        base_url: Some(server.url().to_owned()),
+        ..config_with_ca(test_ca())
+    })
+    .unwrap();
+
+    list_files(&client, API_KEY).unwrap();
+
+    assert_eq!(server.requests_to("/api/files").len(), 1);
+}
+
+#[test]
+fn missing_ca_cert_is_an_io_error() {
+    let result = build_client(&config_with_ca(PathBuf::from("/nonexistent/ca.pem")));
+
+    match result {
+        Err(ApiError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn malformed_ca_cert_is_an_error() {
+    let dir = std::env::temp_dir().join(format!("codedefender-ca-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("ca.pem");
+    std::fs::write(
+        &path,
+        "-----BEGIN CERTIFICATE-----\nnot base64\n-----END CERTIFICATE-----\n",
+    )
+    .unwrap();
+
+    let result = build_client(&config_with_ca(path));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(matches!(result, Err(ApiError::Http(_))), "{result:?}");
+}
+
+#[test]
+fn accepting_invalid_certs_builds() {
+    let config = ClientConfig {
+        danger_accept_invalid_certs: true,
+        ..config_with_ca(test_ca())
+    };
+
+    assert!(build_client(&config).is_ok());
+}
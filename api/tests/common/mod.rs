@@ -0,0 +1,225 @@
+//! Fake CodeDefender server for integration tests.
+//!
+//! [`MockServer::start`] serves every request with a handler on a local port, recording the
+//! requests so tests can assert on their method, headers, query and body. Requests are
+//! handled concurrently, so handlers can sleep to simulate slow endpoints.
+
+#![allow(dead_code)]
+
+use codedefender_api::{ApiClient, ClientConfig, build_client};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// API key sent by the clients of [`MockServer::client`].
+pub const API_KEY: &str = "test-key";
+
+/// A request received by the mock server.
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Recorded {
+    /// Returns the first value of a header, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the first value of a query parameter.
+    pub fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// A response for the mock server to send.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub delay: Duration,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn json(status: u16, value: &serde_json::Value) -> Self {
+        Self::new(status, value.to_string()).with_header("Content-Type", "application/json")
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Waits this long before responding.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+type Handler = dyn Fn(&Recorded) -> MockResponse + Send + Sync;
+
+/// A local HTTP server answering requests with a handler, stopped when dropped.
+pub struct MockServer {
+    server: Arc<tiny_http::Server>,
+    requests: Arc<Mutex<Vec<Recorded>>>,
+    url: String,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Starts a server on a free local port.
+    pub fn start(handler: impl Fn(&Recorded) -> MockResponse + Send + Sync + 'static) -> Self {
+        let server = Arc::new(tiny_http::Server::http("127.0.0.1:0").expect("bind mock server"));
+        let url = format!("http://{}", server.server_addr());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let thread = {
+            let server = Arc::clone(&server);
+            let requests = Arc::clone(&requests);
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    let requests = Arc::clone(&requests);
+                    let handler = Arc::clone(&handler);
+                    std::thread::spawn(move || serve(request, &*handler, &requests));
+                }
+            })
+        };
+
+        Self {
+            server,
+            requests,
+            url,
+            thread: Some(thread),
+        }
+    }
+
+    /// Base URL of the server, e.g. `http://127.0.0.1:12345`.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns a client sending its requests to this server.
+    pub fn client(&self) -> ApiClient {
+        build_client(&ClientConfig {
+            base_url: Some(self.url.clone()),
+            ..ClientConfig::default()
+        })
+        .expect("build client")
+    }
+
+    /// Requests received so far, in the order their handling started.
+    pub fn requests(&self) -> Vec<Recorded> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Requests received so far for `path`.
+    pub fn requests_to(&self, path: &str) -> Vec<Recorded> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request.path == path)
+            .collect()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve(mut request: tiny_http::Request, handler: &Handler, requests: &Mutex<Vec<Recorded>>) {
+    let (path, query) = match request.url().split_once('?') {
+        Some((path, query)) => (path.to_owned(), parse_query(query)),
+        None => (request.url().to_owned(), Vec::new()),
+    };
+    let mut body = Vec::new();
+    let _ = request.as_reader().read_to_end(&mut body);
+    let recorded = Recorded {
+        method: request.method().as_str().to_owned(),
+        path,
+        query,
+        headers: request
+            .headers()
+            .iter()
+            .map(|h| {
+                (
+                    h.field.as_str().as_str().to_owned(),
+                    h.value.as_str().to_owned(),
+                )
+            })
+            .collect(),
+        body,
+    };
+    requests.lock().unwrap().push(recorded.clone());
+
+    let response = handler(&recorded);
+    std::thread::sleep(response.delay);
+    let mut reply = tiny_http::Response::from_data(response.body).with_status_code(response.status);
+    for (name, value) in &response.headers {
+        reply.add_header(
+            tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("header"),
+        );
+    }
+    let _ = request.respond(reply);
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        out.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                    Err(_) => out.push(b'%'),
+                }
+            }
+            b'+' => out.push(b' '),
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
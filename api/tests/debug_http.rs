@@ -0,0 +1,43 @@
+mod common;
+
+use codedefender_api::{set_debug_http, upload_file};
+use common::{API_KEY, MockResponse, MockServer};
+use std::sync::Mutex;
+
+// Collects every `codedefender_api::http` log line.
+struct CaptureLogger;
+
+static LOGGED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static LOGGER: CaptureLogger = CaptureLogger;
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target() == "codedefender_api::http"
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            LOGGED.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn logged_exchanges_never_contain_the_api_key() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Debug);
+    set_debug_http(true);
+    let server =
+        MockServer::start(|_| MockResponse::new(200, "11111111-2222-3333-4444-555555555555"));
+
+    upload_file(b"MZ".to_vec(), &server.client(), API_KEY).unwrap();
+
+    let logged = LOGGED.lock().unwrap().join("\n");
+    assert!(logged.contains("--> PUT"), "{logged}");
+    assert!(logged.contains("<-- 200"), "{logged}");
+    assert!(logged.contains("authorization: <redacted>"), "{logged}");
+    assert!(!logged.contains("ApiKey"), "{logged}");
+    assert!(!logged.contains(API_KEY), "{logged}");
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use codedefender_api::{ApiError, ExecutionId, FileId, cancel, list_files, verify_upload};
+use codedefender_config::{PeEnvironment, UploadedFile};
+use common::{API_KEY, MockResponse, MockServer};
+use serde_json::json;
+
+const FILE_ID: &str = "11111111-2222-3333-4444-555555555555";
+const EXECUTION_ID: &str = "66666666-7777-8888-9999-aaaaaaaaaaaa";
+
+fn file_id() -> FileId {
+    FILE_ID.parse().unwrap()
+}
+
+fn execution_id() -> ExecutionId {
+    EXECUTION_ID.parse().unwrap()
+}
+
+#[test]
+fn list_files_parses_the_uploaded_files() {
+    let server = MockServer::start(|_| {
+        MockResponse::json(
+            200,
+            &json!([
+                { "id": FILE_ID, "size": 4096, "uploaded_at": "2024-01-01T12:00:00Z" },
+                { "id": "22222222-3333-4444-5555-666666666666", "size": 1, "uploaded_at": "2024-01-02T00:00:00Z" }
+            ]),
+        )
+    });
+
+    let files = list_files(&server.client(), API_KEY).unwrap();
+
+    assert_eq!(files.len(), 2);
+    assert_eq!(
+        files[0],
+        UploadedFile {
+            id: FILE_ID.to_owned(),
+            size: 4096,
+            uploaded_at: "2024-01-01T12:00:00Z".to_owned(),
+        }
+    );
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].path, "/api/files");
+    assert_eq!(
+        requests[0].header("Authorization"),
+        Some(format!("ApiKey {}", API_KEY).as_str())
+    );
+}
+
+#[test]
+fn list_files_reports_errors() {
+    let server = MockServer::start(|request| match request.header("Authorization") {
+        Some("ApiKey bad-key") => MockResponse::new(401, "invalid API key"),
+        _ => MockResponse::json(200, &json!({ "files": [] })),
+    });
+
+    match list_files(&server.client(), "bad-key") {
+        Err(ApiError::Status { status, body }) => {
+            assert_eq!(status.as_u16(), 401);
+            assert_eq!(body, "invalid API key");
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert!(matches!(
+        list_files(&server.client(), API_KEY),
+        Err(ApiError::Decode(_))
+    ));
+}
+
+#[test]
+fn cancel_posts_the_execution_id() {
+    let server = MockServer::start(|_| MockResponse::new(200, ""));
+
+    cancel(execution_id(), &server.client(), API_KEY).unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].path, "/api/cancel");
+    assert_eq!(requests[0].query("executionId"), Some(EXECUTION_ID));
+    assert_eq!(
+        requests[0].header("Authorization"),
+        Some(format!("ApiKey {}", API_KEY).as_str())
+    );
+}
+
+#[test]
+fn cancelling_a_finished_execution_is_not_an_error() {
+    let server = MockServer::start(|_| MockResponse::new(409, "execution already finished"));
+
+    assert!(cancel(execution_id(), &server.client(), API_KEY).is_ok());
+}
+
+#[test]
+fn failed_cancel_is_an_error() {
+    let server = MockServer::start(|_| MockResponse::new(500, "internal error"));
+
+    assert!(matches!(
+        cancel(execution_id(), &server.client(), API_KEY),
+        Err(ApiError::Status { status, .. }) if status.as_u16() == 500
+    ));
+}
+
+#[test]
+fn verified_upload_carries_its_metadata() {
+    let server = MockServer::start(|_| {
+        MockResponse::json(
+            200,
+            &json!({
+                "valid": true,
+                "environment": "KernelMode",
+                "machine": "x86_64",
+                "image_size": 65536
+            }),
+        )
+    });
+
+    let result = verify_upload(&file_id(), &server.client(), API_KEY).unwrap();
+
+    assert!(result.valid);
+    assert_eq!(result.environment, Some(PeEnvironment::KernelMode));
+    assert_eq!(result.machine.as_deref(), Some("x86_64"));
+    assert_eq!(result.image_size, Some(65536));
+    assert_eq!(result.reason, None);
+    let requests = server.requests();
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].path, "/api/verify");
+    assert_eq!(requests[0].query("fileId"), Some(FILE_ID));
+}
+
+#[test]
+fn rejected_upload_is_not_an_error() {
+    let server = MockServer::start(|_| {
+        MockResponse::json(200, &json!({ "valid": false, "reason": "not a PE image" }))
+    });
+
+    let result = verify_upload(&file_id(), &server.client(), API_KEY).unwrap();
+
+    assert!(!result.valid);
+    assert_eq!(result.reason.as_deref(), Some("not a PE image"));
+    assert_eq!(result.environment, None);
+}
+
+#[test]
+fn missing_verify_endpoint_is_a_404_status() {
+    // Callers fall back to skipping verification on exactly this error.
+    let server = MockServer::start(|_| MockResponse::new(404, "not found"));
+
+    assert!(matches!(
+        verify_upload(&file_id(), &server.client(), API_KEY),
+        Err(ApiError::Status { status, .. }) if status.as_u16() == 404
+    ));
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use codedefender_api::{
+    ApiError, DefendErrorCode, ExecutionId, PollError, PollStrategy, defend, download,
+    poll_until_ready,
+};
+use codedefender_config::{CDConfig, CDModuleSettings};
+use common::{API_KEY, MockResponse, MockServer};
+use serde_json::json;
+use std::time::Duration;
+
+const FILE_ID: &str = "11111111-2222-3333-4444-555555555555";
+const EXECUTION_ID: &str = "66666666-7777-8888-9999-aaaaaaaaaaaa";
+
+fn config() -> CDConfig {
+    CDConfig {
+        module_settings: CDModuleSettings::default(),
+        profiles: Vec::new(),
+    }
+}
+
+fn failing_server(status: u16, code: &str) -> MockServer {
+    let body = json!({ "code": code, "message": "push rsp at 0x1400010A3 in main" });
+    MockServer::start(move |_| MockResponse::json(status, &body))
+}
+
+#[test]
+fn rejected_submission_reports_the_reason() {
+    let server = failing_server(422, "unsupported_instruction");
+
+    let error = defend(
+        FILE_ID.parse().unwrap(),
+        config(),
+        &server.client(),
+        API_KEY,
+    )
+    .unwrap_err();
+
+    match &error {
+        ApiError::Defend { status, error } => {
+            assert_eq!(status.as_u16(), 422);
+            assert_eq!(error.code, DefendErrorCode::UnsupportedInstruction);
+            assert_eq!(error.message, "push rsp at 0x1400010A3 in main");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    assert_eq!(
+        error.to_string(),
+        "obfuscation failed: unsupported instruction: push rsp at 0x1400010A3 in main"
+    );
+}
+
+#[test]
+fn failed_obfuscation_propagates_through_polling() {
+    let server = failing_server(500, "internal_error");
+    let client = server.client();
+    let execution_id: ExecutionId = EXECUTION_ID.parse().unwrap();
+    let poll = PollStrategy::fixed(Duration::from_millis(10), Duration::from_secs(5));
+
+    let error =
+        poll_until_ready(|| download(execution_id.clone(), &client, API_KEY), &poll).unwrap_err();
+
+    match &error {
+        PollError::Failed(ApiError::Defend { error, .. }) => {
+            assert_eq!(error.code, DefendErrorCode::InternalError)
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    assert!(
+        error
+            .to_string()
+            .starts_with("obfuscation failed: internal error")
+    );
+}
+
+#[test]
+fn unknown_codes_keep_the_message() {
+    let server = failing_server(400, "quota_exceeded");
+
+    let error = defend(
+        FILE_ID.parse().unwrap(),
+        config(),
+        &server.client(),
+        API_KEY,
+    )
+    .unwrap_err();
+
+    assert!(
+        matches!(&error, ApiError::Defend { error, .. } if error.code == DefendErrorCode::Other),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn other_statuses_are_not_obfuscation_failures() {
+    for status in [401, 429, 503] {
+        let server = failing_server(status, "invalid_config");
+
+        let error = defend(
+            FILE_ID.parse().unwrap(),
+            config(),
+            &server.client(),
+            API_KEY,
+        )
+        .unwrap_err();
+
+        assert!(
+            matches!(&error, ApiError::Status { status: s, .. } if s.as_u16() == status),
+            "{status}: {error:?}"
+        );
+    }
+}
+
+#[test]
+fn plain_text_failures_stay_status_errors() {
+    let server = MockServer::start(|_| MockResponse::new(500, "internal server error"));
+
+    let error = defend(
+        FILE_ID.parse().unwrap(),
+        config(),
+        &server.client(),
+        API_KEY,
+    )
+    .unwrap_err();
+
+    assert!(matches!(error, ApiError::Status { .. }), "{error:?}");
+}
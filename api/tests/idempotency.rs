@@ -0,0 +1,122 @@
+mod common;
+
+use codedefender_api::{
+    DefendOptions, IDEMPOTENCY_KEY_HEADER, IdempotencyKey, defend, defend_with,
+};
+use codedefender_config::{CDConfig, CDModuleSettings};
+use common::{API_KEY, MockResponse, MockServer};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+const FILE_ID: &str = "11111111-2222-3333-4444-555555555555";
+
+fn config() -> CDConfig {
+    CDConfig {
+        module_settings: CDModuleSettings::default(),
+        profiles: Vec::new(),
+    }
+}
+
+// Starts a server that answers a repeated idempotency key with the execution ID of the
+// first request carrying it, and counts the duplicates it recognized.
+fn deduplicating_server() -> (MockServer, Arc<AtomicUsize>) {
+    let duplicates = Arc::new(AtomicUsize::new(0));
+    let seen: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+    let next = AtomicUsize::new(0);
+    let server = {
+        let duplicates = Arc::clone(&duplicates);
+        MockServer::start(move |request| {
+            let fresh = || {
+                format!(
+                    "00000000-0000-4000-8000-{:012}",
+                    next.fetch_add(1, Ordering::SeqCst)
+                )
+            };
+            let id = match request.header(IDEMPOTENCY_KEY_HEADER) {
+                Some(key) => {
+                    let mut seen = seen.lock().unwrap();
+                    if let Some(id) = seen.get(key) {
+                        duplicates.fetch_add(1, Ordering::SeqCst);
+                        id.clone()
+                    } else {
+                        let id = fresh();
+                        seen.insert(key.to_owned(), id.clone());
+                        id
+                    }
+                }
+                None => fresh(),
+            };
+            MockResponse::new(200, id)
+        })
+    };
+    (server, duplicates)
+}
+
+#[test]
+fn retry_with_the_same_key_is_recognized_as_a_duplicate() {
+    let (server, duplicates) = deduplicating_server();
+    let client = server.client();
+    let options = DefendOptions {
+        idempotency_key: Some(IdempotencyKey::generate()),
+    };
+
+    let first = defend_with(
+        FILE_ID.parse().unwrap(),
+        config(),
+        &options,
+        &client,
+        API_KEY,
+    )
+    .unwrap();
+    let retry = defend_with(
+        FILE_ID.parse().unwrap(),
+        config(),
+        &options,
+        &client,
+        API_KEY,
+    )
+    .unwrap();
+
+    assert_eq!(first, retry);
+    assert_eq!(duplicates.load(Ordering::SeqCst), 1);
+    let key = options.idempotency_key.unwrap();
+    for request in server.requests_to("/api/defend") {
+        assert_eq!(request.header(IDEMPOTENCY_KEY_HEADER), Some(key.as_str()));
+    }
+}
+
+#[test]
+fn requests_without_or_with_different_keys_start_new_jobs() {
+    let (server, duplicates) = deduplicating_server();
+    let client = server.client();
+    let with_key = |key| DefendOptions {
+        idempotency_key: Some(key),
+    };
+
+    let first = defend_with(
+        FILE_ID.parse().unwrap(),
+        config(),
+        &with_key(IdempotencyKey::generate()),
+        &client,
+        API_KEY,
+    )
+    .unwrap();
+    let second = defend_with(
+        FILE_ID.parse().unwrap(),
+        config(),
+        &with_key(IdempotencyKey::generate()),
+        &client,
+        API_KEY,
+    )
+    .unwrap();
+    let keyless = defend(FILE_ID.parse().unwrap(), config(), &client, API_KEY).unwrap();
+
+    assert_ne!(first, second);
+    assert_ne!(second, keyless);
+    assert_eq!(duplicates.load(Ordering::SeqCst), 0);
+    assert_eq!(
+        server.requests_to("/api/defend")[2].header(IDEMPOTENCY_KEY_HEADER),
+        None
+    );
+}
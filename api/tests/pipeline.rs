@@ -0,0 +1,276 @@
+mod common;
+
+use codedefender_api::{
+    AnalyzeOptions, ApiError, ArtifactRole, ClientConfig, DownloadStatus, ExecutionId, PollError,
+    PollStrategy, analyze_program, analyze_program_with, build_client, defend, download,
+    poll_until_ready, upload_file, wait_for_download,
+};
+use codedefender_config::{CDConfig, CDModuleSettings, CDProfile, ObfuscationPass};
+use common::{API_KEY, MockResponse, MockServer};
+use serde_json::json;
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+const FILE_ID: &str = "11111111-2222-3333-4444-555555555555";
+const PDB_ID: &str = "22222222-3333-4444-5555-666666666666";
+const EXECUTION_ID: &str = "66666666-7777-8888-9999-aaaaaaaaaaaa";
+
+fn analysis_json() -> serde_json::Value {
+    json!({
+        "environment": "UserMode",
+        "functions": [
+            { "rva": 4096, "symbol": "main", "ref_count": 3 },
+            { "rva": 8192, "symbol": "helper", "ref_count": 1, "section": ".text" }
+        ],
+        "rejects": [],
+        "macros": []
+    })
+}
+
+fn config() -> CDConfig {
+    CDConfig {
+        module_settings: CDModuleSettings::default(),
+        profiles: vec![CDProfile {
+            name: "main".to_owned(),
+            passes: vec![ObfuscationPass::AntiEmulator],
+            compiler_settings: Default::default(),
+            symbols: vec![4096],
+        }],
+    }
+}
+
+fn fast_poll() -> PollStrategy {
+    PollStrategy::fixed(Duration::from_millis(10), Duration::from_secs(5))
+}
+
+#[test]
+fn full_pipeline_sends_documented_requests() {
+    let downloads = AtomicUsize::new(0);
+    let server = MockServer::start(move |request| match request.path.as_str() {
+        "/api/upload" => MockResponse::new(200, FILE_ID),
+        "/api/analyze" => MockResponse::json(200, &analysis_json()),
+        "/api/defend" => MockResponse::new(200, EXECUTION_ID),
+        "/api/download" if downloads.fetch_add(1, Ordering::SeqCst) == 0 => {
+            MockResponse::new(202, "")
+        }
+        "/api/download" => MockResponse::new(200, b"PK\x03\x04archive".to_vec()),
+        _ => MockResponse::new(404, "not found"),
+    });
+    let client = server.client();
+
+    let file_id = upload_file(b"MZ binary".to_vec(), &client, API_KEY).unwrap();
+    assert_eq!(file_id.as_str(), FILE_ID);
+    let analysis = analyze_program(file_id.clone(), None, &client, API_KEY).unwrap();
+    assert_eq!(analysis.functions.len(), 2);
+    assert_eq!(analysis.functions[1].section.as_deref(), Some(".text"));
+    let execution_id = defend(file_id, config(), &client, API_KEY).unwrap();
+    assert_eq!(execution_id.as_str(), EXECUTION_ID);
+    let (archive, manifest) = poll_until_ready(
+        || download(execution_id.clone(), &client, API_KEY),
+        &fast_poll(),
+    )
+    .unwrap();
+    assert_eq!(archive, b"PK\x03\x04archive");
+    assert_eq!(manifest, None);
+
+    let requests = server.requests();
+    for request in &requests {
+        assert_eq!(request.header("Authorization"), Some("ApiKey test-key"));
+    }
+
+    let upload = &server.requests_to("/api/upload")[0];
+    assert_eq!(upload.method, "PUT");
+    assert_eq!(
+        upload.header("Content-Type"),
+        Some("application/octet-stream")
+    );
+    assert_eq!(upload.body, b"MZ binary");
+
+    let analyze = &server.requests_to("/api/analyze")[0];
+    assert_eq!(analyze.method, "PUT");
+    assert_eq!(analyze.query("fileId"), Some(FILE_ID));
+    assert_eq!(analyze.query("pdbFileId"), None);
+
+    let defend_request = &server.requests_to("/api/defend")[0];
+    assert_eq!(defend_request.method, "POST");
+    assert_eq!(defend_request.query("fileId"), Some(FILE_ID));
+    assert_eq!(
+        defend_request.header("Content-Type"),
+        Some("application/json")
+    );
+    let sent: CDConfig = serde_json::from_slice(&defend_request.body).unwrap();
+    assert_eq!(sent, config());
+
+    let polls = server.requests_to("/api/download");
+    assert_eq!(polls.len(), 2);
+    for poll in &polls {
+        assert_eq!(poll.method, "GET");
+        assert_eq!(poll.query("executionId"), Some(EXECUTION_ID));
+    }
+}
+
+#[test]
+fn rejected_api_key_is_reported_with_the_server_message() {
+    let server = MockServer::start(|_| MockResponse::new(401, "invalid API key"));
+    let client = server.client();
+
+    let error = analyze_program(FILE_ID.parse().unwrap(), None, &client, API_KEY).unwrap_err();
+    match error {
+        ApiError::Status { status, body } => {
+            assert_eq!(status.as_u16(), 401);
+            assert_eq!(body, "invalid API key");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn malformed_analysis_is_a_decode_error() {
+    let server = MockServer::start(|_| MockResponse::json(200, &json!({ "functions": 3 })));
+    let client = server.client();
+
+    let error = analyze_program(FILE_ID.parse().unwrap(), None, &client, API_KEY).unwrap_err();
+    assert!(matches!(error, ApiError::Decode(_)), "{error:?}");
+}
+
+#[test]
+fn failed_download_stops_polling() {
+    let server = MockServer::start(|_| MockResponse::new(503, "maintenance"));
+    let client = server.client();
+    let execution_id: ExecutionId = EXECUTION_ID.parse().unwrap();
+
+    assert!(matches!(
+        download(EXECUTION_ID.parse().unwrap(), &client, API_KEY),
+        DownloadStatus::Failed(ApiError::Status { .. })
+    ));
+    let error = poll_until_ready(
+        || download(execution_id.clone(), &client, API_KEY),
+        &fast_poll(),
+    );
+    assert!(matches!(error, Err(PollError::Failed(_))));
+}
+
+#[test]
+fn download_that_never_finishes_times_out() {
+    let server = MockServer::start(|_| MockResponse::new(202, ""));
+    let client = server.client();
+    let poll_interval = Duration::from_millis(20);
+    let max_wait = Duration::from_millis(200);
+
+    let started = std::time::Instant::now();
+    let result = wait_for_download(
+        EXECUTION_ID.parse().unwrap(),
+        poll_interval,
+        max_wait,
+        &client,
+        API_KEY,
+    );
+
+    assert!(
+        matches!(result, Err(PollError::TimedOut(waited)) if waited == max_wait),
+        "{result:?}"
+    );
+    // It gives up once the next poll would land past `max_wait`.
+    assert!(started.elapsed() >= max_wait - poll_interval);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    let polls = server.requests_to("/api/download");
+    assert!(polls.len() >= 2, "{} polls", polls.len());
+    assert!(
+        polls
+            .iter()
+            .all(|r| r.query("executionId") == Some(EXECUTION_ID))
+    );
+}
+
+#[test]
+fn base_url_is_per_client() {
+    let first = MockServer::start(|_| MockResponse::new(200, FILE_ID));
+    let second = MockServer::start(|_| MockResponse::new(200, FILE_ID));
+    let client = build_client(&ClientConfig {
+        base_url: Some(format!("{}/", second.url())),
+        ..ClientConfig::default()
+    })
+    .unwrap();
+
+    upload_file(b"MZ".to_vec(), &client, API_KEY).unwrap();
+    assert!(first.requests().is_empty());
+    assert_eq!(second.requests_to("/api/upload").len(), 1);
+}
+
+#[test]
+fn analyze_options_are_sent_as_query_params() {
+    let server = MockServer::start(|_| MockResponse::json(200, &analysis_json()));
+    let options = AnalyzeOptions {
+        include_thunks: true,
+        min_function_size: Some(16),
+        sections: vec![".text".to_owned(), ".code".to_owned()],
+    };
+
+    analyze_program_with(
+        FILE_ID.parse().unwrap(),
+        Some(PDB_ID.parse().unwrap()),
+        &options,
+        &server.client(),
+        API_KEY,
+    )
+    .unwrap();
+
+    let request = &server.requests_to("/api/analyze")[0];
+    assert_eq!(request.query("fileId"), Some(FILE_ID));
+    assert_eq!(request.query("pdbFileId"), Some(PDB_ID));
+    assert_eq!(request.query("includeThunks"), Some("true"));
+    assert_eq!(request.query("minFunctionSize"), Some("16"));
+    assert_eq!(request.query("sections"), Some(".text,.code"));
+}
+
+#[test]
+fn default_analyze_options_send_no_extra_params() {
+    let server = MockServer::start(|_| MockResponse::json(200, &analysis_json()));
+
+    analyze_program_with(
+        FILE_ID.parse().unwrap(),
+        None,
+        &AnalyzeOptions::default(),
+        &server.client(),
+        API_KEY,
+    )
+    .unwrap();
+
+    let request = &server.requests_to("/api/analyze")[0];
+    assert_eq!(request.query.len(), 1);
+    assert_eq!(request.query("fileId"), Some(FILE_ID));
+}
+
+#[test]
+fn downloaded_archive_comes_with_its_file_list() {
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options =
+        zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    for name in ["app.exe", "app.pdb"] {
+        writer.start_file(name, options).unwrap();
+        writer.write_all(b"contents").unwrap();
+    }
+    let archive = writer.finish().unwrap().into_inner();
+    let server = {
+        let archive = archive.clone();
+        MockServer::start(move |_| MockResponse::new(200, archive.clone()))
+    };
+
+    let DownloadStatus::Ready {
+        archive: downloaded,
+        manifest,
+    } = download(EXECUTION_ID.parse().unwrap(), &server.client(), API_KEY)
+    else {
+        panic!("download is not ready");
+    };
+
+    assert_eq!(downloaded, archive);
+    let manifest = manifest.unwrap();
+    assert_eq!(manifest.files.len(), 2);
+    assert_eq!(manifest.find(ArtifactRole::Binary).unwrap().name, "app.exe");
+    assert_eq!(
+        manifest.find(ArtifactRole::Symbols).unwrap().name,
+        "app.pdb"
+    );
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use codedefender_api::{
+    ApiError, DEFAULT_MAX_UPLOAD_SIZE, upload_file, upload_file_with_limit, upload_files,
+};
+use common::{API_KEY, MockResponse, MockServer};
+use std::time::{Duration, Instant};
+
+const FILE_ID: &str = "11111111-2222-3333-4444-555555555555";
+const PDB_ID: &str = "22222222-3333-4444-5555-666666666666";
+
+// Answers binary uploads (starting with `MZ`) and PDB uploads with different IDs.
+fn upload_server(delay: Duration, pdb_status: u16) -> MockServer {
+    MockServer::start(move |request| {
+        let response = if request.body.starts_with(b"MZ") {
+            MockResponse::new(200, FILE_ID)
+        } else if pdb_status == 200 {
+            MockResponse::new(200, PDB_ID)
+        } else {
+            MockResponse::new(pdb_status, "bad pdb")
+        };
+        response.with_delay(delay)
+    })
+}
+
+#[test]
+fn binary_and_pdb_are_uploaded_concurrently() {
+    let delay = Duration::from_millis(500);
+    let server = upload_server(delay, 200);
+    let client = server.client();
+
+    let start = Instant::now();
+    let (file_id, pdb_id) =
+        upload_files(b"MZ".to_vec(), Some(b"PDB".to_vec()), &client, API_KEY).unwrap();
+    let elapsed = start.elapsed();
+
+    assert_eq!(file_id.as_str(), FILE_ID);
+    assert_eq!(pdb_id.unwrap().as_str(), PDB_ID);
+    assert_eq!(server.requests_to("/api/upload").len(), 2);
+    // Sequential uploads would take at least twice the delay.
+    assert!(elapsed >= delay, "{elapsed:?}");
+    assert!(elapsed < delay * 2, "{elapsed:?}");
+}
+
+#[test]
+fn without_pdb_only_the_binary_is_uploaded() {
+    let server = upload_server(Duration::ZERO, 200);
+
+    let (file_id, pdb_id) = upload_files(b"MZ".to_vec(), None, &server.client(), API_KEY).unwrap();
+
+    assert_eq!(file_id.as_str(), FILE_ID);
+    assert_eq!(pdb_id, None);
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn failed_pdb_upload_is_reported() {
+    let server = upload_server(Duration::ZERO, 413);
+
+    let error = upload_files(
+        b"MZ".to_vec(),
+        Some(b"PDB".to_vec()),
+        &server.client(),
+        API_KEY,
+    )
+    .unwrap_err();
+
+    assert!(
+        matches!(&error, ApiError::Status { status, .. } if status.as_u16() == 413),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn oversized_payload_is_rejected_before_sending() {
+    let server = upload_server(Duration::ZERO, 200);
+
+    let error = upload_file_with_limit(vec![0; 1025], 1024, &server.client(), API_KEY).unwrap_err();
+
+    assert!(
+        matches!(
+            error,
+            ApiError::TooLarge {
+                size: 1025,
+                limit: 1024
+            }
+        ),
+        "{error:?}"
+    );
+    assert_eq!(
+        error.to_string(),
+        "file is 1025 bytes, larger than the 1024 byte client-side upload limit"
+    );
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn payload_at_the_limit_is_sent() {
+    let server = upload_server(Duration::ZERO, 200);
+    let mut payload = b"MZ".to_vec();
+    payload.resize(1024, 0);
+
+    upload_file_with_limit(payload, 1024, &server.client(), API_KEY).unwrap();
+
+    assert_eq!(server.requests_to("/api/upload")[0].body.len(), 1024);
+}
+
+#[test]
+fn default_limit_applies_to_upload_file() {
+    let server = upload_server(Duration::ZERO, 200);
+    // Zeroed allocations are lazily backed, so this does not touch 1 GiB of memory.
+    let payload = vec![0; DEFAULT_MAX_UPLOAD_SIZE as usize + 1];
+
+    let error = upload_file(payload, &server.client(), API_KEY).unwrap_err();
+
+    assert!(
+        matches!(error, ApiError::TooLarge { limit, .. } if limit == DEFAULT_MAX_UPLOAD_SIZE),
+        "{error:?}"
+    );
+    assert!(server.requests().is_empty());
+}
@@ -0,0 +1,52 @@
+//! Canonical serialization of [`CDConfig`], for hashing and caching.
+
+use crate::{CDConfig, ConfigError};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+impl CDConfig {
+    /// Serializes the config into a canonical form: compact JSON with the keys of every
+    /// object sorted. Two configs that compare equal always produce the same bytes,
+    /// whatever order their fields or maps were built in.
+    ///
+    /// The format is stable across patch versions of this crate, so the bytes (and
+    /// [`CDConfig::config_hash`]) can be used as persistent cache keys. Adding a config
+    /// field changes the output, which only happens in minor or major versions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Json`] if the config cannot be serialized to JSON.
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, ConfigError> {
+        let value = serde_json::to_value(self)?;
+        Ok(serde_json::to_vec(&sort_keys(value))?)
+    }
+
+    /// SHA-256 of [`CDConfig::canonical_bytes`], identifying the effective config.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Json`] if the config cannot be serialized to JSON.
+    pub fn config_hash(&self) -> Result<[u8; 32], ConfigError> {
+        Ok(Sha256::digest(self.canonical_bytes()?).into())
+    }
+}
+
+/// Rebuilds every object with its keys inserted in sorted order. `serde_json` keeps
+/// insertion order when its `preserve_order` feature is enabled anywhere in the
+/// dependency graph, so the sort can't be left to `Map`.
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        other => other,
+    }
+}
@@ -0,0 +1,240 @@
+//! Structural comparison of two [`CDConfig`]s.
+
+use crate::{
+    AntiTamperSettings, CDConfig, CDModuleSettings, CDProfile, CustomSectionName, FakePdbString,
+    ObfuscationPass,
+};
+use std::collections::HashSet;
+use std::fmt;
+
+/// A single structural difference between two configs, as reported by [`diff_configs`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigDiff {
+    /// A module setting has a different value. Holds the setting's field path, e.g.
+    /// `custom_section_name.value`.
+    ModuleSettingChanged(String),
+    /// A profile only exists in the second config.
+    ProfileAdded(String),
+    /// A profile only exists in the first config.
+    ProfileRemoved(String),
+    /// A pass only exists in the second config's profile.
+    PassAdded {
+        profile: String,
+        pass: ObfuscationPass,
+    },
+    /// A pass only exists in the first config's profile.
+    PassRemoved {
+        profile: String,
+        pass: ObfuscationPass,
+    },
+    /// Both profiles contain the same passes, but in a different order.
+    PassOrderChanged { profile: String },
+    /// The profile's compiler settings differ.
+    CompilerSettingsChanged { profile: String },
+    /// Symbol RVAs only targeted by the second config's profile.
+    SymbolsAdded { profile: String, rvas: Vec<u64> },
+    /// Symbol RVAs only targeted by the first config's profile.
+    SymbolsRemoved { profile: String, rvas: Vec<u64> },
+}
+
+impl fmt::Display for ConfigDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigDiff::ModuleSettingChanged(field) => {
+                write!(f, "module setting `{}` changed", field)
+            }
+            ConfigDiff::ProfileAdded(name) => write!(f, "profile `{}` added", name),
+            ConfigDiff::ProfileRemoved(name) => write!(f, "profile `{}` removed", name),
+            ConfigDiff::PassAdded { profile, pass } => {
+                write!(f, "profile `{}`: pass {} added", profile, pass)
+            }
+            ConfigDiff::PassRemoved { profile, pass } => {
+                write!(f, "profile `{}`: pass {} removed", profile, pass)
+            }
+            ConfigDiff::PassOrderChanged { profile } => {
+                write!(f, "profile `{}`: pass order changed", profile)
+            }
+            ConfigDiff::CompilerSettingsChanged { profile } => {
+                write!(f, "profile `{}`: compiler settings changed", profile)
+            }
+            ConfigDiff::SymbolsAdded { profile, rvas } => {
+                write!(f, "profile `{}`: {} symbol(s) added", profile, rvas.len())
+            }
+            ConfigDiff::SymbolsRemoved { profile, rvas } => {
+                write!(f, "profile `{}`: {} symbol(s) removed", profile, rvas.len())
+            }
+        }
+    }
+}
+
+/// Compares two configs and reports how `b` differs from `a`.
+///
+/// Profiles are matched by name. Module settings are compared field by field, including
+/// the ones that are not sent to the API. Identical configs produce an empty list.
+pub fn diff_configs(a: &CDConfig, b: &CDConfig) -> Vec<ConfigDiff> {
+    let mut diffs = Vec::new();
+
+    diff_module_settings(&a.module_settings, &b.module_settings, &mut diffs);
+
+    for profile_a in &a.profiles {
+        match b.profiles.iter().find(|p| p.name == profile_a.name) {
+            Some(profile_b) => diff_profiles(profile_a, profile_b, &mut diffs),
+            None => diffs.push(ConfigDiff::ProfileRemoved(profile_a.name.clone())),
+        }
+    }
+    for profile_b in &b.profiles {
+        if !a.profiles.iter().any(|p| p.name == profile_b.name) {
+            diffs.push(ConfigDiff::ProfileAdded(profile_b.name.clone()));
+        }
+    }
+
+    diffs
+}
+
+fn diff_module_settings(a: &CDModuleSettings, b: &CDModuleSettings, diffs: &mut Vec<ConfigDiff>) {
+    let mut changed = |field: &str, differs: bool| {
+        if differs {
+            diffs.push(ConfigDiff::ModuleSettingChanged(field.to_owned()));
+        }
+    };
+
+    // Destructured without `..`, so a new field does not compile until it is compared here.
+    let CDModuleSettings {
+        ida_crasher,
+        import_protection,
+        obscure_entry_point,
+        clear_unwind_info,
+        tls_callback_protection,
+        fake_pdb_string:
+            FakePdbString {
+                enabled: pdb_enabled,
+                value: pdb_value,
+            },
+        custom_section_name:
+            CustomSectionName {
+                enabled: section_enabled,
+                value: section_value,
+                allow_standard_name,
+            },
+        anti_tamper:
+            AntiTamperSettings {
+                enabled: anti_tamper_enabled,
+                anti_debug,
+                anti_vm,
+                crash_on_detection,
+            },
+        max_section_entropy,
+        pad_sections,
+        min_cpu_features,
+    } = a;
+
+    changed("ida_crasher", *ida_crasher != b.ida_crasher);
+    changed(
+        "import_protection",
+        *import_protection != b.import_protection,
+    );
+    changed(
+        "obscure_entry_point",
+        *obscure_entry_point != b.obscure_entry_point,
+    );
+    changed(
+        "clear_unwind_info",
+        *clear_unwind_info != b.clear_unwind_info,
+    );
+    changed(
+        "tls_callback_protection",
+        *tls_callback_protection != b.tls_callback_protection,
+    );
+    changed(
+        "fake_pdb_string.enabled",
+        *pdb_enabled != b.fake_pdb_string.enabled,
+    );
+    changed(
+        "fake_pdb_string.value",
+        *pdb_value != b.fake_pdb_string.value,
+    );
+    changed(
+        "custom_section_name.enabled",
+        *section_enabled != b.custom_section_name.enabled,
+    );
+    changed(
+        "custom_section_name.value",
+        *section_value != b.custom_section_name.value,
+    );
+    changed(
+        "custom_section_name.allow_standard_name",
+        *allow_standard_name != b.custom_section_name.allow_standard_name,
+    );
+    changed(
+        "anti_tamper.enabled",
+        *anti_tamper_enabled != b.anti_tamper.enabled,
+    );
+    changed(
+        "anti_tamper.anti_debug",
+        *anti_debug != b.anti_tamper.anti_debug,
+    );
+    changed("anti_tamper.anti_vm", *anti_vm != b.anti_tamper.anti_vm);
+    changed(
+        "anti_tamper.crash_on_detection",
+        *crash_on_detection != b.anti_tamper.crash_on_detection,
+    );
+    changed(
+        "max_section_entropy",
+        *max_section_entropy != b.max_section_entropy,
+    );
+    changed("pad_sections", *pad_sections != b.pad_sections);
+    changed("min_cpu_features", *min_cpu_features != b.min_cpu_features);
+}
+
+fn diff_profiles(a: &CDProfile, b: &CDProfile, diffs: &mut Vec<ConfigDiff>) {
+    let profile = &a.name;
+
+    let removed: Vec<_> = a.passes.iter().filter(|p| !b.passes.contains(p)).collect();
+    let added: Vec<_> = b.passes.iter().filter(|p| !a.passes.contains(p)).collect();
+    if removed.is_empty() && added.is_empty() && a.passes != b.passes {
+        diffs.push(ConfigDiff::PassOrderChanged {
+            profile: profile.clone(),
+        });
+    }
+    diffs.extend(removed.into_iter().map(|pass| ConfigDiff::PassRemoved {
+        profile: profile.clone(),
+        pass: pass.clone(),
+    }));
+    diffs.extend(added.into_iter().map(|pass| ConfigDiff::PassAdded {
+        profile: profile.clone(),
+        pass: pass.clone(),
+    }));
+
+    if a.compiler_settings != b.compiler_settings {
+        diffs.push(ConfigDiff::CompilerSettingsChanged {
+            profile: profile.clone(),
+        });
+    }
+
+    let symbols_a: HashSet<u64> = a.symbols.iter().copied().collect();
+    let symbols_b: HashSet<u64> = b.symbols.iter().copied().collect();
+    let removed: Vec<u64> = a
+        .symbols
+        .iter()
+        .filter(|rva| !symbols_b.contains(rva))
+        .copied()
+        .collect();
+    let added: Vec<u64> = b
+        .symbols
+        .iter()
+        .filter(|rva| !symbols_a.contains(rva))
+        .copied()
+        .collect();
+    if !removed.is_empty() {
+        diffs.push(ConfigDiff::SymbolsRemoved {
+            profile: profile.clone(),
+            rvas: removed,
+        });
+    }
+    if !added.is_empty() {
+        diffs.push(ConfigDiff::SymbolsAdded {
+            profile: profile.clone(),
+            rvas: added,
+        });
+    }
+}
@@ -3,14 +3,172 @@
 //! the CodeDefender CLI and its backend services.
 //!
 //! This crate is intended to be consumed by tools that integrate with or generate CodeDefender config files.
+//!
+//! The backend serializes these same structures, so their snake_case field names (e.g.
+//! `ref_count`) are the JSON wire format. Fields must not be renamed without a matching
+//! backend change.
+//!
+//! The data structures only need `serde`. Format helpers sit behind the default features:
+//!
+//! * `yaml` - [`YamlConfig::from_yaml_str`], [`upgrade_config`], and the `extra` fields
+//!   that keep unknown YAML keys.
+//! * `json` - [`CDConfig::to_json_string`] and [`CDConfig::config_hash`].
+//!
+//! RVAs are read from integers or hex strings and written losslessly for JavaScript
+//! consumers; see [`rva`].
+//!
+//! With `default-features = false` the crate builds for constrained targets such as
+//! `wasm32-unknown-unknown`, and the structs can be used with any serde format.
 
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cmp::Reverse;
+#[cfg(feature = "yaml")]
+use std::collections::BTreeMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[cfg(feature = "json")]
+mod canonical;
+mod diff;
+mod limits;
+mod lint;
+mod reference;
+pub mod rva;
+mod starter;
+#[cfg(feature = "yaml")]
+mod upgrade;
+mod validate;
+
+pub use diff::{ConfigDiff, diff_configs};
+pub use limits::{LimitError, Limits, split_profile};
+pub use lint::{Lint, LintKind, lint_config};
+pub use reference::{FieldDoc, config_reference};
+pub use starter::{StarterOptions, cdconfig_to_yaml};
+#[cfg(feature = "yaml")]
+pub use upgrade::{UpgradeError, upgrade_config};
+pub use validate::{
+    MAX_ITERATIONS, Severity, ValidationError, ValidationReport, validate_cdconfig,
+    validate_config, validate_environment, validate_resolved,
+};
 
 /// Current supported YAML config version.
-pub const YAML_CONFIG_VERSION: &str = "1.0.4";
+///
+/// The version is only checked locally, by [`validate_config`]. The API does not report
+/// which config versions the backend accepts, so a backend that has drifted from this
+/// crate shows up as a rejected `defend` request rather than before submission.
+pub const YAML_CONFIG_VERSION: &str = "1.1.0";
+
+/// Errors produced while parsing or serializing configuration data.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The YAML document could not be parsed or serialized.
+    #[cfg(feature = "yaml")]
+    Yaml(serde_yaml::Error),
+    /// The JSON document could not be parsed or serialized.
+    #[cfg(feature = "json")]
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for ConfigError {
+    // Without format features the enum is empty and `f` goes unused.
+    #[cfg_attr(not(any(feature = "yaml", feature = "json")), allow(unused_variables))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            #[cfg(feature = "yaml")]
+            ConfigError::Yaml(ref e) => write!(f, "invalid YAML config: {}", e),
+            #[cfg(feature = "json")]
+            ConfigError::Json(ref e) => write!(f, "invalid JSON config: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            #[cfg(feature = "yaml")]
+            ConfigError::Yaml(ref e) => Some(e),
+            #[cfg(feature = "json")]
+            ConfigError::Json(ref e) => Some(e),
+        }
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl From<serde_yaml::Error> for ConfigError {
+    fn from(e: serde_yaml::Error) -> Self {
+        ConfigError::Yaml(e)
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for ConfigError {
+    fn from(e: serde_json::Error) -> Self {
+        ConfigError::Json(e)
+    }
+}
+
+/// Errors produced by [`YamlConfig::try_into_cdconfig`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConversionError {
+    /// Some symbols are given by name or wildcard and need analysis to be resolved. Holds
+    /// the symbols as written.
+    UnresolvedSymbols(Vec<String>),
+    /// A profile has no compiler settings and no default is set. Holds the profile name.
+    MissingCompilerSettings(String),
+    /// A profile sets both `compiler_settings` and `compiler_preset`. Holds the profile
+    /// name.
+    ConflictingCompilerSettings(String),
+    /// A pass is restricted to symbols outside its profile. Holds the profile and pass
+    /// names.
+    TargetsOutsideProfile { profile: String, pass: String },
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::UnresolvedSymbols(names) => write!(
+                f,
+                "symbols require analysis to resolve: {}",
+                names.join(", ")
+            ),
+            ConversionError::MissingCompilerSettings(profile) => write!(
+                f,
+                "profile `{}` has no compiler settings and no default is set",
+                profile
+            ),
+            ConversionError::ConflictingCompilerSettings(profile) => write!(
+                f,
+                "profile `{}` sets both compiler_settings and compiler_preset",
+                profile
+            ),
+            ConversionError::TargetsOutsideProfile { profile, pass } => write!(
+                f,
+                "{} in profile `{}` targets symbols the profile does not",
+                pass, profile
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
 
 /// Available SIMD extension types used by mutation engines.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+///
+/// New variants may be added in minor releases, so matches outside this crate need a
+/// wildcard arm. Without one, a match does not compile:
+///
+/// ```compile_fail,E0004
+/// # use codedefender_config::MutationEngineExtension;
+/// fn uses_sse(extension: &MutationEngineExtension) -> bool {
+///     match extension {
+///         MutationEngineExtension::Generic => false,
+///         MutationEngineExtension::SSE3 | MutationEngineExtension::SSE42 => true,
+///     }
+/// }
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum MutationEngineExtension {
     /// All base instructions
     Generic,
@@ -20,8 +178,53 @@ pub enum MutationEngineExtension {
     SSE42,
 }
 
+impl MutationEngineExtension {
+    /// Minimum CPU feature level needed to run code produced with this extension:
+    ///
+    /// | Extension | Required level                 |
+    /// |-----------|--------------------------------|
+    /// | `Generic` | [`CpuFeatureLevel::Baseline`]  |
+    /// | `SSE3`    | [`CpuFeatureLevel::Sse3`]      |
+    /// | `SSE42`   | [`CpuFeatureLevel::Sse42`]     |
+    pub fn required_cpu_features(&self) -> CpuFeatureLevel {
+        match self {
+            MutationEngineExtension::Generic => CpuFeatureLevel::Baseline,
+            MutationEngineExtension::SSE3 => CpuFeatureLevel::Sse3,
+            MutationEngineExtension::SSE42 => CpuFeatureLevel::Sse42,
+        }
+    }
+}
+
+/// Instruction set extensions a protected binary may assume, from oldest to newest. Each
+/// level includes the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum CpuFeatureLevel {
+    /// Baseline x86-64 (SSE2), supported by every 64-bit CPU.
+    Baseline,
+    /// Adds SSE3.
+    Sse3,
+    /// Adds SSSE3, SSE4.1 and SSE4.2 (x86-64-v2).
+    Sse42,
+    /// Adds AVX and AVX2 (x86-64-v3).
+    Avx2,
+}
+
 /// Supported PE environments.
-#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+///
+/// New variants may be added in minor releases, so matches outside this crate need a
+/// wildcard arm. Without one, a match does not compile:
+///
+/// ```compile_fail,E0004
+/// # use codedefender_config::PeEnvironment;
+/// fn is_kernel(environment: PeEnvironment) -> bool {
+///     match environment {
+///         PeEnvironment::UserMode => false,
+///         PeEnvironment::KernelMode | PeEnvironment::UEFI => true,
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum PeEnvironment {
     /// User-mode PE (exe, dll)
     UserMode,
@@ -32,7 +235,7 @@ pub enum PeEnvironment {
 }
 
 /// Configuration settings for lifting x86 instructions into IR.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct LifterSettings {
     /// Whether to lift calls into IR.
     pub lift_calls: bool,
@@ -45,7 +248,7 @@ pub struct LifterSettings {
 }
 
 /// IR optimization settings.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct OptimizationSettings {
     /// Enable constant propagation.
     pub constant_propagation: bool,
@@ -55,12 +258,12 @@ pub struct OptimizationSettings {
     pub dead_code_elim: bool,
     /// Enable pruning of unused block parameters.
     pub prune_useless_block_params: bool,
-    /// Number of optimization iterations to run.
+    /// Number of optimization iterations to run (1–[`MAX_ITERATIONS`]).
     pub iterations: u32,
 }
 
 /// Assembler-level codegen settings.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct AssemblerSettings {
     /// Whether to shuffle basic blocks.
     pub shuffle_basic_blocks: bool,
@@ -68,10 +271,18 @@ pub struct AssemblerSettings {
     pub instruction_prefix: String,
     /// Chance of randomly applying the prefix.
     pub random_prefix_chance: f64,
+    /// Whether to insert junk/NOP sequences between real instructions.
+    /// Junk is inserted within basic blocks, so it moves with its block when
+    /// `shuffle_basic_blocks` is enabled.
+    #[serde(default)]
+    pub nop_insertion: bool,
+    /// Chance (0.0–1.0) of inserting a junk sequence after each instruction.
+    #[serde(default)]
+    pub nop_insertion_chance: f64,
 }
 
 /// Compiler configuration (IR + codegen) for a profile.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct CDCompilerSettings {
     /// Assembler settings.
     pub assembler_settings: AssemblerSettings,
@@ -81,8 +292,77 @@ pub struct CDCompilerSettings {
     pub lifter_settings: LifterSettings,
 }
 
+/// Named starting points for [`CDCompilerSettings`], written in lowercase in YAML
+/// (`compiler_preset: balanced`). See [`CDCompilerSettings::from_preset`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompilerPreset {
+    /// Quickest to compile and lightest on the output.
+    Fast,
+    /// A middle ground, and the [`Default`] for [`CDCompilerSettings`].
+    Balanced,
+    /// Strongest codegen obfuscation, at the cost of size and compile time.
+    Max,
+}
+
+impl CDCompilerSettings {
+    /// Builds the compiler settings for a preset.
+    ///
+    /// Every preset enables all optimizations and lifts calls with the `WindowsAbi`
+    /// convention, a 1024 byte stack copy limit and the split-on-calls fallback. They differ
+    /// in:
+    ///
+    /// | Preset     | Optimization iterations | Shuffle blocks | Junk insertion chance |
+    /// |------------|-------------------------|----------------|-----------------------|
+    /// | `Fast`     | 1                       | no             | off                   |
+    /// | `Balanced` | 1                       | yes            | off                   |
+    /// | `Max`      | 4                       | yes            | 0.1                   |
+    ///
+    /// No preset sets an instruction prefix.
+    ///
+    /// The API does not document default compiler settings. `Balanced` uses the
+    /// `compiler_settings` of the example config shipped in `utils/example/config.yaml`,
+    /// and the other presets only change the columns above.
+    pub fn from_preset(preset: CompilerPreset) -> Self {
+        let (iterations, shuffle_basic_blocks, nop_insertion_chance) = match preset {
+            CompilerPreset::Fast => (1, false, None),
+            CompilerPreset::Balanced => (1, true, None),
+            CompilerPreset::Max => (4, true, Some(0.1)),
+        };
+        Self {
+            assembler_settings: AssemblerSettings {
+                shuffle_basic_blocks,
+                instruction_prefix: String::new(),
+                random_prefix_chance: 0.0,
+                nop_insertion: nop_insertion_chance.is_some(),
+                nop_insertion_chance: nop_insertion_chance.unwrap_or(0.0),
+            },
+            optimization_settings: OptimizationSettings {
+                constant_propagation: true,
+                instruction_combine: true,
+                dead_code_elim: true,
+                prune_useless_block_params: true,
+                iterations,
+            },
+            lifter_settings: LifterSettings {
+                lift_calls: true,
+                calling_convention: "WindowsAbi".to_string(),
+                max_stack_copy_size: 1024,
+                split_on_calls_fallback: true,
+            },
+        }
+    }
+}
+
+/// Defaults to [`CompilerPreset::Balanced`], the settings of the shipped example config.
+impl Default for CDCompilerSettings {
+    fn default() -> Self {
+        Self::from_preset(CompilerPreset::Balanced)
+    }
+}
+
 /// Fake PDB string settings to confuse debuggers.
-#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct FakePdbString {
     /// Whether the fake PDB string is enabled.
     pub enabled: bool,
@@ -91,16 +371,40 @@ pub struct FakePdbString {
 }
 
 /// Custom `.text` section name override.
-#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CustomSectionName {
     /// Whether this feature is enabled.
     pub enabled: bool,
-    /// Custom section name value.
+    /// Custom section name value. PE section names are at most 8 bytes long.
     pub value: String,
+    /// Allow `value` to be a standard section name such as `.data`. Reusing the name of
+    /// a section that already exists in the image usually corrupts it, so this is off
+    /// unless the image is known not to have that section.
+    ///
+    /// Only read by [`validate_config`]; it is kept in YAML configs but left out of the
+    /// [`CDConfig`] sent to the API.
+    #[serde(default)]
+    pub allow_standard_name: bool,
+}
+
+/// Granular anti-debug and anti-VM settings.
+///
+/// `obscure_entry_point` already places anti-debug checks around the entry point; these
+/// settings control runtime detection independently of it and can be combined with it.
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AntiTamperSettings {
+    /// Whether this feature is enabled.
+    pub enabled: bool,
+    /// Detect attached debuggers.
+    pub anti_debug: bool,
+    /// Detect execution inside a virtual machine or hypervisor.
+    pub anti_vm: bool,
+    /// Crash the process on detection instead of exiting gracefully.
+    pub crash_on_detection: bool,
 }
 
 /// Global obfuscation settings for the module.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CDModuleSettings {
     /// Whether to crash the IDA decompiler intentionally.
     #[serde(default)]
@@ -115,16 +419,63 @@ pub struct CDModuleSettings {
     /// structured exception handling will not work.
     #[serde(default)]
     pub clear_unwind_info: bool,
+    /// Protect TLS callbacks against tampering and hooking. TLS callbacks run before the
+    /// entry point, so this complements `obscure_entry_point`, which only covers the entry
+    /// point itself. Only applies to user-mode images.
+    #[serde(default)]
+    pub tls_callback_protection: bool,
     /// Fake PDB string settings.
     #[serde(default)]
     pub fake_pdb_string: FakePdbString,
     /// Custom PE section name settings.
     #[serde(default)]
     pub custom_section_name: CustomSectionName,
+    /// Anti-debug and anti-VM settings.
+    #[serde(default)]
+    pub anti_tamper: AntiTamperSettings,
+    /// Upper bound, in bits per byte (0.0–8.0), for the entropy of sections produced by
+    /// obfuscation. This is a hint to the backend, which trades size for stealth to meet it.
+    #[serde(default)]
+    pub max_section_entropy: Option<f64>,
+    /// Pad emitted sections with low-entropy filler. This is a hint to the backend.
+    #[serde(default)]
+    pub pad_sections: bool,
+    /// Oldest CPU the protected binary must run on. Passes are checked against it so they
+    /// do not emit instructions such a CPU lacks, see
+    /// [`MutationEngineExtension::required_cpu_features`]. `None` skips the check.
+    ///
+    /// Only read by [`validate_config`]; it is kept in YAML configs but left out of the
+    /// [`CDConfig`] sent to the API.
+    #[serde(default)]
+    pub min_cpu_features: Option<CpuFeatureLevel>,
+}
+
+impl CDModuleSettings {
+    /// Returns module settings that are safe to start from for the given environment.
+    ///
+    /// - [`PeEnvironment::UserMode`]: import protection, entry point obfuscation and TLS
+    ///   callback protection are enabled.
+    /// - [`PeEnvironment::KernelMode`] and [`PeEnvironment::UEFI`]: everything is left
+    ///   disabled. Drivers and firmware images have no TLS callbacks, and entry point and
+    ///   import tampering is harder to recover from there.
+    ///
+    /// `clear_unwind_info` stays disabled everywhere, since it breaks structured exception
+    /// handling, which kernel-mode code in particular relies on.
+    pub fn recommended_for(environment: PeEnvironment) -> Self {
+        match environment {
+            PeEnvironment::UserMode => Self {
+                import_protection: true,
+                obscure_entry_point: true,
+                tls_callback_protection: true,
+                ..Self::default()
+            },
+            PeEnvironment::KernelMode | PeEnvironment::UEFI => Self::default(),
+        }
+    }
 }
 
 /// Instruction-level semantics used in transformations.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Semantics {
     #[serde(default)]
     pub add: bool,
@@ -143,7 +494,7 @@ pub struct Semantics {
 }
 
 /// Bit widths to apply transformations to.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct BitWidths {
     #[serde(default)]
     pub bit8: bool,
@@ -158,7 +509,7 @@ pub struct BitWidths {
 /// The origin of SSA value from within the instruction.
 /// Please refer to this documentation for more info:
 /// https://docs.codedefender.io/features/ethnicity
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct SsaOrigins {
     pub normal: bool,
     pub memop: bool,
@@ -166,8 +517,57 @@ pub struct SsaOrigins {
     pub sp_based_memop: bool,
 }
 
+impl Semantics {
+    /// Every instruction semantic enabled.
+    pub fn all() -> Self {
+        Self {
+            add: true,
+            sub: true,
+            and: true,
+            xor: true,
+            or: true,
+            not: true,
+            neg: true,
+        }
+    }
+
+    /// Returns `true` if no semantic is enabled, which makes a pass a no-op.
+    pub fn is_empty(&self) -> bool {
+        !(self.add || self.sub || self.and || self.xor || self.or || self.not || self.neg)
+    }
+}
+
+impl BitWidths {
+    /// Every bit width enabled.
+    pub fn all() -> Self {
+        Self {
+            bit8: true,
+            bit16: true,
+            bit32: true,
+            bit64: true,
+        }
+    }
+
+    /// Returns `true` if no bit width is enabled, which makes a pass a no-op.
+    pub fn is_empty(&self) -> bool {
+        !(self.bit8 || self.bit16 || self.bit32 || self.bit64)
+    }
+}
+
+impl SsaOrigins {
+    /// Every SSA value origin enabled.
+    pub fn all() -> Self {
+        Self {
+            normal: true,
+            memop: true,
+            fp_based_memop: true,
+            sp_based_memop: true,
+        }
+    }
+}
+
 /// Configuration for the Loop Encode Semantics pass.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct LoopEncodeSemantics {
     /// Number of times to attempt transformation.
     pub iterations: u32,
@@ -181,7 +581,7 @@ pub struct LoopEncodeSemantics {
 }
 
 /// Configuration for Mixed Boolean Arithmetic pass.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct MixedBooleanArithmetic {
     pub iterations: u32,
     pub probability: u32,
@@ -191,7 +591,7 @@ pub struct MixedBooleanArithmetic {
 }
 
 /// Configuration for Mutation Engine pass.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct MutationEngine {
     pub iterations: u32,
     pub probability: u32,
@@ -201,20 +601,65 @@ pub struct MutationEngine {
     pub ethnicities: SsaOrigins,
 }
 
+/// Defaults to one iteration at 100% probability over every semantic, bit width and SSA
+/// origin ([`Semantics::all`], [`BitWidths::all`], [`SsaOrigins::all`]).
+impl Default for LoopEncodeSemantics {
+    fn default() -> Self {
+        Self {
+            iterations: 1,
+            probability: 100,
+            semantics: Semantics::all(),
+            bitwidths: BitWidths::all(),
+            ethnicities: SsaOrigins::all(),
+        }
+    }
+}
+
+/// Defaults to one iteration at 100% probability over every semantic, bit width and SSA
+/// origin ([`Semantics::all`], [`BitWidths::all`], [`SsaOrigins::all`]).
+impl Default for MixedBooleanArithmetic {
+    fn default() -> Self {
+        Self {
+            iterations: 1,
+            probability: 100,
+            semantics: Semantics::all(),
+            bitwidths: BitWidths::all(),
+            ethnicities: SsaOrigins::all(),
+        }
+    }
+}
+
+/// Defaults to one iteration at 100% probability with the [`MutationEngineExtension::Generic`]
+/// extension over every semantic, bit width and SSA origin.
+impl Default for MutationEngine {
+    fn default() -> Self {
+        Self {
+            iterations: 1,
+            probability: 100,
+            extension: MutationEngineExtension::Generic,
+            semantics: Semantics::all(),
+            bitwidths: BitWidths::all(),
+            ethnicities: SsaOrigins::all(),
+        }
+    }
+}
+
 /// Pass that crashes IDA’s decompiler.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct IDADecompilerCrasher;
 
 /// Suppress constants and prevent them from rematerializing at runtime.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct SuppressConstants {
     pub ethnicities: SsaOrigins,
 }
 
 /// Statically obscure constants, this does not prevent rematerialization at runtime.
 /// Use the SuppressConstants pass in tandem with this!
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ObscureConstants {
+    /// Percent chance to apply transformation (0–100). Defaults to 100.
+    #[serde(default = "default_probability")]
     pub probability: u32,
     pub iterations: u32,
     pub bitwidths: BitWidths,
@@ -222,17 +667,36 @@ pub struct ObscureConstants {
 }
 
 /// Memory reference obfuscation pass.
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct ObscureReferences;
+///
+/// Older configs wrote this pass without settings (`- type: ObscureReferences`); those
+/// still load and apply at 100% probability.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ObscureReferences {
+    /// Percent chance to apply transformation (0–100). Defaults to 100.
+    #[serde(default = "default_probability")]
+    pub probability: u32,
+}
+
+impl Default for ObscureReferences {
+    fn default() -> Self {
+        Self {
+            probability: default_probability(),
+        }
+    }
+}
+
+fn default_probability() -> u32 {
+    100
+}
 
 /// Control-flow obfuscation pass.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ObscureControlFlow {
     pub probability: u32,
 }
 
 /// Tether extraction pass.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct TetherExtraction {
     /// Min length of a sequence of instructions that should be extracted.
     /// Its a bad idea for this to be 1 usually because its easy to synthesize
@@ -247,7 +711,7 @@ pub struct TetherExtraction {
 }
 
 /// Opaque block duplication pass.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct OpaqueBlockDuplication {
     /// Number of iterations to attempt transformation.
     pub iterations: u32,
@@ -256,23 +720,71 @@ pub struct OpaqueBlockDuplication {
 }
 
 /// Split block pass, used to create more control flow points for other passes to transform.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct SplitBlockPass {
     /// The number of SSA values required to be within a block for it to be split into two seperate blocks.
     pub threshold: u32,
 }
 
 /// Encode immediate ssa values into lea's
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct LeaEncodeImm {
     /// Percent chance to apply transformation (0–100).
     pub probability: u32,
     pub ethnicities: SsaOrigins,
 }
 
+/// Virtualization pass, converts native code into bytecode executed by a custom virtual machine.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct VirtualizationProtection {
+    /// Complexity of the generated virtual machine, from 1 (fastest) to 5 (strongest).
+    pub complexity: u32,
+    /// Whether to emit several distinct handlers for the same virtual instruction.
+    pub handler_duplication: bool,
+    /// Percent chance to apply transformation (0–100).
+    pub probability: u32,
+}
+
 /// All possible obfuscation passes.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+///
+/// Passes are internally tagged by their variant name in a `type` field, with the pass's
+/// settings as sibling fields:
+///
+/// ```yaml
+/// - type: ObscureControlFlow
+///   probability: 50
+/// ```
+///
+/// Variants without settings (`IDADecompilerCrasher`, `AntiEmulator`)
+/// are written as a map holding only the tag, e.g. `- type: AntiEmulator`, never as a bare
+/// string. JSON uses the same shape (`{"type": "AntiEmulator"}`).
+///
+/// New variants may be added in minor releases, so matches outside this crate need a
+/// wildcard arm. Without one, a match does not compile:
+///
+/// ```compile_fail,E0004
+/// # use codedefender_config::ObfuscationPass;
+/// fn has_settings(pass: &ObfuscationPass) -> bool {
+///     match pass {
+///         ObfuscationPass::IDADecompilerCrasher | ObfuscationPass::AntiEmulator => false,
+///         ObfuscationPass::LoopEncodeSemantics(_)
+///         | ObfuscationPass::MixedBooleanArithmetic(_)
+///         | ObfuscationPass::MutationEngine(_)
+///         | ObfuscationPass::TetherExtraction(_)
+///         | ObfuscationPass::SplitBlockPass(_)
+///         | ObfuscationPass::OpaqueBlockDuplication(_)
+///         | ObfuscationPass::ObscureControlFlow(_)
+///         | ObfuscationPass::LeaEncodeImm(_)
+///         | ObfuscationPass::ObscureConstants(_)
+///         | ObfuscationPass::SuppressConstants(_)
+///         | ObfuscationPass::VirtualizationProtection(_)
+///         | ObfuscationPass::ObscureReferences(_) => true,
+///     }
+/// }
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(tag = "type")]
+#[non_exhaustive]
 pub enum ObfuscationPass {
     LoopEncodeSemantics(LoopEncodeSemantics),
     MixedBooleanArithmetic(MixedBooleanArithmetic),
@@ -284,13 +796,209 @@ pub enum ObfuscationPass {
     LeaEncodeImm(LeaEncodeImm),
     ObscureConstants(ObscureConstants),
     SuppressConstants(SuppressConstants),
+    VirtualizationProtection(VirtualizationProtection),
+    ObscureReferences(ObscureReferences),
     IDADecompilerCrasher,
-    ObscureReferences,
     AntiEmulator,
 }
 
+impl ObfuscationPass {
+    /// Returns the pass's type name, as used in the `type` tag of the config.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ObfuscationPass::LoopEncodeSemantics(_) => "LoopEncodeSemantics",
+            ObfuscationPass::MixedBooleanArithmetic(_) => "MixedBooleanArithmetic",
+            ObfuscationPass::MutationEngine(_) => "MutationEngine",
+            ObfuscationPass::TetherExtraction(_) => "TetherExtraction",
+            ObfuscationPass::SplitBlockPass(_) => "SplitBlockPass",
+            ObfuscationPass::OpaqueBlockDuplication(_) => "OpaqueBlockDuplication",
+            ObfuscationPass::ObscureControlFlow(_) => "ObscureControlFlow",
+            ObfuscationPass::LeaEncodeImm(_) => "LeaEncodeImm",
+            ObfuscationPass::ObscureConstants(_) => "ObscureConstants",
+            ObfuscationPass::SuppressConstants(_) => "SuppressConstants",
+            ObfuscationPass::VirtualizationProtection(_) => "VirtualizationProtection",
+            ObfuscationPass::IDADecompilerCrasher => "IDADecompilerCrasher",
+            ObfuscationPass::ObscureReferences(_) => "ObscureReferences",
+            ObfuscationPass::AntiEmulator => "AntiEmulator",
+        }
+    }
+
+    /// Returns the environments this pass can be applied in.
+    ///
+    /// * `TetherExtraction` needs the protected code to reach the tether server at runtime,
+    ///   so it is user-mode only.
+    /// * `IDADecompilerCrasher` and `AntiEmulator` rely on runtime facilities that don't exist
+    ///   in UEFI firmware and are limited to user and kernel mode.
+    /// * Every other pass works in all environments.
+    pub fn supported_environments(&self) -> &'static [PeEnvironment] {
+        const ALL: &[PeEnvironment] = &[
+            PeEnvironment::UserMode,
+            PeEnvironment::KernelMode,
+            PeEnvironment::UEFI,
+        ];
+        match self {
+            ObfuscationPass::TetherExtraction(_) => &[PeEnvironment::UserMode],
+            ObfuscationPass::IDADecompilerCrasher | ObfuscationPass::AntiEmulator => {
+                &[PeEnvironment::UserMode, PeEnvironment::KernelMode]
+            }
+            _ => ALL,
+        }
+    }
+}
+
+/// Read access to the common tunables of a pass, regardless of its variant.
+pub trait Tunable {
+    /// Percent chance (0–100) to apply the pass, or `None` if it is always applied.
+    fn probability(&self) -> Option<u32>;
+    /// Number of times the pass is applied, or `None` if it runs once.
+    fn iterations(&self) -> Option<u32>;
+}
+
+impl Tunable for ObfuscationPass {
+    fn probability(&self) -> Option<u32> {
+        match self {
+            ObfuscationPass::LoopEncodeSemantics(p) => Some(p.probability),
+            ObfuscationPass::MixedBooleanArithmetic(p) => Some(p.probability),
+            ObfuscationPass::MutationEngine(p) => Some(p.probability),
+            ObfuscationPass::OpaqueBlockDuplication(p) => Some(p.probability),
+            ObfuscationPass::ObscureControlFlow(p) => Some(p.probability),
+            ObfuscationPass::LeaEncodeImm(p) => Some(p.probability),
+            ObfuscationPass::ObscureConstants(p) => Some(p.probability),
+            ObfuscationPass::VirtualizationProtection(p) => Some(p.probability),
+            ObfuscationPass::ObscureReferences(p) => Some(p.probability),
+            ObfuscationPass::TetherExtraction(_)
+            | ObfuscationPass::SplitBlockPass(_)
+            | ObfuscationPass::SuppressConstants(_)
+            | ObfuscationPass::IDADecompilerCrasher
+            | ObfuscationPass::AntiEmulator => None,
+        }
+    }
+
+    fn iterations(&self) -> Option<u32> {
+        match self {
+            ObfuscationPass::LoopEncodeSemantics(p) => Some(p.iterations),
+            ObfuscationPass::MixedBooleanArithmetic(p) => Some(p.iterations),
+            ObfuscationPass::MutationEngine(p) => Some(p.iterations),
+            ObfuscationPass::OpaqueBlockDuplication(p) => Some(p.iterations),
+            ObfuscationPass::ObscureConstants(p) => Some(p.iterations),
+            ObfuscationPass::TetherExtraction(_)
+            | ObfuscationPass::SplitBlockPass(_)
+            | ObfuscationPass::ObscureControlFlow(_)
+            | ObfuscationPass::LeaEncodeImm(_)
+            | ObfuscationPass::SuppressConstants(_)
+            | ObfuscationPass::VirtualizationProtection(_)
+            | ObfuscationPass::IDADecompilerCrasher
+            | ObfuscationPass::ObscureReferences(_)
+            | ObfuscationPass::AntiEmulator => None,
+        }
+    }
+}
+
+/// Formats a one-line summary such as `3 passes: MutationEngine x2, ObscureControlFlow over
+/// 42 symbols`. Passes of the same type are counted together in first-seen order.
+fn summarize_profile<'a>(
+    passes: impl IntoIterator<Item = &'a ObfuscationPass>,
+    symbol_count: usize,
+) -> String {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    let mut pass_count = 0;
+    for pass in passes {
+        pass_count += 1;
+        match counts.iter_mut().find(|(name, _)| *name == pass.name()) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((pass.name(), 1)),
+        }
+    }
+
+    let pass_list = counts
+        .iter()
+        .map(|(name, count)| match count {
+            1 => name.to_string(),
+            n => format!("{} x{}", name, n),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "{} pass{}: {} over {} symbol{}",
+        pass_count,
+        if pass_count == 1 { "" } else { "es" },
+        if pass_list.is_empty() {
+            "none"
+        } else {
+            &pass_list
+        },
+        symbol_count,
+        if symbol_count == 1 { "" } else { "s" }
+    )
+}
+
+impl fmt::Display for ObfuscationPass {
+    /// Formats a concise, human-readable label including the pass's key tunables.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObfuscationPass::LoopEncodeSemantics(p) => write!(
+                f,
+                "Loop Encode Semantics (prob {}%, {} iters)",
+                p.probability, p.iterations
+            ),
+            ObfuscationPass::MixedBooleanArithmetic(p) => write!(
+                f,
+                "Mixed Boolean Arithmetic (prob {}%, {} iters)",
+                p.probability, p.iterations
+            ),
+            ObfuscationPass::MutationEngine(p) => write!(
+                f,
+                "Mutation Engine ({:?}, prob {}%, {} iters)",
+                p.extension, p.probability, p.iterations
+            ),
+            ObfuscationPass::TetherExtraction(p) => write!(
+                f,
+                "Tether Extraction ({}:{}, min len {})",
+                p.endpoint, p.port, p.min_extract_len
+            ),
+            ObfuscationPass::SplitBlockPass(p) => {
+                write!(f, "Split Block (threshold {})", p.threshold)
+            }
+            ObfuscationPass::OpaqueBlockDuplication(p) => write!(
+                f,
+                "Opaque Block Duplication (prob {}%, {} iters)",
+                p.probability, p.iterations
+            ),
+            ObfuscationPass::ObscureControlFlow(p) => {
+                write!(f, "Obscure Control Flow (prob {}%)", p.probability)
+            }
+            ObfuscationPass::LeaEncodeImm(p) => {
+                write!(f, "LEA Encode Immediates (prob {}%)", p.probability)
+            }
+            ObfuscationPass::ObscureConstants(p) => write!(
+                f,
+                "Obscure Constants (prob {}%, {} iters)",
+                p.probability, p.iterations
+            ),
+            ObfuscationPass::SuppressConstants(_) => write!(f, "Suppress Constants"),
+            ObfuscationPass::VirtualizationProtection(p) => write!(
+                f,
+                "Virtualization (complexity {}, prob {}%{})",
+                p.complexity,
+                p.probability,
+                if p.handler_duplication {
+                    ", duplicated handlers"
+                } else {
+                    ""
+                }
+            ),
+            ObfuscationPass::IDADecompilerCrasher => write!(f, "IDA Decompiler Crasher"),
+            ObfuscationPass::ObscureReferences(p) => {
+                write!(f, "Obscure References (prob {}%)", p.probability)
+            }
+            ObfuscationPass::AntiEmulator => write!(f, "Anti Emulator"),
+        }
+    }
+}
+
 /// Profile definition used to apply passes to symbols.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CDProfile {
     /// Name of the profile.
     pub name: String,
@@ -298,34 +1006,243 @@ pub struct CDProfile {
     pub passes: Vec<ObfuscationPass>,
     /// Compiler settings for this profile.
     pub compiler_settings: CDCompilerSettings,
-    /// List of symbol RVAs this profile targets.
+    /// List of symbol RVAs this profile targets. Configs built by this crate and
+    /// `codedefender-utils` keep it sorted ascending, so equal configs serialize
+    /// identically.
+    #[serde(deserialize_with = "rva::vec::deserialize")]
     pub symbols: Vec<u64>,
 }
 
+/// Returns the first of `{base}_split_2`, `{base}_split_3`, ... that is not in `taken`, and
+/// adds it to `taken`.
+pub(crate) fn split_name(base: &str, taken: &mut HashSet<String>) -> String {
+    (2..)
+        .map(|n| format!("{}_split_{}", base, n))
+        .find(|name| taken.insert(name.clone()))
+        .expect("some suffix is free")
+}
+
+impl CDProfile {
+    /// Builds the profiles to submit for passes that apply to different subsets of
+    /// `symbols`. Each pass comes with the RVAs it is restricted to, or `None` to apply it
+    /// to every symbol.
+    ///
+    /// Symbols are grouped by the exact set of passes that apply to them, and each group
+    /// becomes a profile, in order of first appearance. The first keeps `name`, the others
+    /// are named `name_split_2`, `name_split_3`, ..., skipping names in `taken`. Every name
+    /// used is added to `taken`, so it can be shared between calls to keep the names of a
+    /// config unique. Symbols no pass applies to are dropped. Without restricted passes this
+    /// returns a single profile with every symbol.
+    pub fn split_by_pass_targets(
+        name: &str,
+        passes: Vec<(ObfuscationPass, Option<HashSet<u64>>)>,
+        compiler_settings: CDCompilerSettings,
+        symbols: Vec<u64>,
+        taken: &mut HashSet<String>,
+    ) -> Vec<CDProfile> {
+        taken.insert(name.to_owned());
+        if passes.iter().all(|(_, targets)| targets.is_none()) {
+            return vec![CDProfile {
+                name: name.to_owned(),
+                passes: passes.into_iter().map(|(pass, _)| pass).collect(),
+                compiler_settings,
+                symbols,
+            }];
+        }
+
+        let mut groups: Vec<(Vec<usize>, Vec<u64>)> = Vec::new();
+        let mut group_of: HashMap<Vec<usize>, usize> = HashMap::new();
+        for rva in symbols {
+            let applying: Vec<usize> = passes
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, targets))| targets.as_ref().is_none_or(|t| t.contains(&rva)))
+                .map(|(i, _)| i)
+                .collect();
+            if applying.is_empty() {
+                continue;
+            }
+            let group = *group_of.entry(applying.clone()).or_insert_with(|| {
+                groups.push((applying, Vec::new()));
+                groups.len() - 1
+            });
+            groups[group].1.push(rva);
+        }
+
+        groups
+            .into_iter()
+            .enumerate()
+            .map(|(i, (applying, symbols))| CDProfile {
+                name: if i == 0 {
+                    name.to_owned()
+                } else {
+                    split_name(name, taken)
+                },
+                passes: applying.into_iter().map(|j| passes[j].0.clone()).collect(),
+                compiler_settings: compiler_settings.clone(),
+                symbols,
+            })
+            .collect()
+    }
+
+    /// Returns a one-line summary of the profile's passes and symbol count.
+    pub fn summary(&self) -> String {
+        summarize_profile(&self.passes, self.symbols.len())
+    }
+
+    /// Rough, unitless estimate of how much work obfuscating this profile takes.
+    ///
+    /// Computed as the number of symbols times the sum of every pass's iteration count,
+    /// where passes without an iteration count weigh 1. It grows with symbols, passes and
+    /// iterations, and is only meant for comparing configs, not predicting runtime.
+    pub fn estimated_work_units(&self) -> u64 {
+        let pass_weight: u64 = self
+            .passes
+            .iter()
+            .map(|pass| u64::from(pass.iterations().unwrap_or(1).max(1)))
+            .sum();
+        (self.symbols.len() as u64).saturating_mul(pass_weight)
+    }
+
+    /// Expected number of symbols each pass transforms, in pass order, labelled with
+    /// [`ObfuscationPass::name`].
+    ///
+    /// Computed as the symbol count times the pass's [`Tunable::probability`], with passes
+    /// that have no probability applying to every symbol. This assumes the probability is
+    /// drawn independently once per symbol; it is a statistical preview, not a guarantee,
+    /// and passes that roll per instruction transform a matching share of instructions
+    /// instead.
+    pub fn estimate_applied(&self) -> Vec<(String, f64)> {
+        let symbols = self.symbols.len() as f64;
+        self.passes
+            .iter()
+            .map(|pass| {
+                let probability = f64::from(pass.probability().unwrap_or(100).min(100));
+                (pass.name().to_owned(), symbols * probability / 100.0)
+            })
+            .collect()
+    }
+}
+
 /// Top-level config file structure.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CDConfig {
-    /// Module-wide settings.
+    /// Module-wide settings. Settings only used by checks in this crate, such as
+    /// [`CustomSectionName::allow_standard_name`], are not serialized.
+    #[serde(serialize_with = "serialize_wire_module_settings")]
     pub module_settings: CDModuleSettings,
     /// All profiles to apply during obfuscation.
     pub profiles: Vec<CDProfile>,
 }
 
+/// Serializes the module settings of a [`CDConfig`] as the API expects them, without the
+/// settings that only exist for local checks.
+fn serialize_wire_module_settings<S: serde::Serializer>(
+    settings: &CDModuleSettings,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct WireSectionName<'a> {
+        enabled: bool,
+        value: &'a str,
+    }
+
+    #[derive(Serialize)]
+    struct WireModuleSettings<'a> {
+        ida_crasher: bool,
+        import_protection: bool,
+        obscure_entry_point: bool,
+        clear_unwind_info: bool,
+        tls_callback_protection: bool,
+        fake_pdb_string: &'a FakePdbString,
+        custom_section_name: WireSectionName<'a>,
+        anti_tamper: &'a AntiTamperSettings,
+        max_section_entropy: Option<f64>,
+        pad_sections: bool,
+    }
+
+    // Destructured without `..`, so a new field does not compile until it is placed here.
+    let CDModuleSettings {
+        ida_crasher,
+        import_protection,
+        obscure_entry_point,
+        clear_unwind_info,
+        tls_callback_protection,
+        fake_pdb_string,
+        custom_section_name:
+            CustomSectionName {
+                enabled,
+                value,
+                allow_standard_name: _,
+            },
+        anti_tamper,
+        max_section_entropy,
+        pad_sections,
+        min_cpu_features: _,
+    } = settings;
+    WireModuleSettings {
+        ida_crasher: *ida_crasher,
+        import_protection: *import_protection,
+        obscure_entry_point: *obscure_entry_point,
+        clear_unwind_info: *clear_unwind_info,
+        tls_callback_protection: *tls_callback_protection,
+        fake_pdb_string,
+        custom_section_name: WireSectionName {
+            enabled: *enabled,
+            value,
+        },
+        anti_tamper,
+        max_section_entropy: *max_section_entropy,
+        pad_sections: *pad_sections,
+    }
+    .serialize(serializer)
+}
+
+impl CDConfig {
+    /// Sum of [`CDProfile::estimated_work_units`] over every profile.
+    pub fn estimated_work_units(&self) -> u64 {
+        self.profiles
+            .iter()
+            .map(CDProfile::estimated_work_units)
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Serializes the config into the JSON form sent to the API.
+    #[cfg(feature = "json")]
+    pub fn to_json_string(&self) -> Result<String, ConfigError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Deserializes a config from JSON bytes.
+    #[cfg(feature = "json")]
+    pub fn from_json_slice(bytes: &[u8]) -> Result<Self, ConfigError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
 /// Information about a single function found during analysis.
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct AnalysisFunction {
     /// RVA of the function.
+    #[serde(deserialize_with = "rva::deserialize")]
     pub rva: u64,
     /// Function name.
     pub symbol: String,
     /// Number of references to this function.
     pub ref_count: usize,
+    /// Name of the PE section containing the function, if analysis reported it.
+    #[serde(default)]
+    pub section: Option<String>,
+    /// Size of the function in bytes, if analysis reported it.
+    #[serde(default)]
+    pub size: Option<u64>,
 }
 
 /// Reason why a function was rejected from analysis.
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct AnalysisReject {
     /// RVA of the rejected function.
+    #[serde(deserialize_with = "rva::deserialize")]
     pub rva: u64,
     /// Symbol name.
     pub symbol: String,
@@ -335,17 +1252,51 @@ pub struct AnalysisReject {
     pub reason: String,
 }
 
+impl AnalysisReject {
+    /// Returns `true` if the function can still be protected by targeting it explicitly,
+    /// by name or RVA ("force resolving" it). Only `ReadWriteToCode` rejects can be.
+    pub fn is_force_resolvable(&self) -> bool {
+        self.ty == "ReadWriteToCode"
+    }
+
+    /// Returns actionable advice for common rejections, or `None` if the rejection type and
+    /// reason are not recognized.
+    pub fn remediation(&self) -> Option<&'static str> {
+        if self.is_force_resolvable() {
+            return Some(
+                "the function reads or writes its own code; target it explicitly by name or RVA to protect it anyway",
+            );
+        }
+
+        let reason = self.reason.to_ascii_lowercase();
+        if reason.contains("too small") {
+            Some("the function is too small to protect; protect its callers instead")
+        } else if reason.contains("inline") {
+            Some("mark the function `noinline` so it survives as a standalone function")
+        } else if reason.contains("jump table") || reason.contains("switch") {
+            Some(
+                "the function uses a jump table; rewrite the switch as if/else chains or protect its callers",
+            )
+        } else if reason.contains("exception") || reason.contains("unwind") {
+            Some("move the exception handling into a separate function and protect the rest")
+        } else {
+            None
+        }
+    }
+}
+
 /// Grouping of functions under a named macro profile.
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct AnalysisMacroProfile {
     /// Name of the macro profile.
     pub name: String,
     /// List of function RVAs in this macro.
+    #[serde(deserialize_with = "rva::vec::deserialize")]
     pub rvas: Vec<u64>,
 }
 
 /// Results from binary analysis, returned to the frontend.
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct AnalysisResult {
     /// Environment type (UserMode, KernelMode, UEFI).
     pub environment: PeEnvironment,
@@ -357,37 +1308,370 @@ pub struct AnalysisResult {
     pub macros: Vec<AnalysisMacroProfile>,
 }
 
-/// Symbol representation used in YAML: either name or RVA.
-#[derive(Debug, Serialize, Deserialize)]
+impl AnalysisResult {
+    /// Returns `true` if analysis found neither functions nor rejects, which usually means
+    /// the binary could not be parsed or its PDB was missing.
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty() && self.rejects.is_empty()
+    }
+
+    /// Returns the functions sorted by descending `ref_count`, most referenced first.
+    /// Functions with the same count keep their analysis order.
+    pub fn functions_by_ref_count_desc(&self) -> Vec<&AnalysisFunction> {
+        let mut functions: Vec<&AnalysisFunction> = self.functions.iter().collect();
+        functions.sort_by_key(|function| Reverse(function.ref_count));
+        functions
+    }
+
+    /// Returns the functions sorted by ascending RVA.
+    pub fn functions_by_rva(&self) -> Vec<&AnalysisFunction> {
+        let mut functions: Vec<&AnalysisFunction> = self.functions.iter().collect();
+        functions.sort_by_key(|function| function.rva);
+        functions
+    }
+}
+
+/// A file previously uploaded to CodeDefender.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct UploadedFile {
+    /// UUID of the file, usable wherever an uploaded file ID is expected.
+    pub id: String,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// RFC 3339 timestamp of when the file was uploaded.
+    pub uploaded_at: String,
+}
+
+/// The server's check of an uploaded file, returned before analysis.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct VerifyResult {
+    /// Whether the file is a PE image the server can decode.
+    pub valid: bool,
+    /// Environment the image targets, when it could be decoded.
+    #[serde(default)]
+    pub environment: Option<PeEnvironment>,
+    /// Target machine (e.g. `x86_64`), when it could be decoded.
+    #[serde(default)]
+    pub machine: Option<String>,
+    /// Size of the image once loaded, in bytes, when it could be decoded.
+    #[serde(default)]
+    pub image_size: Option<u64>,
+    /// Why the file was rejected, when it is not valid.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Symbol representation used in YAML: a name, an RVA, or a group of functions.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum YamlSymbol {
     /// Symbol name
     Name(String),
-    /// Symbol RVA.
-    Rva(u64),
+    /// Symbol RVA, e.g. `!Rva 0x1000` or `!Rva "0xFFFFFFFFFFFFFFFF"`.
+    Rva(#[serde(with = "rva")] u64),
+    /// Every function found during analysis, plus rejected functions that can be force
+    /// resolved. Written as `All` or `"*"` (quoted, since a bare `*` is a YAML alias).
+    /// This can target a very large number of functions and is usually combined with
+    /// an exclusion list.
+    #[serde(alias = "*")]
+    All,
+    /// Every function in the named PE section, e.g. `!Section ".crypto"`. Requires
+    /// analysis to report section membership.
+    Section(String),
+    /// Every analyzed function referenced at least this many times, e.g.
+    /// `!RefCountAtLeast 10`. Depends on the `ref_count` reported by analysis; rejected
+    /// functions have no reference count and never match.
+    RefCountAtLeast(usize),
+}
+
+impl fmt::Display for YamlSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            YamlSymbol::Name(name) => write!(f, "{}", name),
+            YamlSymbol::Rva(rva) => write!(f, "0x{:X}", rva),
+            YamlSymbol::All => write!(f, "*"),
+            YamlSymbol::Section(section) => write!(f, "section {}", section),
+            YamlSymbol::RefCountAtLeast(count) => write!(f, "ref_count >= {}", count),
+        }
+    }
+}
+
+/// A pass in a YAML profile, written like a bare pass with an optional `enabled` key:
+///
+/// ```yaml
+/// - type: ObscureControlFlow
+///   probability: 50
+///   enabled: false
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PassEntry {
+    /// The pass itself.
+    #[serde(flatten)]
+    pub pass: ObfuscationPass,
+    /// Whether the pass is applied. Defaults to `true`. Disabled passes keep their settings
+    /// but are left out of the [`CDProfile`] built from the profile.
+    #[serde(default = "default_enabled", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+    /// Symbols the pass is restricted to, in the same forms as the profile's `symbols`.
+    /// They must resolve to a subset of the profile's symbols. `None` applies the pass to
+    /// every symbol of the profile.
+    ///
+    /// The API only knows profile-wide passes, so a profile with restricted passes is
+    /// submitted as several profiles, see [`CDProfile::split_by_pass_targets`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub targets: Option<Vec<YamlSymbol>>,
+    /// Skip functions smaller than this many bytes, e.g. to keep heavy passes such as
+    /// virtualization off tiny functions where they bloat the binary for little benefit.
+    ///
+    /// Sizes come from [`AnalysisFunction::size`]. Functions without a reported size keep
+    /// the pass, and the option has no effect without analysis, e.g. in
+    /// [`YamlConfig::try_into_cdconfig`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_function_size: Option<u64>,
+}
+
+impl From<ObfuscationPass> for PassEntry {
+    fn from(pass: ObfuscationPass) -> Self {
+        Self {
+            pass,
+            enabled: true,
+            targets: None,
+            min_function_size: None,
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn is_enabled(enabled: &bool) -> bool {
+    *enabled
 }
 
 /// Obfuscation profile for YAML configuration.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct YamlProfile {
     /// Profile name (referenced by source macros).
     pub name: String,
-    /// Passes to apply to this profile.
-    pub passes: Vec<ObfuscationPass>,
-    /// Compiler configuration for this profile.
-    pub compiler_settings: CDCompilerSettings,
+    /// Passes to apply to this profile, including disabled ones.
+    pub passes: Vec<PassEntry>,
+    /// Compiler configuration for this profile. Falls back to `compiler_preset`, then to
+    /// [`YamlConfig::default_compiler_settings`] when omitted.
+    #[serde(default)]
+    pub compiler_settings: Option<CDCompilerSettings>,
+    /// Preset expanded into the profile's compiler settings, as a shorthand for writing
+    /// `compiler_settings` out. Must not be combined with `compiler_settings`.
+    #[serde(default)]
+    pub compiler_preset: Option<CompilerPreset>,
     /// Symbols targeted by this profile.
     pub symbols: Vec<YamlSymbol>,
+    /// Symbols removed from the resolved `symbols`, in the same forms. Mostly useful
+    /// together with [`YamlSymbol::All`].
+    #[serde(default)]
+    pub exclude: Vec<YamlSymbol>,
     /// Only used by the SaaS UI. Not used by the CLI.
     pub color: Option<String>,
+    /// Order in which the profile is applied relative to the others; lower values are
+    /// applied first. Profiles without a priority are treated as priority 0, and profiles
+    /// with equal priority keep their declaration order. A symbol listed by several
+    /// profiles is only kept in the one applied first.
+    #[serde(default)]
+    pub priority: Option<i32>,
+    /// Keys not recognized by this crate, such as metadata added by other tools. They are
+    /// kept so they survive a load/save round trip, and are ignored otherwise.
+    #[cfg(feature = "yaml")]
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+impl YamlProfile {
+    /// Returns the passes that are enabled, in order.
+    pub fn enabled_passes(&self) -> impl Iterator<Item = &ObfuscationPass> {
+        self.passes
+            .iter()
+            .filter(|entry| entry.enabled)
+            .map(|entry| &entry.pass)
+    }
+
+    /// Returns a one-line summary of the profile's enabled passes and symbol count. Symbols
+    /// are counted as written, before resolution.
+    pub fn summary(&self) -> String {
+        summarize_profile(self.enabled_passes(), self.symbols.len())
+    }
 }
 
 /// Root YAML config structure.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct YamlConfig {
     /// Version of the config file format.
     pub version: String,
     /// Global module-wide obfuscation settings.
     pub module_settings: CDModuleSettings,
+    /// Compiler configuration used by profiles that don't specify their own.
+    #[serde(default)]
+    pub default_compiler_settings: Option<CDCompilerSettings>,
     /// Obfuscation profiles to apply.
     pub profiles: Vec<YamlProfile>,
+    /// Top-level keys not recognized by this crate, kept so they survive a load/save
+    /// round trip.
+    ///
+    /// Because of this field, unknown keys are never rejected; serde does not support
+    /// `deny_unknown_fields` together with a flattened catch-all.
+    #[cfg(feature = "yaml")]
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+impl YamlConfig {
+    /// Parses a YAML config file's contents.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml_str(yaml: &str) -> Result<Self, ConfigError> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Serializes the config back into YAML.
+    #[cfg(feature = "yaml")]
+    pub fn to_yaml_string(&self) -> Result<String, ConfigError> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    /// Parses a config written as JSON, with the same structure as the YAML form.
+    #[cfg(feature = "json")]
+    pub fn from_json_str(json: &str) -> Result<Self, ConfigError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Converts the config into a [`CDConfig`] without analysis.
+    ///
+    /// This only succeeds when every symbol is given as an RVA; names and wildcards need
+    /// analysis to be resolved. Analysis macros are not folded into profiles. Profiles are
+    /// emitted in priority order, each with its symbols sorted and deduplicated, and split
+    /// when passes are restricted to [`PassEntry::targets`]. A symbol shared by several
+    /// profiles stays in the one applied first, see [`YamlProfile::priority`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConversionError::UnresolvedSymbols`] listing every symbol not given as an RVA,
+    /// [`ConversionError::MissingCompilerSettings`] if a profile has no compiler settings, or
+    /// [`ConversionError::TargetsOutsideProfile`] if a pass targets symbols outside its profile.
+    pub fn try_into_cdconfig(self) -> Result<CDConfig, ConversionError> {
+        let names: Vec<String> = self
+            .profiles
+            .iter()
+            .flat_map(|p| {
+                let targets = p
+                    .passes
+                    .iter()
+                    .filter(|entry| entry.enabled)
+                    .filter_map(|entry| entry.targets.as_deref())
+                    .flatten();
+                p.symbols.iter().chain(targets)
+            })
+            .filter_map(|symbol| match symbol {
+                YamlSymbol::Rva(_) => None,
+                symbol => Some(symbol.to_string()),
+            })
+            .collect();
+        if !names.is_empty() {
+            return Err(ConversionError::UnresolvedSymbols(names));
+        }
+
+        let mut profiles = Vec::with_capacity(self.profiles.len());
+        let mut taken: HashSet<String> = self.profiles.iter().map(|p| p.name.clone()).collect();
+        let mut claimed = HashSet::new();
+        for profile in self.profiles_by_priority() {
+            if profile.compiler_settings.is_some() && profile.compiler_preset.is_some() {
+                return Err(ConversionError::ConflictingCompilerSettings(
+                    profile.name.clone(),
+                ));
+            }
+            let compiler_settings = self
+                .compiler_settings_for(profile)
+                .ok_or_else(|| ConversionError::MissingCompilerSettings(profile.name.clone()))?;
+            let mut symbols: Vec<u64> = profile
+                .symbols
+                .iter()
+                .filter_map(|symbol| match symbol {
+                    YamlSymbol::Rva(rva) => Some(*rva),
+                    _ => None,
+                })
+                .collect();
+            symbols.sort_unstable();
+            symbols.dedup();
+            let shared: HashSet<u64> = symbols
+                .iter()
+                .copied()
+                .filter(|rva| !claimed.insert(*rva))
+                .collect();
+            symbols.retain(|rva| !shared.contains(rva));
+
+            let mut passes = Vec::new();
+            for entry in profile.passes.iter().filter(|entry| entry.enabled) {
+                let targets = entry.targets.as_ref().map(|targets| {
+                    targets
+                        .iter()
+                        .filter_map(|symbol| match symbol {
+                            YamlSymbol::Rva(rva) => Some(*rva),
+                            _ => None,
+                        })
+                        .filter(|rva| !shared.contains(rva))
+                        .collect::<HashSet<u64>>()
+                });
+                if let Some(targets) = &targets
+                    && !targets.iter().all(|rva| symbols.binary_search(rva).is_ok())
+                {
+                    return Err(ConversionError::TargetsOutsideProfile {
+                        profile: profile.name.clone(),
+                        pass: entry.pass.name().to_owned(),
+                    });
+                }
+                passes.push((entry.pass.clone(), targets));
+            }
+            profiles.extend(CDProfile::split_by_pass_targets(
+                &profile.name,
+                passes,
+                compiler_settings.into_owned(),
+                symbols,
+                &mut taken,
+            ));
+        }
+
+        Ok(CDConfig {
+            module_settings: self.module_settings,
+            profiles,
+        })
+    }
+
+    /// Iterates over the enabled passes of every profile, paired with their profile, in
+    /// declaration order. Disabled passes are skipped since they are never applied.
+    pub fn all_passes(&self) -> impl Iterator<Item = (&YamlProfile, &ObfuscationPass)> {
+        self.profiles
+            .iter()
+            .flat_map(|profile| profile.enabled_passes().map(move |pass| (profile, pass)))
+    }
+
+    /// Returns the profiles in the order they are applied, see [`YamlProfile::priority`].
+    pub fn profiles_by_priority(&self) -> Vec<&YamlProfile> {
+        let mut profiles: Vec<&YamlProfile> = self.profiles.iter().collect();
+        profiles.sort_by_key(|p| p.priority.unwrap_or(0));
+        profiles
+    }
+
+    /// Returns the compiler settings that apply to `profile`: its own if present, then
+    /// those of its `compiler_preset`, otherwise [`YamlConfig::default_compiler_settings`].
+    ///
+    /// A profile setting both `compiler_settings` and `compiler_preset` is a conflict that
+    /// [`YamlConfig::try_into_cdconfig`] and the validators reject; here its own settings win.
+    pub fn compiler_settings_for<'a>(
+        &'a self,
+        profile: &'a YamlProfile,
+    ) -> Option<Cow<'a, CDCompilerSettings>> {
+        if let Some(settings) = &profile.compiler_settings {
+            return Some(Cow::Borrowed(settings));
+        }
+        if let Some(preset) = profile.compiler_preset {
+            return Some(Cow::Owned(CDCompilerSettings::from_preset(preset)));
+        }
+        self.default_compiler_settings.as_ref().map(Cow::Borrowed)
+    }
 }
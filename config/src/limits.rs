@@ -0,0 +1,199 @@
+//! Size limits checked before a [`CDConfig`] is submitted.
+
+use crate::{CDConfig, CDProfile};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Size limits for [`CDConfig::check_limits`].
+///
+/// The defaults are conservative caps meant to catch runaway configs (e.g. an unfiltered
+/// `All` symbol in many profiles) before they are uploaded; raise them if the backend
+/// accepts more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum number of profiles. Defaults to 64.
+    pub max_profiles: usize,
+    /// Maximum number of symbols across all profiles. Defaults to 100,000.
+    pub max_symbols: usize,
+    /// Maximum number of symbols in a single profile. Defaults to 10,000. Larger profiles
+    /// can be divided with [`split_profile`].
+    pub max_symbols_per_profile: usize,
+    /// Maximum number of passes in a single profile. Defaults to 64.
+    pub max_passes_per_profile: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_profiles: 64,
+            max_symbols: 100_000,
+            max_symbols_per_profile: 10_000,
+            max_passes_per_profile: 64,
+        }
+    }
+}
+
+/// A limit exceeded by a config, as reported by [`CDConfig::check_limits`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitError {
+    /// The config has more profiles than [`Limits::max_profiles`].
+    TooManyProfiles { limit: usize, actual: usize },
+    /// The profiles target more symbols in total than [`Limits::max_symbols`].
+    TooManySymbols { limit: usize, actual: usize },
+    /// A profile has more passes than [`Limits::max_passes_per_profile`].
+    TooManyPasses {
+        profile: String,
+        limit: usize,
+        actual: usize,
+    },
+    /// A profile targets more symbols than [`Limits::max_symbols_per_profile`].
+    TooManyProfileSymbols {
+        profile: String,
+        limit: usize,
+        actual: usize,
+    },
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitError::TooManyProfiles { limit, actual } => write!(
+                f,
+                "config has {} profiles, {} over the limit of {}",
+                actual,
+                actual - limit,
+                limit
+            ),
+            LimitError::TooManySymbols { limit, actual } => write!(
+                f,
+                "config targets {} symbols, {} over the limit of {}",
+                actual,
+                actual - limit,
+                limit
+            ),
+            LimitError::TooManyPasses {
+                profile,
+                limit,
+                actual,
+            } => write!(
+                f,
+                "profile `{}` has {} passes, {} over the limit of {}",
+                profile,
+                actual,
+                actual - limit,
+                limit
+            ),
+            LimitError::TooManyProfileSymbols {
+                profile,
+                limit,
+                actual,
+            } => write!(
+                f,
+                "profile `{}` targets {} symbols, {} over the limit of {}",
+                profile,
+                actual,
+                actual - limit,
+                limit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl CDConfig {
+    /// Checks the config against size limits, to fail before submission rather than on
+    /// the backend.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first exceeded limit, checking the profile count, then each profile's
+    /// pass count, then each profile's symbol count, then the total symbol count.
+    pub fn check_limits(&self, limits: &Limits) -> Result<(), LimitError> {
+        if self.profiles.len() > limits.max_profiles {
+            return Err(LimitError::TooManyProfiles {
+                limit: limits.max_profiles,
+                actual: self.profiles.len(),
+            });
+        }
+
+        if let Some(profile) = self
+            .profiles
+            .iter()
+            .find(|p| p.passes.len() > limits.max_passes_per_profile)
+        {
+            return Err(LimitError::TooManyPasses {
+                profile: profile.name.clone(),
+                limit: limits.max_passes_per_profile,
+                actual: profile.passes.len(),
+            });
+        }
+
+        if let Some(profile) = self
+            .profiles
+            .iter()
+            .find(|p| p.symbols.len() > limits.max_symbols_per_profile)
+        {
+            return Err(LimitError::TooManyProfileSymbols {
+                profile: profile.name.clone(),
+                limit: limits.max_symbols_per_profile,
+                actual: profile.symbols.len(),
+            });
+        }
+
+        let symbols: usize = self.profiles.iter().map(|p| p.symbols.len()).sum();
+        if symbols > limits.max_symbols {
+            return Err(LimitError::TooManySymbols {
+                limit: limits.max_symbols,
+                actual: symbols,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits `profile` into profiles of at most `max_symbols` symbols each, usually
+/// [`Limits::max_symbols_per_profile`], so it passes [`CDConfig::check_limits`].
+///
+/// Every part keeps the passes and compiler settings of `profile`. The first part keeps its
+/// name and the others are named `name_2`, `name_3`, and so on, skipping names in
+/// `taken`. Every name used is added to `taken`, see [`CDProfile::split_by_pass_targets`].
+/// Symbols keep their order, and a profile already within the limit is returned as is. A
+/// `max_symbols` of 0 is treated as 1.
+pub fn split_profile(
+    profile: CDProfile,
+    max_symbols: usize,
+    taken: &mut HashSet<String>,
+) -> Vec<CDProfile> {
+    let max_symbols = max_symbols.max(1);
+    taken.insert(profile.name.clone());
+    if profile.symbols.len() <= max_symbols {
+        return vec![profile];
+    }
+
+    profile
+        .symbols
+        .chunks(max_symbols)
+        .enumerate()
+        .map(|(i, symbols)| CDProfile {
+            name: if i == 0 {
+                profile.name.clone()
+            } else {
+                part_name(&profile.name, taken)
+            },
+            passes: profile.passes.clone(),
+            compiler_settings: profile.compiler_settings.clone(),
+            symbols: symbols.to_vec(),
+        })
+        .collect()
+}
+
+/// Returns the first of `{base}_2`, `{base}_3`, ... that is not in `taken`, and adds it to
+/// `taken`.
+fn part_name(base: &str, taken: &mut HashSet<String>) -> String {
+    (2..)
+        .map(|n| format!("{}_{}", base, n))
+        .find(|name| taken.insert(name.clone()))
+        .expect("some suffix is free")
+}
@@ -0,0 +1,127 @@
+//! Detection of settings that are valid but have no effect.
+//!
+//! Unlike [`validate_config`](crate::validate_config), which reports settings that are
+//! wrong, the lints here flag configuration effort that is silently wasted. Settings that
+//! `validate_config` already rejects, such as `iterations: 0`, are not linted again.
+
+use crate::{CDCompilerSettings, Tunable, YamlConfig};
+use std::fmt;
+
+/// What kind of redundancy a [`Lint`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    /// An enabled pass has `probability: 0`, so it never applies.
+    PassNeverApplies,
+    /// A setting is switched on, but the value it relies on is empty or zero.
+    EmptyValue,
+    /// A profile has no enabled passes.
+    NoPasses,
+}
+
+/// A setting that has no effect, as reported by [`lint_config`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lint {
+    /// What kind of redundancy this is.
+    pub kind: LintKind,
+    /// Path of the redundant field, e.g. `profiles[0].passes[1].probability`.
+    pub path: String,
+    /// Human-readable description of why the setting has no effect.
+    pub message: String,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "lint: {}: {}", self.path, self.message)
+    }
+}
+
+/// Finds settings in `config` that are valid but have no effect. Disabled passes are
+/// intentionally inert and are not reported.
+pub fn lint_config(config: &YamlConfig) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    let mut push = |kind, path: String, message: &str| {
+        lints.push(Lint {
+            kind,
+            path,
+            message: message.to_owned(),
+        })
+    };
+
+    let module = &config.module_settings;
+    if module.custom_section_name.enabled && module.custom_section_name.value.is_empty() {
+        push(
+            LintKind::EmptyValue,
+            "module_settings.custom_section_name.value".to_owned(),
+            "custom section names are enabled but the name is empty",
+        );
+    }
+    if module.fake_pdb_string.enabled && module.fake_pdb_string.value.is_empty() {
+        push(
+            LintKind::EmptyValue,
+            "module_settings.fake_pdb_string.value".to_owned(),
+            "the fake PDB string is enabled but empty",
+        );
+    }
+
+    if let Some(settings) = &config.default_compiler_settings {
+        lint_compiler_settings(&mut push, "default_compiler_settings", settings);
+    }
+
+    for (i, profile) in config.profiles.iter().enumerate() {
+        let profile_path = format!("profiles[{}]", i);
+
+        if let Some(settings) = &profile.compiler_settings {
+            lint_compiler_settings(
+                &mut push,
+                &format!("{}.compiler_settings", profile_path),
+                settings,
+            );
+        }
+
+        if profile.enabled_passes().next().is_none() {
+            push(
+                LintKind::NoPasses,
+                format!("{}.passes", profile_path),
+                "the profile has no enabled passes",
+            );
+        }
+
+        for (j, entry) in profile.passes.iter().enumerate() {
+            if !entry.enabled {
+                continue;
+            }
+            let pass_path = format!("{}.passes[{}]", profile_path, j);
+            if entry.pass.probability() == Some(0) {
+                push(
+                    LintKind::PassNeverApplies,
+                    format!("{}.probability", pass_path),
+                    "the pass never applies with a probability of 0",
+                );
+            }
+        }
+    }
+
+    lints
+}
+
+fn lint_compiler_settings(
+    push: &mut impl FnMut(LintKind, String, &str),
+    path: &str,
+    settings: &CDCompilerSettings,
+) {
+    let assembler = &settings.assembler_settings;
+    if assembler.random_prefix_chance > 0.0 && assembler.instruction_prefix.is_empty() {
+        push(
+            LintKind::EmptyValue,
+            format!("{}.assembler_settings.instruction_prefix", path),
+            "a random prefix chance is set but the instruction prefix is empty",
+        );
+    }
+    if assembler.nop_insertion && assembler.nop_insertion_chance == 0.0 {
+        push(
+            LintKind::EmptyValue,
+            format!("{}.assembler_settings.nop_insertion_chance", path),
+            "junk insertion is enabled but its chance is 0",
+        );
+    }
+}
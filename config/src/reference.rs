@@ -0,0 +1,259 @@
+//! Reference of the options a YAML config can set.
+
+use serde::Serialize;
+use std::fmt;
+
+/// A single config option, as listed by [`config_reference`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FieldDoc {
+    /// Dotted path of the option in the YAML config, e.g. `module_settings.import_protection`.
+    /// `[]` marks a list element.
+    pub path: &'static str,
+    /// Type of the value, as written in YAML.
+    pub ty: &'static str,
+    /// What the option does.
+    pub doc: &'static str,
+}
+
+impl fmt::Display for FieldDoc {
+    /// Formats the option as `path (type): doc`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.path, self.ty, self.doc)
+    }
+}
+
+/// Returns every top-level, module, profile and compiler option of a YAML config, in the
+/// order they are usually written.
+///
+/// Options under `compiler_settings` apply to both `default_compiler_settings` and
+/// `profiles[].compiler_settings`. The tunables of individual passes are documented on
+/// [`crate::ObfuscationPass`] instead.
+///
+/// The list is maintained by hand next to the structs; add an entry with every new field.
+/// A test serializes a config setting every option and fails on any that is missing here.
+pub fn config_reference() -> Vec<FieldDoc> {
+    const FIELDS: &[(&str, &str, &str)] = &[
+        ("version", "string", "Version of the config file format."),
+        (
+            "module_settings.ida_crasher",
+            "bool",
+            "Crash the IDA decompiler intentionally.",
+        ),
+        (
+            "module_settings.import_protection",
+            "bool",
+            "Enable IAT/import protection.",
+        ),
+        (
+            "module_settings.obscure_entry_point",
+            "bool",
+            "Obscure the entry point of the module with anti-tamper and anti-debug tactics.",
+        ),
+        (
+            "module_settings.clear_unwind_info",
+            "bool",
+            "Clear unwind information. Makes functions harder to locate, but structured exception handling no longer works.",
+        ),
+        (
+            "module_settings.tls_callback_protection",
+            "bool",
+            "Protect TLS callbacks against tampering and hooking. Only applies to user-mode images.",
+        ),
+        (
+            "module_settings.fake_pdb_string.enabled",
+            "bool",
+            "Emit a fake PDB path.",
+        ),
+        (
+            "module_settings.fake_pdb_string.value",
+            "string",
+            "Fake PDB path to emit.",
+        ),
+        (
+            "module_settings.custom_section_name.enabled",
+            "bool",
+            "Rename the section holding obfuscated code.",
+        ),
+        (
+            "module_settings.custom_section_name.value",
+            "string",
+            "Section name to use, at most 8 bytes long.",
+        ),
+        (
+            "module_settings.custom_section_name.allow_standard_name",
+            "bool",
+            "Allow a standard section name such as `.data`, which usually corrupts images that already have that section.",
+        ),
+        (
+            "module_settings.anti_tamper.enabled",
+            "bool",
+            "Enable runtime anti-debug and anti-VM detection.",
+        ),
+        (
+            "module_settings.anti_tamper.anti_debug",
+            "bool",
+            "Detect attached debuggers.",
+        ),
+        (
+            "module_settings.anti_tamper.anti_vm",
+            "bool",
+            "Detect execution inside a virtual machine or hypervisor.",
+        ),
+        (
+            "module_settings.anti_tamper.crash_on_detection",
+            "bool",
+            "Crash the process on detection instead of exiting gracefully.",
+        ),
+        (
+            "module_settings.max_section_entropy",
+            "float, optional",
+            "Upper bound, in bits per byte (0.0-8.0), for the entropy of obfuscated sections. A hint to the backend.",
+        ),
+        (
+            "module_settings.pad_sections",
+            "bool",
+            "Pad emitted sections with low-entropy filler. A hint to the backend.",
+        ),
+        (
+            "module_settings.min_cpu_features",
+            "Baseline | Sse3 | Sse42 | Avx2, optional",
+            "Oldest CPU the protected binary must run on. Passes needing newer instructions are rejected.",
+        ),
+        (
+            "default_compiler_settings",
+            "compiler settings, optional",
+            "Compiler settings for profiles that set neither compiler_settings nor compiler_preset.",
+        ),
+        ("profiles", "list", "Obfuscation profiles to apply."),
+        (
+            "profiles[].name",
+            "string",
+            "Profile name, referenced by source macros.",
+        ),
+        (
+            "profiles[].passes",
+            "list of passes",
+            "Passes to apply to the profile's symbols, in order.",
+        ),
+        (
+            "profiles[].passes[].enabled",
+            "bool",
+            "Whether the pass is applied. Defaults to true.",
+        ),
+        (
+            "profiles[].passes[].targets",
+            "list of symbols, optional",
+            "Restrict the pass to these symbols, a subset of the profile's symbols.",
+        ),
+        (
+            "profiles[].passes[].min_function_size",
+            "integer, optional",
+            "Skip functions smaller than this many bytes, using the sizes reported by analysis.",
+        ),
+        (
+            "profiles[].compiler_settings",
+            "compiler settings, optional",
+            "Compiler settings for this profile. Must not be combined with compiler_preset.",
+        ),
+        (
+            "profiles[].compiler_preset",
+            "fast | balanced | max, optional",
+            "Preset expanded into the profile's compiler settings.",
+        ),
+        (
+            "profiles[].symbols",
+            "list of symbols",
+            "Symbols targeted by the profile: names, !Rva, All, !Section or !RefCountAtLeast.",
+        ),
+        (
+            "profiles[].exclude",
+            "list of symbols",
+            "Symbols removed from the resolved symbols, in the same forms.",
+        ),
+        (
+            "profiles[].color",
+            "string, optional",
+            "Only used by the SaaS UI.",
+        ),
+        (
+            "profiles[].priority",
+            "integer, optional",
+            "Order in which the profile is applied; lower values first. Defaults to 0.",
+        ),
+        (
+            "compiler_settings.assembler_settings.shuffle_basic_blocks",
+            "bool",
+            "Shuffle basic blocks.",
+        ),
+        (
+            "compiler_settings.assembler_settings.instruction_prefix",
+            "string",
+            "Instruction prefix to prepend to emitted instructions.",
+        ),
+        (
+            "compiler_settings.assembler_settings.random_prefix_chance",
+            "float",
+            "Chance of randomly applying the prefix.",
+        ),
+        (
+            "compiler_settings.assembler_settings.nop_insertion",
+            "bool",
+            "Insert junk sequences between real instructions.",
+        ),
+        (
+            "compiler_settings.assembler_settings.nop_insertion_chance",
+            "float",
+            "Chance (0.0-1.0) of inserting a junk sequence after each instruction.",
+        ),
+        (
+            "compiler_settings.optimization_settings.constant_propagation",
+            "bool",
+            "Enable constant propagation.",
+        ),
+        (
+            "compiler_settings.optimization_settings.instruction_combine",
+            "bool",
+            "Enable instruction combining.",
+        ),
+        (
+            "compiler_settings.optimization_settings.dead_code_elim",
+            "bool",
+            "Enable dead code elimination.",
+        ),
+        (
+            "compiler_settings.optimization_settings.prune_useless_block_params",
+            "bool",
+            "Enable pruning of unused block parameters.",
+        ),
+        (
+            "compiler_settings.optimization_settings.iterations",
+            "integer",
+            "Number of optimization iterations to run.",
+        ),
+        (
+            "compiler_settings.lifter_settings.lift_calls",
+            "bool",
+            "Lift calls into IR.",
+        ),
+        (
+            "compiler_settings.lifter_settings.calling_convention",
+            "WindowsAbi | Conservative",
+            "Calling convention used for lifting.",
+        ),
+        (
+            "compiler_settings.lifter_settings.max_stack_copy_size",
+            "integer",
+            "Max stack copy size in bytes when lifting.",
+        ),
+        (
+            "compiler_settings.lifter_settings.split_on_calls_fallback",
+            "bool",
+            "Split on calls if lifting fails.",
+        ),
+    ];
+
+    FIELDS
+        .iter()
+        .map(|&(path, ty, doc)| FieldDoc { path, ty, doc })
+        .collect()
+}
@@ -0,0 +1,130 @@
+//! Serde helpers for RVAs that keep full `u64` precision through JavaScript tooling.
+//!
+//! JSON parsers built on JavaScript numbers lose precision above 2^53. Reading accepts an
+//! integer, a hex string (`"0x1000"`) or a decimal string (`"4096"`), so RVAs written as
+//! strings by such tooling still load. With human-readable formats (JSON, YAML),
+//! [`serialize`] writes RVAs up to [`MAX_SAFE_INTEGER`] as integers and larger ones as
+//! `0x`-prefixed hex strings. Binary formats read and write plain `u64`s.
+//!
+//! Only [`YamlSymbol::Rva`] writes hex strings. The structs exchanged with the API,
+//! [`CDProfile::symbols`] and the analysis results, only use [`deserialize`], so they are
+//! always sent as integers.
+//!
+//! Use with `#[serde(with = "codedefender_config::rva")]` on a `u64` field, or
+//! `codedefender_config::rva::vec` on a `Vec<u64>`.
+//!
+//! [`YamlSymbol::Rva`]: crate::YamlSymbol::Rva
+//! [`CDProfile::symbols`]: crate::CDProfile::symbols
+
+use serde::de::{self, Visitor};
+use serde::{Deserializer, Serializer};
+use std::fmt;
+
+/// Largest integer a JavaScript number represents exactly, `2^53 - 1`.
+pub const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Serializes an RVA, as a hex string if it exceeds [`MAX_SAFE_INTEGER`].
+pub fn serialize<S: Serializer>(rva: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() && *rva > MAX_SAFE_INTEGER {
+        serializer.collect_str(&format_args!("0x{:X}", rva))
+    } else {
+        serializer.serialize_u64(*rva)
+    }
+}
+
+/// Deserializes an RVA written as an integer or as a hex or decimal string.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(RvaVisitor)
+    } else {
+        deserializer.deserialize_u64(RvaVisitor)
+    }
+}
+
+/// Parses an RVA from `0x`-prefixed hex or decimal text.
+pub fn parse(text: &str) -> Option<u64> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+struct RvaVisitor;
+
+impl Visitor<'_> for RvaVisitor {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "an RVA as an unsigned integer or a hex or decimal string"
+        )
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<u64, E> {
+        Ok(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<u64, E> {
+        u64::try_from(value).map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<u64, E> {
+        parse(value).ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
+
+/// The same conversions for a `Vec<u64>` of RVAs.
+pub mod vec {
+    use serde::de::{SeqAccess, Visitor};
+    use serde::ser::SerializeSeq;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::fmt;
+
+    struct Rva(u64);
+
+    impl Serialize for Rva {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize(&self.0, serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Rva {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::deserialize(deserializer).map(Rva)
+        }
+    }
+
+    /// Serializes a list of RVAs, each as [`super::serialize`] does.
+    pub fn serialize<S: Serializer>(rvas: &[u64], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(rvas.len()))?;
+        for &rva in rvas {
+            seq.serialize_element(&Rva(rva))?;
+        }
+        seq.end()
+    }
+
+    /// Deserializes a list of RVAs, each as [`super::deserialize`] does.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u64>, D::Error> {
+        struct RvasVisitor;
+
+        impl<'de> Visitor<'de> for RvasVisitor {
+            type Value = Vec<u64>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a list of RVAs")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u64>, A::Error> {
+                let mut rvas = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(Rva(rva)) = seq.next_element()? {
+                    rvas.push(rva);
+                }
+                Ok(rvas)
+            }
+        }
+
+        deserializer.deserialize_seq(RvasVisitor)
+    }
+}
@@ -0,0 +1,137 @@
+//! Generation of YAML configs from analysis results: starter configs, and editable
+//! configs reconstructed from a resolved [`CDConfig`].
+
+use crate::{
+    AnalysisFunction, AnalysisResult, CDConfig, CDModuleSettings, CompilerPreset, MutationEngine,
+    ObfuscationPass, ObscureControlFlow, ObscureReferences, YAML_CONFIG_VERSION, YamlConfig,
+    YamlProfile, YamlSymbol,
+};
+#[cfg(feature = "yaml")]
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+/// Name of the profile created by [`YamlConfig::from_analysis`].
+const STARTER_PROFILE_NAME: &str = "default";
+
+/// Options for [`YamlConfig::from_analysis`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StarterOptions {
+    /// Only target the `n` most-referenced functions. `None` targets every analyzed
+    /// function.
+    pub top_n: Option<usize>,
+}
+
+impl YamlConfig {
+    /// Generates a starter config from an analysis result.
+    ///
+    /// The config has the module settings recommended for the analyzed environment (see
+    /// [`CDModuleSettings::recommended_for`]) and a single profile applying
+    /// `MutationEngine`, `ObscureReferences` and `ObscureControlFlow` with the
+    /// [`CompilerPreset::Balanced`] compiler preset. Functions are selected by descending
+    /// `ref_count` (ties keep analysis order) when [`StarterOptions::top_n`] is set. Each
+    /// function is written by name when its name resolves back to it, and by RVA otherwise.
+    pub fn from_analysis(analysis: &AnalysisResult, opts: StarterOptions) -> YamlConfig {
+        let functions: Vec<&AnalysisFunction> = match opts.top_n {
+            Some(n) => {
+                let mut functions = analysis.functions_by_ref_count_desc();
+                functions.truncate(n);
+                functions
+            }
+            None => analysis.functions.iter().collect(),
+        };
+
+        let first_rva = first_rva_by_name(analysis);
+        let symbols = functions
+            .into_iter()
+            .map(|function| symbol_for(&first_rva, &function.symbol, function.rva))
+            .collect();
+
+        YamlConfig {
+            version: YAML_CONFIG_VERSION.to_string(),
+            module_settings: CDModuleSettings::recommended_for(analysis.environment),
+            default_compiler_settings: None,
+            profiles: vec![YamlProfile {
+                name: STARTER_PROFILE_NAME.to_string(),
+                passes: vec![
+                    ObfuscationPass::MutationEngine(MutationEngine::default()).into(),
+                    ObfuscationPass::ObscureReferences(ObscureReferences::default()).into(),
+                    ObfuscationPass::ObscureControlFlow(ObscureControlFlow { probability: 100 })
+                        .into(),
+                ],
+                compiler_settings: None,
+                compiler_preset: Some(CompilerPreset::Balanced),
+                symbols,
+                exclude: Vec::new(),
+                color: None,
+                priority: None,
+                #[cfg(feature = "yaml")]
+                extra: BTreeMap::new(),
+            }],
+            #[cfg(feature = "yaml")]
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+/// Reconstructs an editable [`YamlConfig`] from a resolved [`CDConfig`].
+///
+/// Each profile keeps its passes and explicit compiler settings, in the submitted order.
+/// Every RVA is written by name when analysis names a function that the name resolves back
+/// to, and by RVA otherwise. The version is set to [`YAML_CONFIG_VERSION`].
+pub fn cdconfig_to_yaml(config: &CDConfig, analysis: &AnalysisResult) -> YamlConfig {
+    let first_rva = first_rva_by_name(analysis);
+    let names: HashMap<u64, &str> = analysis
+        .functions
+        .iter()
+        .map(|function| (function.rva, function.symbol.as_str()))
+        .collect();
+
+    let profiles = config
+        .profiles
+        .iter()
+        .map(|profile| YamlProfile {
+            name: profile.name.clone(),
+            passes: profile.passes.iter().cloned().map(Into::into).collect(),
+            compiler_settings: Some(profile.compiler_settings.clone()),
+            compiler_preset: None,
+            symbols: profile
+                .symbols
+                .iter()
+                .map(|&rva| symbol_for(&first_rva, names.get(&rva).copied().unwrap_or(""), rva))
+                .collect(),
+            exclude: Vec::new(),
+            color: None,
+            priority: None,
+            #[cfg(feature = "yaml")]
+            extra: BTreeMap::new(),
+        })
+        .collect();
+
+    YamlConfig {
+        version: YAML_CONFIG_VERSION.to_string(),
+        module_settings: config.module_settings.clone(),
+        default_compiler_settings: None,
+        profiles,
+        #[cfg(feature = "yaml")]
+        extra: BTreeMap::new(),
+    }
+}
+
+/// Maps each function name to the RVA of the first function with that name, which is what
+/// name lookups resolve to.
+fn first_rva_by_name(analysis: &AnalysisResult) -> HashMap<&str, u64> {
+    let mut first_rva: HashMap<&str, u64> = HashMap::new();
+    for function in &analysis.functions {
+        first_rva.entry(&function.symbol).or_insert(function.rva);
+    }
+    first_rva
+}
+
+/// Writes a function by name when its name resolves back to `rva`, and by RVA otherwise.
+fn symbol_for(first_rva: &HashMap<&str, u64>, name: &str, rva: u64) -> YamlSymbol {
+    if !name.is_empty() && first_rva.get(name) == Some(&rva) {
+        YamlSymbol::Name(name.to_owned())
+    } else {
+        YamlSymbol::Rva(rva)
+    }
+}
@@ -0,0 +1,127 @@
+//! Migration of YAML config files written for older config versions.
+
+use crate::{ConfigError, YAML_CONFIG_VERSION, YamlConfig};
+use serde_yaml::{Mapping, Value};
+use std::fmt;
+
+/// Errors produced by [`upgrade_config`].
+#[derive(Debug)]
+pub enum UpgradeError {
+    /// The config has no `version` field.
+    MissingVersion,
+    /// No migration path exists from this version. Holds the version.
+    UnsupportedVersion(String),
+    /// The config could not be parsed before or after migration.
+    Config(ConfigError),
+}
+
+impl fmt::Display for UpgradeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpgradeError::MissingVersion => write!(f, "config has no `version` field"),
+            UpgradeError::UnsupportedVersion(version) => write!(
+                f,
+                "cannot upgrade config version {} to {}",
+                version, YAML_CONFIG_VERSION
+            ),
+            UpgradeError::Config(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for UpgradeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UpgradeError::Config(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConfigError> for UpgradeError {
+    fn from(e: ConfigError) -> Self {
+        UpgradeError::Config(e)
+    }
+}
+
+impl From<serde_yaml::Error> for UpgradeError {
+    fn from(e: serde_yaml::Error) -> Self {
+        UpgradeError::Config(ConfigError::Yaml(e))
+    }
+}
+
+/// A single migration step from `from` to `to`.
+struct Migration {
+    from: &'static str,
+    to: &'static str,
+    apply: fn(&mut Mapping),
+}
+
+/// Migrations keyed by source version. Each step moves a config exactly one version
+/// forward; [`upgrade_config`] chains them until [`YAML_CONFIG_VERSION`] is reached.
+///
+/// Only changes that an older config does not load under need a step. Fields added with a
+/// serde default (module settings such as `tls_callback_protection`, `exclude`,
+/// `priority`, the `ObscureReferences` probability, ...) are written out by the final
+/// re-serialization.
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: "1.0.4",
+    to: "1.1.0",
+    apply: raise_zero_optimization_iterations,
+}];
+
+/// Upgrades a YAML config written for an older config version to [`YAML_CONFIG_VERSION`].
+///
+/// Fields added since the config's version are written out with their default values. The
+/// output is re-serialized, so comments and formatting of the input are not preserved.
+/// A config that is already current is returned re-serialized.
+///
+/// # Errors
+///
+/// Returns an error if the input has no version, its version has no migration path, or
+/// it cannot be parsed as a config once migrated.
+pub fn upgrade_config(old: &str) -> Result<String, UpgradeError> {
+    let mut value: Value = serde_yaml::from_str(old)?;
+    let Some(root) = value.as_mapping_mut() else {
+        return Err(UpgradeError::MissingVersion);
+    };
+
+    let mut version = match root.get("version") {
+        Some(Value::String(version)) => version.clone(),
+        Some(Value::Number(version)) => version.to_string(),
+        _ => return Err(UpgradeError::MissingVersion),
+    };
+
+    while version != YAML_CONFIG_VERSION {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|m| m.from == version)
+            .ok_or_else(|| UpgradeError::UnsupportedVersion(version.clone()))?;
+        (migration.apply)(root);
+        version = migration.to.to_owned();
+    }
+    root.insert("version".into(), version.into());
+
+    let config: YamlConfig = serde_yaml::from_value(value)?;
+    Ok(config.to_yaml_string()?)
+}
+
+/// 1.1.0 rejects `optimization_settings.iterations` of 0, which 1.0.4 accepted. Rewrites it
+/// in every profile as 1, the smallest count that validates.
+fn raise_zero_optimization_iterations(root: &mut Mapping) {
+    let Some(Value::Sequence(profiles)) = root.get_mut("profiles") else {
+        return;
+    };
+    for profile in profiles {
+        let Some(iterations) = profile
+            .get_mut("compiler_settings")
+            .and_then(|settings| settings.get_mut("optimization_settings"))
+            .and_then(|optimization| optimization.get_mut("iterations"))
+        else {
+            continue;
+        };
+        if iterations.as_u64() == Some(0) {
+            *iterations = 1.into();
+        }
+    }
+}
@@ -0,0 +1,659 @@
+//! Checks that catch configurations the backend would accept but that are unlikely to do
+//! what the user intended.
+//!
+//! Every problem is reported as a [`ValidationError`] carrying the path of the offending
+//! field (e.g. `profiles[2].passes[0].probability`), collected into a [`ValidationReport`].
+
+use crate::{
+    AnalysisResult, BitWidths, CDConfig, Limits, LoopEncodeSemantics, MixedBooleanArithmetic,
+    MutationEngine, ObfuscationPass, PeEnvironment, Semantics, Tunable, YAML_CONFIG_VERSION,
+    YamlConfig, lint_config,
+};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Largest accepted value for optimization and per-pass `iterations`. Higher values
+/// multiply obfuscation time without meaningfully increasing protection.
+pub const MAX_ITERATIONS: u32 = 16;
+
+/// Longest accepted `fake_pdb_string` value, matching the Windows `MAX_PATH` limit.
+const MAX_FAKE_PDB_LEN: usize = 260;
+
+/// Longest PE section name, the size of the name field in a section header.
+const MAX_SECTION_NAME_LEN: usize = 8;
+
+/// Section names emitted by common toolchains, which a custom section name must not reuse.
+const STANDARD_SECTION_NAMES: &[&str] = &[
+    ".text", ".data", ".rdata", ".bss", ".idata", ".edata", ".pdata", ".xdata", ".reloc", ".rsrc",
+    ".tls", ".CRT", ".debug", ".didat", ".gfids", ".00cfg", ".textbss", ".sxdata",
+];
+
+/// How serious a [`ValidationError`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The config is usable but probably not what was intended.
+    Warning,
+    /// The config should not be submitted.
+    Error,
+}
+
+/// A single problem found while validating a config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// Path of the offending field, e.g. `profiles[2].passes[0].probability`.
+    pub path: String,
+    /// The offending value, when the problem is about a single value.
+    pub value: Option<String>,
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Warning => write!(f, "warning: ")?,
+            Severity::Error => write!(f, "error: ")?,
+        }
+        match &self.value {
+            Some(value) => write!(f, "{} = {}: {}", self.path, value, self.message),
+            None => write!(f, "{}: {}", self.path, self.message),
+        }
+    }
+}
+
+/// All problems found by one or more validators.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    /// The problems, in the order they were found.
+    pub issues: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no problems were found.
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns `true` if any problem is an error.
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Iterates over the problems with [`Severity::Error`].
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationError> {
+        self.issues.iter().filter(|i| i.severity == Severity::Error)
+    }
+
+    /// Iterates over the problems with [`Severity::Warning`].
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationError> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == Severity::Warning)
+    }
+
+    /// Returns `Err(self)` if the report contains errors. Warnings alone are not a failure.
+    pub fn into_result(self) -> Result<(), ValidationReport> {
+        if self.has_errors() { Err(self) } else { Ok(()) }
+    }
+
+    /// Returns `Err(self)` if the report contains any problem, treating warnings as errors.
+    /// Useful in CI, where warnings should not be allowed to accumulate.
+    pub fn into_strict_result(self) -> Result<(), ValidationReport> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+
+    /// Appends the problems of another report.
+    pub fn merge(&mut self, other: ValidationReport) {
+        self.issues.extend(other.issues);
+    }
+
+    fn push(
+        &mut self,
+        severity: Severity,
+        path: impl Into<String>,
+        value: Option<String>,
+        message: impl Into<String>,
+    ) {
+        self.issues.push(ValidationError {
+            severity,
+            path: path.into(),
+            value,
+            message: message.into(),
+        });
+    }
+
+    fn error(
+        &mut self,
+        path: impl Into<String>,
+        value: impl fmt::Display,
+        message: impl Into<String>,
+    ) {
+        self.push(Severity::Error, path, Some(value.to_string()), message);
+    }
+
+    fn warning(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.push(Severity::Warning, path, None, message);
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationReport {}
+
+/// Runs the checks that need nothing but the config itself.
+pub fn validate_config(config: &YamlConfig) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    check_version(&mut report, &config.version);
+
+    let mut seen_names = HashSet::new();
+    for (i, profile) in config.profiles.iter().enumerate() {
+        let path = format!("profiles[{}].name", i);
+        if profile.name.is_empty() {
+            report.error(path, "\"\"", "must not be empty");
+        } else if !seen_names.insert(profile.name.as_str()) {
+            report.error(
+                path,
+                &profile.name,
+                "is used by an earlier profile; macros are matched to profiles by name",
+            );
+        }
+        if let Some(color) = &profile.color
+            && !is_hex_color(color)
+        {
+            report.push(
+                Severity::Warning,
+                format!("profiles[{}].color", i),
+                Some(color.clone()),
+                "is not a `#RRGGBB` color and will be ignored by the UI",
+            );
+        }
+    }
+
+    if let Some(entropy) = config.module_settings.max_section_entropy
+        && !(0.0..=8.0).contains(&entropy)
+    {
+        report.error(
+            "module_settings.max_section_entropy",
+            entropy,
+            "must be between 0.0 and 8.0 bits per byte",
+        );
+    }
+
+    let fake_pdb = &config.module_settings.fake_pdb_string;
+    if fake_pdb.enabled {
+        check_fake_pdb_string(&mut report, &fake_pdb.value);
+    }
+
+    let section_name = &config.module_settings.custom_section_name;
+    if section_name.enabled {
+        check_custom_section_name(
+            &mut report,
+            &section_name.value,
+            section_name.allow_standard_name,
+        );
+    }
+
+    // Analysis doesn't report which functions rely on exception handling, so this can't be
+    // narrowed down to the affected profiles.
+    if config.module_settings.clear_unwind_info {
+        report.warning(
+            "module_settings.clear_unwind_info",
+            "structured exception handling will not work in the output; leave this disabled \
+             unless no protected code throws or catches exceptions",
+        );
+    }
+
+    let anti_tamper = &config.module_settings.anti_tamper;
+    if anti_tamper.enabled && !anti_tamper.anti_debug && !anti_tamper.anti_vm {
+        report.warning(
+            "module_settings.anti_tamper",
+            "is enabled without `anti_debug` or `anti_vm` and will detect nothing",
+        );
+    }
+
+    for (i, profile) in config.profiles.iter().enumerate() {
+        let profile_path = format!("profiles[{}]", i);
+
+        if profile.compiler_settings.is_some() && profile.compiler_preset.is_some() {
+            report.push(
+                Severity::Error,
+                format!("{}.compiler_preset", profile_path),
+                None,
+                "must not be combined with `compiler_settings`",
+            );
+        }
+
+        match config.compiler_settings_for(profile) {
+            Some(compiler_settings) => {
+                let path = if profile.compiler_settings.is_some() {
+                    format!("{}.compiler_settings", profile_path)
+                } else if profile.compiler_preset.is_some() {
+                    format!("{}.compiler_preset", profile_path)
+                } else {
+                    "default_compiler_settings".to_owned()
+                };
+                let assembler = &compiler_settings.assembler_settings;
+                check_chance(
+                    &mut report,
+                    format!("{}.assembler_settings.random_prefix_chance", path),
+                    assembler.random_prefix_chance,
+                );
+                check_chance(
+                    &mut report,
+                    format!("{}.assembler_settings.nop_insertion_chance", path),
+                    assembler.nop_insertion_chance,
+                );
+                check_iterations(
+                    &mut report,
+                    format!("{}.optimization_settings.iterations", path),
+                    compiler_settings.optimization_settings.iterations,
+                );
+            }
+            None => report.push(
+                Severity::Error,
+                format!("{}.compiler_settings", profile_path),
+                None,
+                format!(
+                    "profile `{}` has no compiler settings and no `default_compiler_settings` is set",
+                    profile.name
+                ),
+            ),
+        }
+
+        // Disabled passes are checked too, so they are still valid when re-enabled.
+        // Whether targets are a subset of the profile's symbols is only known once both are
+        // resolved against analysis, so it is checked when the config is built.
+        for (j, entry) in profile.passes.iter().enumerate() {
+            let pass_path = format!("{}.passes[{}]", profile_path, j);
+            if entry.targets.as_ref().is_some_and(Vec::is_empty) {
+                report.warning(
+                    format!("{}.targets", pass_path),
+                    format!(
+                        "{} in profile `{}` is restricted to no symbol and will never apply",
+                        entry.pass.name(),
+                        profile.name
+                    ),
+                );
+            }
+            if let Some(iterations) = entry.pass.iterations() {
+                check_iterations(&mut report, format!("{}.iterations", pass_path), iterations);
+            }
+            if let Some(probability) = entry.pass.probability() {
+                check_probability(
+                    &mut report,
+                    format!("{}.probability", pass_path),
+                    probability,
+                );
+            }
+            let (semantics, bitwidths) = match &entry.pass {
+                ObfuscationPass::LoopEncodeSemantics(p) => (Some(&p.semantics), Some(&p.bitwidths)),
+                ObfuscationPass::MixedBooleanArithmetic(p) => {
+                    (Some(&p.semantics), Some(&p.bitwidths))
+                }
+                ObfuscationPass::MutationEngine(p) => (Some(&p.semantics), Some(&p.bitwidths)),
+                ObfuscationPass::ObscureConstants(p) => (None, Some(&p.bitwidths)),
+                _ => (None, None),
+            };
+            if semantics.is_some_and(Semantics::is_empty) {
+                report.warning(
+                    format!("{}.semantics", pass_path),
+                    format!(
+                        "{} in profile `{}` has no semantic enabled and will never apply",
+                        entry.pass.name(),
+                        profile.name
+                    ),
+                );
+            }
+            if bitwidths.is_some_and(BitWidths::is_empty) {
+                report.warning(
+                    format!("{}.bitwidths", pass_path),
+                    format!(
+                        "{} in profile `{}` has no bit width enabled and will never apply",
+                        entry.pass.name(),
+                        profile.name
+                    ),
+                );
+            }
+            if let ObfuscationPass::VirtualizationProtection(vm) = &entry.pass
+                && !(1..=5).contains(&vm.complexity)
+            {
+                report.error(
+                    format!("{}.complexity", pass_path),
+                    vm.complexity,
+                    "must be between 1 and 5",
+                );
+            }
+            if let ObfuscationPass::MutationEngine(p) = &entry.pass
+                && let Some(min) = config.module_settings.min_cpu_features
+                && p.extension.required_cpu_features() > min
+            {
+                report.error(
+                    format!("{}.extension", pass_path),
+                    format_args!("{:?}", p.extension),
+                    format!(
+                        "needs {:?} CPU features, but module_settings.min_cpu_features is {:?}",
+                        p.extension.required_cpu_features(),
+                        min
+                    ),
+                );
+            }
+        }
+    }
+
+    report
+}
+
+impl YamlConfig {
+    /// Runs every check that needs nothing but the config: [`validate_config`], with the
+    /// findings of [`lint_config`] added as warnings.
+    pub fn validation_report(&self) -> ValidationReport {
+        let mut report = validate_config(self);
+        for lint in lint_config(self) {
+            report.warning(lint.path, lint.message);
+        }
+        report
+    }
+
+    /// Runs [`YamlConfig::validation_report`] and returns `Err` with the whole report,
+    /// warnings included, if it contains errors.
+    pub fn validate(&self) -> Result<(), ValidationReport> {
+        self.validation_report().into_result()
+    }
+}
+
+impl LoopEncodeSemantics {
+    /// Checks that `probability` is at most 100 and `iterations` is within
+    /// 1..=[`MAX_ITERATIONS`].
+    pub fn validate(&self) -> Result<(), ValidationReport> {
+        let mut report = ValidationReport::default();
+        check_transform_pass(&mut report, "", self.iterations, self.probability);
+        report.into_result()
+    }
+}
+
+impl MixedBooleanArithmetic {
+    /// Checks that `probability` is at most 100 and `iterations` is within
+    /// 1..=[`MAX_ITERATIONS`].
+    pub fn validate(&self) -> Result<(), ValidationReport> {
+        let mut report = ValidationReport::default();
+        check_transform_pass(&mut report, "", self.iterations, self.probability);
+        report.into_result()
+    }
+}
+
+impl MutationEngine {
+    /// Checks that `probability` is at most 100 and `iterations` is within
+    /// 1..=[`MAX_ITERATIONS`].
+    pub fn validate(&self) -> Result<(), ValidationReport> {
+        let mut report = ValidationReport::default();
+        check_transform_pass(&mut report, "", self.iterations, self.probability);
+        report.into_result()
+    }
+}
+
+/// Checks the tunables shared by the semantics-based transformation passes. `prefix` is
+/// the pass's path, or empty when validating a standalone pass.
+fn check_transform_pass(
+    report: &mut ValidationReport,
+    prefix: &str,
+    iterations: u32,
+    probability: u32,
+) {
+    let field = |name: &str| {
+        if prefix.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{}.{}", prefix, name)
+        }
+    };
+    check_iterations(report, field("iterations"), iterations);
+    check_probability(report, field("probability"), probability);
+}
+
+fn check_fake_pdb_string(report: &mut ValidationReport, value: &str) {
+    const PATH: &str = "module_settings.fake_pdb_string.value";
+
+    if value.contains('\0') {
+        report.error(PATH, format!("{:?}", value), "must not contain NUL bytes");
+    } else if value.chars().any(char::is_control) {
+        report.warning(PATH, "contains control characters");
+    }
+    if value.len() > MAX_FAKE_PDB_LEN {
+        report.error(
+            PATH,
+            format!("<{} bytes>", value.len()),
+            format!("must be at most {} bytes long", MAX_FAKE_PDB_LEN),
+        );
+    }
+    if !value.to_ascii_lowercase().ends_with(".pdb") {
+        report.warning(
+            PATH,
+            "does not end in `.pdb` and is unlikely to pass as a real PDB path",
+        );
+    }
+}
+
+fn check_custom_section_name(report: &mut ValidationReport, value: &str, allow_standard: bool) {
+    const PATH: &str = "module_settings.custom_section_name.value";
+
+    if value.len() > MAX_SECTION_NAME_LEN {
+        report.error(
+            PATH,
+            value,
+            format!("must be at most {} bytes long", MAX_SECTION_NAME_LEN),
+        );
+    }
+    // The loader doesn't care about case, so neither does the collision check.
+    if !allow_standard
+        && STANDARD_SECTION_NAMES
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(value))
+    {
+        report.error(
+            PATH,
+            value,
+            "collides with a standard section name; set `allow_standard_name` to use it anyway",
+        );
+    }
+}
+
+/// Accepts the current version and older versions, which [`upgrade_config`] can migrate.
+/// Versions newer than [`YAML_CONFIG_VERSION`] have fields this crate doesn't know.
+///
+/// [`upgrade_config`]: crate::upgrade_config
+fn check_version(report: &mut ValidationReport, version: &str) {
+    let parse = |v: &str| -> Option<Vec<u32>> { v.split('.').map(|n| n.parse().ok()).collect() };
+    let current = parse(YAML_CONFIG_VERSION).expect("YAML_CONFIG_VERSION is a valid version");
+
+    match parse(version) {
+        None => report.error("version", version, "is not a valid config version"),
+        Some(v) if v > current => report.error(
+            "version",
+            version,
+            format!(
+                "is newer than the supported version {}; update this tool",
+                YAML_CONFIG_VERSION
+            ),
+        ),
+        Some(v) if v < current => report.push(
+            Severity::Warning,
+            "version",
+            Some(version.to_owned()),
+            format!(
+                "is older than the current version {}; upgrade the config with `upgrade_config`",
+                YAML_CONFIG_VERSION
+            ),
+        ),
+        Some(_) => {}
+    }
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn check_chance(report: &mut ValidationReport, path: String, value: f64) {
+    if !(0.0..=1.0).contains(&value) {
+        report.error(path, value, "must be between 0.0 and 1.0");
+    }
+}
+
+fn check_iterations(report: &mut ValidationReport, path: String, value: u32) {
+    if !(1..=MAX_ITERATIONS).contains(&value) {
+        report.error(
+            path,
+            value,
+            format!("must be between 1 and {}", MAX_ITERATIONS),
+        );
+    }
+}
+
+fn check_probability(report: &mut ValidationReport, path: String, value: u32) {
+    if value > 100 {
+        report.error(path, value, "must be between 0 and 100");
+    }
+}
+
+/// Checks a config against the environment of the analyzed binary.
+///
+/// # Arguments
+///
+/// * `config` - The config to check.
+/// * `environment` - Environment reported by analysis (`AnalysisResult::environment`).
+pub fn validate_environment(config: &YamlConfig, environment: PeEnvironment) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    if config.module_settings.tls_callback_protection && environment != PeEnvironment::UserMode {
+        report.warning(
+            "module_settings.tls_callback_protection",
+            format!(
+                "has no effect on {:?} images, which have no TLS callbacks",
+                environment
+            ),
+        );
+    }
+
+    for (i, profile) in config.profiles.iter().enumerate() {
+        for (j, entry) in profile.passes.iter().enumerate() {
+            let pass = &entry.pass;
+            if entry.enabled && !pass.supported_environments().contains(&environment) {
+                report.push(
+                    Severity::Error,
+                    format!("profiles[{}].passes[{}]", i, j),
+                    Some(pass.name().to_owned()),
+                    format!("pass is not supported on {:?} images", environment),
+                );
+            }
+        }
+    }
+
+    if environment != PeEnvironment::UserMode {
+        for (i, profile) in config.profiles.iter().enumerate() {
+            for (j, entry) in profile.passes.iter().enumerate() {
+                if entry.enabled
+                    && matches!(entry.pass, ObfuscationPass::VirtualizationProtection(_))
+                {
+                    report.warning(
+                        format!("profiles[{}].passes[{}]", i, j),
+                        format!(
+                            "VirtualizationProtection on {:?} images may add unacceptable overhead",
+                            environment
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    report
+}
+
+/// Checks a config after its symbols have been resolved.
+///
+/// A profile without symbols is reported as a warning, since its passes will never apply.
+/// A config without any symbols at all is an error: it would ship an unprotected binary.
+pub fn validate_resolved(config: &CDConfig) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    for (i, profile) in config.profiles.iter().enumerate() {
+        if profile.symbols.is_empty() {
+            report.warning(
+                format!("profiles[{}].symbols", i),
+                format!(
+                    "profile `{}` resolved to no symbols and protects nothing",
+                    profile.name
+                ),
+            );
+        }
+    }
+    if config.profiles.iter().all(|p| p.symbols.is_empty()) {
+        report.push(
+            Severity::Error,
+            "profiles",
+            None,
+            "config resolved to no symbols, the binary would not be protected",
+        );
+    }
+
+    report
+}
+
+/// Checks the exact [`CDConfig`] that would be submitted, as the backend would see it.
+///
+/// On top of [`validate_resolved`], every symbol must be the RVA of an analyzed function or
+/// of a force-resolvable reject (see [`crate::AnalysisReject::is_force_resolvable`]), and
+/// the config must fit `limits`, usually [`Limits::default`] unless the backend is known to
+/// accept other sizes.
+///
+/// # Errors
+///
+/// Returns the full report, warnings included, if any error was found.
+pub fn validate_cdconfig(
+    config: &CDConfig,
+    analysis: &AnalysisResult,
+    limits: &Limits,
+) -> Result<(), ValidationReport> {
+    let mut report = validate_resolved(config);
+
+    let valid_rvas: HashSet<u64> = analysis
+        .functions
+        .iter()
+        .map(|f| f.rva)
+        .chain(
+            analysis
+                .rejects
+                .iter()
+                .filter(|r| r.is_force_resolvable())
+                .map(|r| r.rva),
+        )
+        .collect();
+    for (i, profile) in config.profiles.iter().enumerate() {
+        for (j, rva) in profile.symbols.iter().enumerate() {
+            if !valid_rvas.contains(rva) {
+                report.error(
+                    format!("profiles[{}].symbols[{}]", i, j),
+                    format_args!("{:#X}", rva),
+                    "is not the RVA of an analyzed function",
+                );
+            }
+        }
+    }
+
+    if let Err(e) = config.check_limits(limits) {
+        report.push(Severity::Error, "profiles", None, e.to_string());
+    }
+
+    report.into_result()
+}
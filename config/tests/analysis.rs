@@ -0,0 +1,165 @@
+#![cfg(feature = "json")]
+
+use codedefender_config::{
+    AnalysisReject, AnalysisResult, CDConfig, CDModuleSettings, CDProfile, Limits, ObfuscationPass,
+    validate_cdconfig,
+};
+use serde_json::json;
+
+fn reject(ty: &str, reason: &str) -> AnalysisReject {
+    AnalysisReject {
+        rva: 0x2000,
+        symbol: "rejected".to_owned(),
+        ty: ty.to_owned(),
+        reason: reason.to_owned(),
+    }
+}
+
+fn analysis() -> AnalysisResult {
+    serde_json::from_value(json!({
+        "environment": "UserMode",
+        "functions": [{ "rva": 0x1000, "symbol": "main", "ref_count": 1 }],
+        "rejects": [
+            { "rva": 0x2000, "symbol": "patcher", "ty": "ReadWriteToCode", "reason": "writes its own code" },
+            { "rva": 0x3000, "symbol": "tiny", "ty": "TooSmall", "reason": "function is too small" }
+        ],
+        "macros": []
+    }))
+    .unwrap()
+}
+
+fn config(symbols: Vec<u64>) -> CDConfig {
+    CDConfig {
+        module_settings: CDModuleSettings::default(),
+        profiles: vec![CDProfile {
+            name: "app".to_owned(),
+            passes: vec![ObfuscationPass::AntiEmulator],
+            compiler_settings: Default::default(),
+            symbols,
+        }],
+    }
+}
+
+#[test]
+fn only_read_write_to_code_rejects_are_force_resolvable() {
+    assert!(reject("ReadWriteToCode", "").is_force_resolvable());
+    assert!(!reject("TooSmall", "").is_force_resolvable());
+    assert!(!reject("readwritetocode", "").is_force_resolvable());
+}
+
+#[test]
+fn force_resolvable_rejects_are_told_to_target_the_function() {
+    let advice = reject("ReadWriteToCode", "anything").remediation().unwrap();
+    assert!(advice.contains("target it explicitly"), "{advice}");
+
+    let advice = reject("Other", "Function is too small")
+        .remediation()
+        .unwrap();
+    assert!(advice.contains("too small"), "{advice}");
+    assert_eq!(reject("Other", "unknown").remediation(), None);
+}
+
+#[test]
+fn submitted_symbols_may_be_force_resolvable_rejects() {
+    assert!(
+        validate_cdconfig(
+            &config(vec![0x1000, 0x2000]),
+            &analysis(),
+            &Limits::default()
+        )
+        .is_ok()
+    );
+}
+
+#[test]
+fn submitted_symbols_may_not_be_other_rejects() {
+    let report = validate_cdconfig(
+        &config(vec![0x1000, 0x3000]),
+        &analysis(),
+        &Limits::default(),
+    )
+    .unwrap_err();
+
+    let paths: Vec<&str> = report.errors().map(|e| e.path.as_str()).collect();
+    assert_eq!(paths, ["profiles[0].symbols[1]"]);
+}
+
+#[test]
+fn submitted_config_must_fit_the_given_limits() {
+    let config = config(vec![0x1000, 0x2000]);
+    let limits = Limits {
+        max_symbols_per_profile: 1,
+        ..Limits::default()
+    };
+
+    let report = validate_cdconfig(&config, &analysis(), &limits).unwrap_err();
+
+    let errors: Vec<String> = report.errors().map(|e| e.to_string()).collect();
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert!(
+        errors[0].contains("profile `app` targets 2 symbols"),
+        "{errors:?}"
+    );
+
+    let limits = Limits {
+        max_profiles: 0,
+        ..Limits::default()
+    };
+    let report = validate_cdconfig(&config, &analysis(), &limits).unwrap_err();
+    assert_eq!(report.errors().count(), 1);
+}
+
+// Functions in neither RVA nor reference-count order, with `b` and `d` tied.
+fn unsorted_analysis() -> AnalysisResult {
+    serde_json::from_value(json!({
+        "environment": "UserMode",
+        "functions": [
+            { "rva": 0x3000, "symbol": "a", "ref_count": 1 },
+            { "rva": 0x1000, "symbol": "b", "ref_count": 5 },
+            { "rva": 0x4000, "symbol": "c", "ref_count": 9 },
+            { "rva": 0x2000, "symbol": "d", "ref_count": 5 }
+        ],
+        "rejects": [],
+        "macros": []
+    }))
+    .unwrap()
+}
+
+#[test]
+fn functions_sort_by_descending_ref_count_keeping_ties_in_order() {
+    let analysis = unsorted_analysis();
+
+    let symbols: Vec<&str> = analysis
+        .functions_by_ref_count_desc()
+        .into_iter()
+        .map(|f| f.symbol.as_str())
+        .collect();
+
+    assert_eq!(symbols, ["c", "b", "d", "a"]);
+}
+
+#[test]
+fn functions_sort_by_rva_without_touching_the_analysis() {
+    let analysis = unsorted_analysis();
+
+    let rvas: Vec<u64> = analysis
+        .functions_by_rva()
+        .into_iter()
+        .map(|f| f.rva)
+        .collect();
+
+    assert_eq!(rvas, [0x1000, 0x2000, 0x3000, 0x4000]);
+    assert_eq!(analysis, unsorted_analysis());
+}
+
+#[test]
+fn analysis_is_empty_only_without_functions_and_rejects() {
+    let mut analysis = analysis();
+    assert!(!analysis.is_empty());
+
+    analysis.functions.clear();
+    assert!(!analysis.is_empty());
+
+    analysis.rejects.clear();
+    assert!(analysis.is_empty());
+}
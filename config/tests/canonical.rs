@@ -0,0 +1,110 @@
+#![cfg(feature = "json")]
+
+use codedefender_config::{CDConfig, CDModuleSettings, CDProfile, ObfuscationPass};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+fn config() -> CDConfig {
+    CDConfig {
+        module_settings: CDModuleSettings {
+            import_protection: true,
+            ..CDModuleSettings::default()
+        },
+        profiles: vec![CDProfile {
+            name: "app".to_owned(),
+            passes: vec![ObfuscationPass::AntiEmulator],
+            compiler_settings: Default::default(),
+            symbols: vec![0x1000, 0x2000],
+        }],
+    }
+}
+
+// The JSON of `config()` rebuilt from `HashMap`s, filling each with its keys in sorted or
+// reverse-sorted order, then parsed back.
+fn rebuilt(reverse: bool) -> CDConfig {
+    fn rebuild(value: Value, reverse: bool) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                if reverse {
+                    entries.reverse();
+                }
+                let mut rebuilt = HashMap::new();
+                for (key, value) in entries {
+                    rebuilt.insert(key, rebuild(value, reverse));
+                }
+                serde_json::to_value(rebuilt).unwrap()
+            }
+            Value::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|value| rebuild(value, reverse))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
+    let value = rebuild(serde_json::to_value(config()).unwrap(), reverse);
+    serde_json::from_str(&serde_json::to_string(&value).unwrap()).unwrap()
+}
+
+#[test]
+fn hashmap_insertion_order_does_not_change_the_hash() {
+    let forward = rebuilt(false);
+    let backward = rebuilt(true);
+
+    assert_eq!(
+        forward.canonical_bytes().unwrap(),
+        backward.canonical_bytes().unwrap()
+    );
+    assert_eq!(
+        forward.config_hash().unwrap(),
+        config().config_hash().unwrap()
+    );
+    assert_eq!(
+        backward.config_hash().unwrap(),
+        config().config_hash().unwrap()
+    );
+}
+
+#[test]
+fn field_order_of_the_input_does_not_change_the_hash() {
+    let text = serde_json::to_string(&config()).unwrap();
+    let mut reordered = serde_json::from_str::<Value>(&text).unwrap();
+    let profiles = reordered["profiles"].take();
+    let reordered =
+        json!({ "profiles": profiles, "module_settings": reordered["module_settings"] });
+
+    let parsed: CDConfig = serde_json::from_value(reordered).unwrap();
+
+    assert_eq!(
+        parsed.config_hash().unwrap(),
+        config().config_hash().unwrap()
+    );
+}
+
+#[test]
+fn canonical_bytes_are_compact_json_with_sorted_keys() {
+    let bytes = config().canonical_bytes().unwrap();
+    let text = std::str::from_utf8(&bytes).unwrap();
+
+    assert!(
+        text.starts_with(r#"{"module_settings":{"anti_tamper":{"#),
+        "{text}"
+    );
+    assert!(!text.contains(' '), "{text}");
+    assert!(text.ends_with(r#""symbols":[4096,8192]}]}"#), "{text}");
+}
+
+#[test]
+fn different_configs_hash_differently() {
+    let mut other = config();
+    other.profiles[0].symbols.push(0x3000);
+
+    assert_ne!(
+        other.config_hash().unwrap(),
+        config().config_hash().unwrap()
+    );
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "yaml")]
+
+use codedefender_config::{CDCompilerSettings, CompilerPreset, ConversionError, YamlConfig};
+
+// Compiler settings of the first profile of the shipped example config.
+fn example_compiler_settings() -> CDCompilerSettings {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../utils/example/config.yaml");
+    let example: serde_yaml::Value =
+        serde_yaml::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    serde_yaml::from_value(example["profiles"][0]["compiler_settings"].clone()).unwrap()
+}
+
+#[test]
+fn default_matches_the_example_config() {
+    assert_eq!(CDCompilerSettings::default(), example_compiler_settings());
+    assert_eq!(
+        CDCompilerSettings::default(),
+        CDCompilerSettings::from_preset(CompilerPreset::Balanced)
+    );
+}
+
+#[test]
+fn presets_only_differ_in_iterations_and_codegen_noise() {
+    let balanced = CDCompilerSettings::from_preset(CompilerPreset::Balanced);
+
+    for preset in [CompilerPreset::Fast, CompilerPreset::Max] {
+        let mut settings = CDCompilerSettings::from_preset(preset);
+        settings.optimization_settings.iterations = balanced.optimization_settings.iterations;
+        settings.assembler_settings.shuffle_basic_blocks =
+            balanced.assembler_settings.shuffle_basic_blocks;
+        settings.assembler_settings.nop_insertion = balanced.assembler_settings.nop_insertion;
+        settings.assembler_settings.nop_insertion_chance =
+            balanced.assembler_settings.nop_insertion_chance;
+
+        assert_eq!(settings, balanced, "{preset:?}");
+    }
+}
+
+const PROFILES: &str = r#"
+version: "1.1.0"
+module_settings: {}
+default_compiler_settings:
+  assembler_settings:
+    shuffle_basic_blocks: false
+    instruction_prefix: ""
+    random_prefix_chance: 0.0
+  optimization_settings:
+    constant_propagation: true
+    instruction_combine: true
+    dead_code_elim: true
+    prune_useless_block_params: true
+    iterations: 7
+  lifter_settings:
+    lift_calls: true
+    calling_convention: WindowsAbi
+    max_stack_copy_size: 1024
+    split_on_calls_fallback: true
+profiles:
+  - name: inherits
+    passes: []
+    symbols: [!Rva 0x1000]
+  - name: overrides
+    passes: []
+    symbols: [!Rva 0x2000]
+    compiler_settings:
+      assembler_settings:
+        shuffle_basic_blocks: true
+        instruction_prefix: ""
+        random_prefix_chance: 0.0
+      optimization_settings:
+        constant_propagation: false
+        instruction_combine: false
+        dead_code_elim: false
+        prune_useless_block_params: false
+        iterations: 2
+      lifter_settings:
+        lift_calls: false
+        calling_convention: WindowsAbi
+        max_stack_copy_size: 512
+        split_on_calls_fallback: false
+"#;
+
+#[test]
+fn profile_without_settings_inherits_the_default() {
+    let config = YamlConfig::from_yaml_str(PROFILES).unwrap();
+    let default = config.default_compiler_settings.clone().unwrap();
+
+    let built = config.try_into_cdconfig().unwrap();
+
+    assert_eq!(built.profiles[0].name, "inherits");
+    assert_eq!(built.profiles[0].compiler_settings, default);
+}
+
+#[test]
+fn profile_settings_override_the_default() {
+    let config = YamlConfig::from_yaml_str(PROFILES).unwrap();
+    let own = config.profiles[1].compiler_settings.clone().unwrap();
+
+    let built = config.try_into_cdconfig().unwrap();
+
+    assert_eq!(built.profiles[1].name, "overrides");
+    assert_eq!(built.profiles[1].compiler_settings, own);
+    assert_eq!(own.optimization_settings.iterations, 2);
+}
+
+#[test]
+fn profile_without_settings_or_default_is_an_error() {
+    let mut config = YamlConfig::from_yaml_str(PROFILES).unwrap();
+    config.default_compiler_settings = None;
+
+    let error = config.try_into_cdconfig().unwrap_err();
+
+    assert!(
+        matches!(&error, ConversionError::MissingCompilerSettings(profile) if profile == "inherits"),
+        "{error:?}"
+    );
+}
@@ -0,0 +1,149 @@
+#![cfg(feature = "yaml")]
+
+use codedefender_config::{
+    CDCompilerSettings, CDConfig, CompilerPreset, ConversionError, ObfuscationPass, YamlConfig,
+};
+
+const CONFIG: &str = r#"
+version: "1.1.0"
+module_settings:
+  import_protection: true
+profiles:
+  - name: app
+    compiler_preset: fast
+    passes:
+      - type: AntiEmulator
+      - type: IDADecompilerCrasher
+        enabled: false
+    symbols: [!Rva 0x3000, !Rva 0x1000, !Rva 0x3000]
+"#;
+
+fn config() -> YamlConfig {
+    YamlConfig::from_yaml_str(CONFIG).unwrap()
+}
+
+fn convert(profiles: &str) -> Result<CDConfig, ConversionError> {
+    let mut config = config();
+    config.profiles = serde_yaml::from_str(profiles).unwrap();
+    config.try_into_cdconfig()
+}
+
+#[test]
+fn rva_only_config_converts() {
+    let built = config().try_into_cdconfig().unwrap();
+
+    assert!(built.module_settings.import_protection);
+    assert_eq!(built.profiles.len(), 1);
+    let profile = &built.profiles[0];
+    assert_eq!(profile.name, "app");
+    assert_eq!(profile.symbols, [0x1000, 0x3000]);
+    assert_eq!(profile.passes, [ObfuscationPass::AntiEmulator]);
+    assert_eq!(
+        profile.compiler_settings,
+        CDCompilerSettings::from_preset(CompilerPreset::Fast)
+    );
+}
+
+#[test]
+fn named_symbols_are_unresolved() {
+    let error = convert(
+        r#"
+- name: app
+  compiler_preset: fast
+  passes:
+    - type: AntiEmulator
+      targets: [!Name helper]
+  symbols: [!Rva 0x1000, !Name main, All]
+"#,
+    )
+    .unwrap_err();
+
+    match error {
+        ConversionError::UnresolvedSymbols(names) => {
+            assert_eq!(names.len(), 3, "{names:?}");
+            assert!(names.iter().any(|n| n.contains("main")), "{names:?}");
+            assert!(names.iter().any(|n| n.contains("helper")), "{names:?}");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn profile_without_compiler_settings_is_an_error() {
+    let error = convert(
+        r#"
+- name: bare
+  passes: []
+  symbols: [!Rva 0x1000]
+"#,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        error,
+        ConversionError::MissingCompilerSettings("bare".to_owned())
+    );
+}
+
+#[test]
+fn profile_with_settings_and_preset_is_an_error() {
+    let mut config = config();
+    config.profiles[0].compiler_settings = Some(CDCompilerSettings::default());
+
+    let error = config.try_into_cdconfig().unwrap_err();
+
+    assert_eq!(
+        error,
+        ConversionError::ConflictingCompilerSettings("app".to_owned())
+    );
+}
+
+#[test]
+fn targets_outside_the_profile_are_an_error() {
+    let error = convert(
+        r#"
+- name: app
+  compiler_preset: fast
+  passes:
+    - type: AntiEmulator
+      targets: [!Rva 0x2000]
+  symbols: [!Rva 0x1000]
+"#,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        error,
+        ConversionError::TargetsOutsideProfile {
+            profile: "app".to_owned(),
+            pass: "AntiEmulator".to_owned(),
+        }
+    );
+}
+
+#[test]
+fn shared_symbols_stay_in_the_profile_applied_first() {
+    let built = convert(
+        r#"
+- name: broad
+  compiler_preset: fast
+  passes:
+    - type: AntiEmulator
+      targets: [!Rva 0x1000, !Rva 0x2000]
+  symbols: [!Rva 0x1000, !Rva 0x2000]
+- name: hot
+  priority: -1
+  compiler_preset: max
+  passes: []
+  symbols: [!Rva 0x2000]
+"#,
+    )
+    .unwrap();
+
+    let symbols: Vec<(&str, &[u64])> = built
+        .profiles
+        .iter()
+        .map(|p| (p.name.as_str(), p.symbols.as_slice()))
+        .collect();
+    assert_eq!(symbols, [("hot", &[0x2000][..]), ("broad", &[0x1000][..])]);
+}
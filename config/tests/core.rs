@@ -0,0 +1,59 @@
+//! The core config types, usable without the `yaml` and `json` features.
+//!
+//! Nothing here is feature-gated, so running the tests with `--no-default-features`
+//! checks that the structs, validation and conversion don't depend on a format crate.
+
+use codedefender_config::{
+    AnalysisFunction, AnalysisResult, CDConfig, CDModuleSettings, CDProfile, Limits,
+    ObfuscationPass, PeEnvironment, StarterOptions, YamlConfig, YamlSymbol, validate_config,
+};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+fn is_serde<T: Serialize + DeserializeOwned>() {}
+
+#[test]
+fn core_types_implement_serde_without_format_features() {
+    is_serde::<CDConfig>();
+    is_serde::<YamlConfig>();
+    is_serde::<ObfuscationPass>();
+    is_serde::<AnalysisResult>();
+}
+
+#[test]
+fn configs_can_be_built_and_checked_without_format_features() {
+    let analysis = AnalysisResult {
+        environment: PeEnvironment::UserMode,
+        functions: vec![AnalysisFunction {
+            rva: 0x1000,
+            symbol: "main".to_owned(),
+            ref_count: 1,
+            section: None,
+            size: None,
+        }],
+        rejects: Vec::new(),
+        macros: Vec::new(),
+    };
+
+    let mut config = YamlConfig::from_analysis(&analysis, StarterOptions::default());
+    assert!(!validate_config(&config).has_errors());
+
+    config.profiles[0].symbols = vec![YamlSymbol::Rva(0x1000)];
+    let built = config.try_into_cdconfig().unwrap();
+    assert_eq!(built.profiles[0].symbols, [0x1000]);
+    assert_eq!(built.check_limits(&Limits::default()), Ok(()));
+
+    let manual = CDConfig {
+        module_settings: CDModuleSettings::default(),
+        profiles: vec![CDProfile {
+            name: "app".to_owned(),
+            passes: vec![ObfuscationPass::AntiEmulator],
+            compiler_settings: Default::default(),
+            symbols: vec![0x1000],
+        }],
+    };
+    assert_eq!(
+        manual.profiles[0].summary(),
+        "1 pass: AntiEmulator over 1 symbol"
+    );
+}
@@ -0,0 +1,128 @@
+use codedefender_config::{
+    CDCompilerSettings, CDConfig, CDModuleSettings, CDProfile, CompilerPreset, ConfigDiff,
+    CpuFeatureLevel, ObfuscationPass, ObscureControlFlow, diff_configs,
+};
+
+fn profile(name: &str) -> CDProfile {
+    CDProfile {
+        name: name.to_owned(),
+        passes: vec![
+            ObfuscationPass::AntiEmulator,
+            ObfuscationPass::ObscureControlFlow(ObscureControlFlow { probability: 50 }),
+        ],
+        compiler_settings: Default::default(),
+        symbols: vec![0x1000, 0x2000, 0x3000],
+    }
+}
+
+fn config() -> CDConfig {
+    CDConfig {
+        module_settings: CDModuleSettings::default(),
+        profiles: vec![profile("hot"), profile("cold")],
+    }
+}
+
+#[test]
+fn identical_configs_have_no_diff() {
+    assert_eq!(diff_configs(&config(), &config()), vec![]);
+}
+
+#[test]
+fn changed_boolean_setting_is_reported() {
+    let mut b = config();
+    b.module_settings.import_protection = true;
+
+    assert_eq!(
+        diff_configs(&config(), &b),
+        vec![ConfigDiff::ModuleSettingChanged(
+            "import_protection".to_owned()
+        )]
+    );
+}
+
+#[test]
+fn settings_not_sent_to_the_api_are_still_compared() {
+    let mut b = config();
+    b.module_settings.custom_section_name.allow_standard_name = true;
+    b.module_settings.min_cpu_features = Some(CpuFeatureLevel::Sse42);
+
+    assert_eq!(
+        diff_configs(&config(), &b),
+        vec![
+            ConfigDiff::ModuleSettingChanged("custom_section_name.allow_standard_name".to_owned()),
+            ConfigDiff::ModuleSettingChanged("min_cpu_features".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn added_and_removed_profiles_are_reported() {
+    let mut b = config();
+    b.profiles.remove(1);
+    b.profiles.push(profile("new"));
+
+    assert_eq!(
+        diff_configs(&config(), &b),
+        vec![
+            ConfigDiff::ProfileRemoved("cold".to_owned()),
+            ConfigDiff::ProfileAdded("new".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn pass_changes_are_reported_and_displayed() {
+    let mut b = config();
+    b.profiles[0].passes[1] =
+        ObfuscationPass::ObscureControlFlow(ObscureControlFlow { probability: 75 });
+
+    let diffs = diff_configs(&config(), &b);
+
+    assert_eq!(diffs.len(), 2);
+    assert_eq!(
+        diffs[0].to_string(),
+        format!("profile `hot`: pass {} removed", profile("hot").passes[1])
+    );
+    assert_eq!(
+        diffs[1].to_string(),
+        format!("profile `hot`: pass {} added", b.profiles[0].passes[1])
+    );
+    assert!(!diffs[1].to_string().contains("ObscureControlFlow {"));
+}
+
+#[test]
+fn reordered_passes_are_reported_as_an_order_change() {
+    let mut b = config();
+    b.profiles[1].passes.reverse();
+
+    assert_eq!(
+        diff_configs(&config(), &b),
+        vec![ConfigDiff::PassOrderChanged {
+            profile: "cold".to_owned()
+        }]
+    );
+}
+
+#[test]
+fn symbol_and_compiler_setting_changes_are_reported() {
+    let mut b = config();
+    b.profiles[0].symbols = vec![0x3000, 0x1000, 0x4000];
+    b.profiles[0].compiler_settings = CDCompilerSettings::from_preset(CompilerPreset::Max);
+
+    assert_eq!(
+        diff_configs(&config(), &b),
+        vec![
+            ConfigDiff::CompilerSettingsChanged {
+                profile: "hot".to_owned()
+            },
+            ConfigDiff::SymbolsRemoved {
+                profile: "hot".to_owned(),
+                rvas: vec![0x2000],
+            },
+            ConfigDiff::SymbolsAdded {
+                profile: "hot".to_owned(),
+                rvas: vec![0x4000],
+            },
+        ]
+    );
+}
@@ -0,0 +1,111 @@
+use codedefender_config::{
+    BitWidths, LeaEncodeImm, LoopEncodeSemantics, MixedBooleanArithmetic, MutationEngine,
+    MutationEngineExtension, ObfuscationPass, ObscureConstants, ObscureControlFlow,
+    ObscureReferences, OpaqueBlockDuplication, SplitBlockPass, SsaOrigins, SuppressConstants,
+    TetherExtraction, VirtualizationProtection,
+};
+
+#[test]
+fn each_pass_has_a_concise_label() {
+    let cases = [
+        (
+            ObfuscationPass::LoopEncodeSemantics(LoopEncodeSemantics {
+                probability: 40,
+                iterations: 3,
+                ..LoopEncodeSemantics::default()
+            }),
+            "Loop Encode Semantics (prob 40%, 3 iters)",
+        ),
+        (
+            ObfuscationPass::MixedBooleanArithmetic(MixedBooleanArithmetic {
+                probability: 50,
+                iterations: 2,
+                ..MixedBooleanArithmetic::default()
+            }),
+            "Mixed Boolean Arithmetic (prob 50%, 2 iters)",
+        ),
+        (
+            ObfuscationPass::MutationEngine(MutationEngine {
+                extension: MutationEngineExtension::SSE42,
+                probability: 25,
+                ..MutationEngine::default()
+            }),
+            "Mutation Engine (SSE42, prob 25%, 1 iters)",
+        ),
+        (
+            ObfuscationPass::TetherExtraction(TetherExtraction {
+                min_extract_len: 16,
+                endpoint: "tether.example.com".to_owned(),
+                port: 8443,
+                server_public_key: "key".to_owned(),
+            }),
+            "Tether Extraction (tether.example.com:8443, min len 16)",
+        ),
+        (
+            ObfuscationPass::SplitBlockPass(SplitBlockPass { threshold: 8 }),
+            "Split Block (threshold 8)",
+        ),
+        (
+            ObfuscationPass::OpaqueBlockDuplication(OpaqueBlockDuplication {
+                iterations: 2,
+                probability: 30,
+            }),
+            "Opaque Block Duplication (prob 30%, 2 iters)",
+        ),
+        (
+            ObfuscationPass::ObscureControlFlow(ObscureControlFlow { probability: 60 }),
+            "Obscure Control Flow (prob 60%)",
+        ),
+        (
+            ObfuscationPass::LeaEncodeImm(LeaEncodeImm {
+                probability: 70,
+                ethnicities: SsaOrigins::all(),
+            }),
+            "LEA Encode Immediates (prob 70%)",
+        ),
+        (
+            ObfuscationPass::ObscureConstants(ObscureConstants {
+                probability: 80,
+                iterations: 1,
+                bitwidths: BitWidths::all(),
+                ethnicities: SsaOrigins::all(),
+            }),
+            "Obscure Constants (prob 80%, 1 iters)",
+        ),
+        (
+            ObfuscationPass::SuppressConstants(SuppressConstants {
+                ethnicities: SsaOrigins::all(),
+            }),
+            "Suppress Constants",
+        ),
+        (
+            ObfuscationPass::VirtualizationProtection(VirtualizationProtection {
+                complexity: 3,
+                handler_duplication: true,
+                probability: 50,
+            }),
+            "Virtualization (complexity 3, prob 50%, duplicated handlers)",
+        ),
+        (
+            ObfuscationPass::VirtualizationProtection(VirtualizationProtection {
+                complexity: 1,
+                handler_duplication: false,
+                probability: 100,
+            }),
+            "Virtualization (complexity 1, prob 100%)",
+        ),
+        (
+            ObfuscationPass::ObscureReferences(ObscureReferences { probability: 90 }),
+            "Obscure References (prob 90%)",
+        ),
+        (
+            ObfuscationPass::IDADecompilerCrasher,
+            "IDA Decompiler Crasher",
+        ),
+        (ObfuscationPass::AntiEmulator, "Anti Emulator"),
+    ];
+
+    for (pass, label) in cases {
+        assert_eq!(pass.to_string(), label, "{pass:?}");
+    }
+}
@@ -0,0 +1,66 @@
+use codedefender_config::{
+    CDConfig, CDModuleSettings, CDProfile, ObfuscationPass, ObscureControlFlow,
+    OpaqueBlockDuplication,
+};
+
+fn profile(passes: Vec<ObfuscationPass>, symbols: usize) -> CDProfile {
+    CDProfile {
+        name: "app".to_owned(),
+        passes,
+        compiler_settings: Default::default(),
+        symbols: (0..symbols as u64).map(|i| 0x1000 + i * 0x10).collect(),
+    }
+}
+
+fn control_flow(probability: u32) -> ObfuscationPass {
+    ObfuscationPass::ObscureControlFlow(ObscureControlFlow { probability })
+}
+
+#[test]
+fn half_probability_over_100_symbols_applies_to_about_50() {
+    let estimate = profile(vec![control_flow(50)], 100).estimate_applied();
+
+    assert_eq!(estimate.len(), 1);
+    assert_eq!(estimate[0].0, "ObscureControlFlow");
+    assert!((estimate[0].1 - 50.0).abs() < 1e-9, "{estimate:?}");
+}
+
+#[test]
+fn passes_without_a_probability_apply_to_every_symbol() {
+    let estimate = profile(
+        vec![
+            ObfuscationPass::AntiEmulator,
+            control_flow(0),
+            control_flow(250),
+        ],
+        40,
+    )
+    .estimate_applied();
+
+    assert_eq!(
+        estimate,
+        [
+            ("AntiEmulator".to_owned(), 40.0),
+            ("ObscureControlFlow".to_owned(), 0.0),
+            ("ObscureControlFlow".to_owned(), 40.0),
+        ]
+    );
+}
+
+#[test]
+fn work_units_grow_with_symbols_and_iterations() {
+    let duplication = ObfuscationPass::OpaqueBlockDuplication(OpaqueBlockDuplication {
+        iterations: 3,
+        probability: 100,
+    });
+    let small = profile(vec![ObfuscationPass::AntiEmulator], 10);
+    let large = profile(vec![ObfuscationPass::AntiEmulator, duplication], 10);
+
+    assert_eq!(small.estimated_work_units(), 10);
+    assert_eq!(large.estimated_work_units(), 40);
+    let config = CDConfig {
+        module_settings: CDModuleSettings::default(),
+        profiles: vec![small, large],
+    };
+    assert_eq!(config.estimated_work_units(), 50);
+}
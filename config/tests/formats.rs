@@ -0,0 +1,81 @@
+#![cfg(all(feature = "yaml", feature = "json"))]
+
+use codedefender_config::{CDConfig, ConfigError, YamlConfig};
+
+const CONFIG: &str = r#"
+version: "1.1.0"
+module_settings:
+  import_protection: true
+  clear_unwind_info: false
+  fake_pdb_string:
+    enabled: true
+    value: "app.pdb"
+  custom_section_name:
+    enabled: true
+    value: ".cdx"
+  anti_tamper:
+    enabled: true
+    anti_debug: true
+    anti_vm: false
+    crash_on_detection: false
+  max_section_entropy: 7.0
+profiles:
+  - name: hot
+    compiler_preset: max
+    passes:
+      - type: ObscureControlFlow
+        probability: 50
+      - type: ObscureReferences
+        probability: 75
+      - type: AntiEmulator
+    symbols: [!Rva 0x2000, !Rva 0x1000]
+  - name: cold
+    compiler_preset: fast
+    passes:
+      - type: IDADecompilerCrasher
+    symbols: [!Rva 0xFFFFFFFFFFFFFFFF]
+"#;
+
+#[test]
+fn yaml_to_json_round_trip_keeps_the_config() {
+    let config = YamlConfig::from_yaml_str(CONFIG)
+        .unwrap()
+        .try_into_cdconfig()
+        .unwrap();
+
+    let json = config.to_json_string().unwrap();
+    let reloaded = CDConfig::from_json_slice(json.as_bytes()).unwrap();
+
+    assert_eq!(reloaded, config);
+    assert_eq!(reloaded.profiles[0].symbols, vec![0x1000, 0x2000]);
+    assert_eq!(reloaded.profiles[1].symbols, vec![u64::MAX]);
+}
+
+#[test]
+fn json_form_of_a_yaml_config_parses_the_same() {
+    let config = YamlConfig::from_yaml_str(CONFIG).unwrap();
+
+    let json = serde_json::to_string(&config).unwrap();
+
+    assert_eq!(YamlConfig::from_json_str(&json).unwrap(), config);
+    assert_eq!(
+        YamlConfig::from_json_str(&json)
+            .unwrap()
+            .try_into_cdconfig()
+            .unwrap(),
+        config.try_into_cdconfig().unwrap()
+    );
+}
+
+#[test]
+fn malformed_input_is_a_config_error() {
+    let yaml = YamlConfig::from_yaml_str("profiles: [").unwrap_err();
+    let json = CDConfig::from_json_slice(b"{\"profiles\": ").unwrap_err();
+    let yaml_as_json = YamlConfig::from_json_str("[]").unwrap_err();
+
+    assert!(matches!(yaml, ConfigError::Yaml(_)));
+    assert!(yaml.to_string().starts_with("invalid YAML config: "));
+    assert!(matches!(json, ConfigError::Json(_)));
+    assert!(json.to_string().starts_with("invalid JSON config: "));
+    assert!(matches!(yaml_as_json, ConfigError::Json(_)));
+}
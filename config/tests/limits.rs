@@ -0,0 +1,204 @@
+use codedefender_config::{
+    CDConfig, CDModuleSettings, CDProfile, LimitError, Limits, ObfuscationPass, split_profile,
+};
+use std::collections::HashSet;
+
+fn profile(name: &str, symbols: usize) -> CDProfile {
+    CDProfile {
+        name: name.to_owned(),
+        passes: vec![ObfuscationPass::AntiEmulator],
+        compiler_settings: Default::default(),
+        symbols: (0..symbols as u64).map(|i| 0x1000 + i * 0x10).collect(),
+    }
+}
+
+#[test]
+fn splitting_1000_symbols_keeps_every_symbol_once() {
+    let original = profile("big", 1000);
+
+    let parts = split_profile(original.clone(), 128, &mut HashSet::new());
+
+    assert_eq!(parts.len(), 8);
+    assert!(parts.iter().all(|part| part.symbols.len() <= 128));
+    assert_eq!(parts[7].symbols.len(), 1000 - 7 * 128);
+    let covered: Vec<u64> = parts.iter().flat_map(|p| p.symbols.clone()).collect();
+    assert_eq!(covered, original.symbols);
+    for part in &parts {
+        assert_eq!(part.passes, original.passes);
+        assert_eq!(part.compiler_settings, original.compiler_settings);
+    }
+    let names: HashSet<&str> = parts.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names.len(), parts.len());
+    assert_eq!(parts[0].name, "big");
+    assert_eq!(parts[1].name, "big_2");
+}
+
+#[test]
+fn profile_within_the_limit_is_unchanged() {
+    let original = profile("small", 10);
+
+    assert_eq!(
+        split_profile(original.clone(), 10, &mut HashSet::new()),
+        [original]
+    );
+}
+
+#[test]
+fn split_names_skip_taken_names() {
+    let mut taken: HashSet<String> = ["big_2".to_owned()].into();
+
+    let parts = split_profile(profile("big", 3), 1, &mut taken);
+
+    let names: Vec<&str> = parts.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["big", "big_3", "big_4"]);
+    assert!(taken.contains("big_4"));
+}
+
+#[test]
+fn zero_max_symbols_is_treated_as_one() {
+    assert_eq!(
+        split_profile(profile("p", 3), 0, &mut HashSet::new()).len(),
+        3
+    );
+}
+
+#[test]
+fn splitting_at_the_per_profile_limit_passes_check_limits() {
+    let limits = Limits {
+        max_symbols_per_profile: 100,
+        ..Limits::default()
+    };
+    let mut config = CDConfig {
+        module_settings: CDModuleSettings::default(),
+        profiles: vec![profile("big", 250), profile("small", 10)],
+    };
+
+    assert_eq!(
+        config.check_limits(&limits),
+        Err(LimitError::TooManyProfileSymbols {
+            profile: "big".to_owned(),
+            limit: 100,
+            actual: 250
+        })
+    );
+
+    let mut taken = HashSet::new();
+    config.profiles = config
+        .profiles
+        .into_iter()
+        .flat_map(|p| split_profile(p, limits.max_symbols_per_profile, &mut taken))
+        .collect();
+
+    assert_eq!(config.check_limits(&limits), Ok(()));
+    let names: Vec<&str> = config.profiles.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["big", "big_2", "big_3", "small"]);
+}
+
+// Limits of 2 profiles, 5 symbols in total, 3 symbols per profile and 2 passes per profile.
+fn small_limits() -> Limits {
+    Limits {
+        max_profiles: 2,
+        max_symbols: 5,
+        max_symbols_per_profile: 3,
+        max_passes_per_profile: 2,
+    }
+}
+
+fn config(profiles: Vec<CDProfile>) -> CDConfig {
+    CDConfig {
+        module_settings: CDModuleSettings::default(),
+        profiles,
+    }
+}
+
+#[test]
+fn config_at_every_limit_passes() {
+    let mut full = profile("b", 2);
+    full.passes.push(ObfuscationPass::IDADecompilerCrasher);
+
+    assert_eq!(
+        config(vec![profile("a", 3), full]).check_limits(&small_limits()),
+        Ok(())
+    );
+}
+
+#[test]
+fn too_many_profiles_is_reported() {
+    let error = config(vec![profile("a", 1), profile("b", 1), profile("c", 1)])
+        .check_limits(&small_limits())
+        .unwrap_err();
+
+    assert_eq!(
+        error,
+        LimitError::TooManyProfiles {
+            limit: 2,
+            actual: 3
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "config has 3 profiles, 1 over the limit of 2"
+    );
+}
+
+#[test]
+fn too_many_passes_is_reported() {
+    let mut busy = profile("busy", 1);
+    busy.passes = vec![ObfuscationPass::AntiEmulator; 4];
+
+    let error = config(vec![profile("a", 1), busy])
+        .check_limits(&small_limits())
+        .unwrap_err();
+
+    assert_eq!(
+        error,
+        LimitError::TooManyPasses {
+            profile: "busy".to_owned(),
+            limit: 2,
+            actual: 4
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "profile `busy` has 4 passes, 2 over the limit of 2"
+    );
+}
+
+#[test]
+fn too_many_symbols_in_a_profile_is_reported() {
+    let error = config(vec![profile("big", 4)])
+        .check_limits(&small_limits())
+        .unwrap_err();
+
+    assert_eq!(
+        error,
+        LimitError::TooManyProfileSymbols {
+            profile: "big".to_owned(),
+            limit: 3,
+            actual: 4
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "profile `big` targets 4 symbols, 1 over the limit of 3"
+    );
+}
+
+#[test]
+fn too_many_symbols_in_total_is_reported() {
+    let error = config(vec![profile("a", 3), profile("b", 3)])
+        .check_limits(&small_limits())
+        .unwrap_err();
+
+    assert_eq!(
+        error,
+        LimitError::TooManySymbols {
+            limit: 5,
+            actual: 6
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "config targets 6 symbols, 1 over the limit of 5"
+    );
+}
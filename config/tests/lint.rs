@@ -0,0 +1,183 @@
+#![cfg(feature = "yaml")]
+
+use codedefender_config::{LintKind, YamlConfig, lint_config};
+
+const MODULE_SETTINGS: &str = r#"
+version: "1.1.0"
+module_settings:
+  fake_pdb_string:
+    enabled: false
+    value: ""
+  custom_section_name:
+    enabled: false
+    value: ""
+"#;
+
+fn config(rest: &str) -> YamlConfig {
+    YamlConfig::from_yaml_str(&format!("{}{}", MODULE_SETTINGS, rest)).unwrap()
+}
+
+fn lints(config: &YamlConfig) -> Vec<(LintKind, String)> {
+    lint_config(config)
+        .into_iter()
+        .map(|lint| (lint.kind, lint.path))
+        .collect()
+}
+
+#[test]
+fn clean_config_has_no_lints() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    compiler_preset: fast
+    passes:
+      - type: ObscureControlFlow
+        probability: 50
+    symbols: [!Name main]
+"#,
+    );
+
+    assert_eq!(lints(&config), []);
+}
+
+#[test]
+fn pass_with_zero_probability_never_applies() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    compiler_preset: fast
+    passes:
+      - type: AntiEmulator
+      - type: ObscureControlFlow
+        probability: 0
+    symbols: [!Name main]
+"#,
+    );
+
+    assert_eq!(
+        lints(&config),
+        [(
+            LintKind::PassNeverApplies,
+            "profiles[0].passes[1].probability".to_owned()
+        )]
+    );
+}
+
+#[test]
+fn disabled_passes_are_not_linted() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    compiler_preset: fast
+    passes:
+      - type: AntiEmulator
+      - type: ObscureControlFlow
+        probability: 0
+        enabled: false
+    symbols: [!Name main]
+"#,
+    );
+
+    assert_eq!(lints(&config), []);
+}
+
+#[test]
+fn enabled_toggles_with_empty_values_are_linted() {
+    let mut config = config(
+        r#"
+default_compiler_settings:
+  assembler_settings:
+    shuffle_basic_blocks: false
+    instruction_prefix: ""
+    random_prefix_chance: 0.5
+    nop_insertion: true
+    nop_insertion_chance: 0.0
+  optimization_settings:
+    constant_propagation: true
+    instruction_combine: true
+    dead_code_elim: true
+    prune_useless_block_params: true
+    iterations: 1
+  lifter_settings:
+    lift_calls: true
+    calling_convention: WindowsAbi
+    max_stack_copy_size: 1024
+    split_on_calls_fallback: true
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+    symbols: [!Name main]
+"#,
+    );
+    config.module_settings.custom_section_name.enabled = true;
+    config.module_settings.fake_pdb_string.enabled = true;
+
+    assert_eq!(
+        lints(&config),
+        [
+            (
+                LintKind::EmptyValue,
+                "module_settings.custom_section_name.value".to_owned()
+            ),
+            (
+                LintKind::EmptyValue,
+                "module_settings.fake_pdb_string.value".to_owned()
+            ),
+            (
+                LintKind::EmptyValue,
+                "default_compiler_settings.assembler_settings.instruction_prefix".to_owned()
+            ),
+            (
+                LintKind::EmptyValue,
+                "default_compiler_settings.assembler_settings.nop_insertion_chance".to_owned()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn profile_without_enabled_passes_is_linted() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    compiler_preset: fast
+    passes:
+      - type: AntiEmulator
+        enabled: false
+    symbols: [!Name main]
+"#,
+    );
+
+    assert_eq!(
+        lints(&config),
+        [(LintKind::NoPasses, "profiles[0].passes".to_owned())]
+    );
+}
+
+#[test]
+fn zero_iterations_are_only_reported_as_errors() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    compiler_preset: fast
+    passes:
+      - type: OpaqueBlockDuplication
+        iterations: 0
+        probability: 50
+    symbols: [!Name main]
+"#,
+    );
+
+    let report = config.validation_report();
+
+    let path = "profiles[0].passes[0].iterations";
+    assert_eq!(report.errors().filter(|e| e.path == path).count(), 1);
+    assert_eq!(report.warnings().filter(|w| w.path == path).count(), 0);
+    assert_eq!(lints(&config), []);
+}
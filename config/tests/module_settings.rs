@@ -0,0 +1,216 @@
+#![cfg(all(feature = "yaml", feature = "json"))]
+
+use codedefender_config::{
+    AnalysisResult, CDConfig, CDModuleSettings, CpuFeatureLevel, MutationEngine,
+    MutationEngineExtension, ObfuscationPass, PassEntry, PeEnvironment, StarterOptions, YamlConfig,
+    validate_config,
+};
+
+const CONFIG: &str = r#"
+version: "1.1.0"
+module_settings:
+  custom_section_name:
+    enabled: true
+    value: ".data"
+    allow_standard_name: true
+profiles: []
+"#;
+
+fn section_name_errors(value: &str, allow_standard_name: bool) -> Vec<String> {
+    let mut config = YamlConfig::from_yaml_str(CONFIG).unwrap();
+    config.module_settings.custom_section_name.value = value.to_owned();
+    config
+        .module_settings
+        .custom_section_name
+        .allow_standard_name = allow_standard_name;
+    validate_config(&config)
+        .errors()
+        .filter(|e| e.path == "module_settings.custom_section_name.value")
+        .map(|e| e.to_string())
+        .collect()
+}
+
+#[test]
+fn allow_standard_name_is_not_sent_to_the_api() {
+    let config = YamlConfig::from_yaml_str(CONFIG).unwrap();
+    let cdconfig = CDConfig {
+        module_settings: config.module_settings.clone(),
+        profiles: Vec::new(),
+    };
+
+    let json: serde_json::Value = serde_json::to_value(&cdconfig).unwrap();
+
+    let section = &json["module_settings"]["custom_section_name"];
+    assert_eq!(
+        section,
+        &serde_json::json!({ "enabled": true, "value": ".data" })
+    );
+}
+
+#[test]
+fn allow_standard_name_survives_a_yaml_round_trip() {
+    let config = YamlConfig::from_yaml_str(CONFIG).unwrap();
+
+    let reloaded = YamlConfig::from_yaml_str(&config.to_yaml_string().unwrap()).unwrap();
+
+    assert!(
+        reloaded
+            .module_settings
+            .custom_section_name
+            .allow_standard_name
+    );
+}
+
+#[test]
+fn wire_json_without_local_settings_deserializes() {
+    let json = serde_json::json!({
+        "module_settings": serde_json::to_value(CDConfig {
+            module_settings: CDModuleSettings::default(),
+            profiles: Vec::new(),
+        })
+        .unwrap()["module_settings"],
+        "profiles": []
+    });
+
+    let config: CDConfig = serde_json::from_value(json).unwrap();
+
+    assert!(
+        !config
+            .module_settings
+            .custom_section_name
+            .allow_standard_name
+    );
+}
+
+#[test]
+fn standard_section_names_need_allow_standard_name() {
+    assert_eq!(section_name_errors(".data", false).len(), 1);
+    assert_eq!(section_name_errors(".TEXT", false).len(), 1);
+    assert!(section_name_errors(".data", true).is_empty());
+    assert!(section_name_errors(".cdx", false).is_empty());
+}
+
+#[test]
+fn section_names_longer_than_8_bytes_are_rejected() {
+    assert!(section_name_errors(".12345678", true)[0].contains("at most 8 bytes"));
+    assert!(section_name_errors(".1234567", true).is_empty());
+}
+
+// A config whose only profile runs the mutation engine with `extension`, checked against
+// `min_cpu_features`.
+fn mutation_engine_errors(
+    extension: MutationEngineExtension,
+    min_cpu_features: Option<CpuFeatureLevel>,
+) -> Vec<String> {
+    let mut config = YamlConfig::from_yaml_str(&format!(
+        "{}{}",
+        CONFIG.replace("profiles: []", ""),
+        r#"
+profiles:
+  - name: app
+    compiler_preset: fast
+    passes:
+      - type: AntiEmulator
+    symbols: [!Name main]
+"#
+    ))
+    .unwrap();
+    config.module_settings.custom_section_name.enabled = false;
+    config.module_settings.min_cpu_features = min_cpu_features;
+    config.profiles[0].passes = vec![PassEntry {
+        pass: ObfuscationPass::MutationEngine(MutationEngine {
+            extension,
+            ..MutationEngine::default()
+        }),
+        enabled: true,
+        targets: None,
+        min_function_size: None,
+    }];
+    validate_config(&config)
+        .errors()
+        .map(|e| e.to_string())
+        .collect()
+}
+
+#[test]
+fn extensions_newer_than_min_cpu_features_are_rejected() {
+    let errors =
+        mutation_engine_errors(MutationEngineExtension::SSE42, Some(CpuFeatureLevel::Sse3));
+
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert!(
+        errors[0].contains("profiles[0].passes[0].extension"),
+        "{errors:?}"
+    );
+    assert!(errors[0].contains("needs Sse42"), "{errors:?}");
+}
+
+#[test]
+fn extensions_within_min_cpu_features_are_accepted() {
+    let min = Some(CpuFeatureLevel::Sse3);
+    assert!(mutation_engine_errors(MutationEngineExtension::Generic, min).is_empty());
+    assert!(mutation_engine_errors(MutationEngineExtension::SSE3, min).is_empty());
+    assert!(mutation_engine_errors(MutationEngineExtension::SSE42, None).is_empty());
+}
+
+#[test]
+fn min_cpu_features_is_not_sent_to_the_api() {
+    let mut config = YamlConfig::from_yaml_str(CONFIG).unwrap();
+    config.module_settings.min_cpu_features = Some(CpuFeatureLevel::Sse3);
+    let cdconfig = CDConfig {
+        module_settings: config.module_settings.clone(),
+        profiles: Vec::new(),
+    };
+
+    let json = serde_json::to_value(&cdconfig).unwrap();
+
+    assert!(json["module_settings"].get("min_cpu_features").is_none());
+    let reloaded = YamlConfig::from_yaml_str(&config.to_yaml_string().unwrap()).unwrap();
+    assert_eq!(
+        reloaded.module_settings.min_cpu_features,
+        Some(CpuFeatureLevel::Sse3)
+    );
+}
+
+#[test]
+fn kernel_mode_recommendations_leave_seh_intact() {
+    let kernel = CDModuleSettings::recommended_for(PeEnvironment::KernelMode);
+
+    assert!(!kernel.clear_unwind_info);
+    assert!(!kernel.import_protection);
+    assert!(!kernel.obscure_entry_point);
+    assert!(!kernel.tls_callback_protection);
+    assert_eq!(
+        CDModuleSettings::recommended_for(PeEnvironment::UEFI),
+        kernel
+    );
+}
+
+#[test]
+fn user_mode_recommendations_enable_entry_and_import_protection() {
+    let user = CDModuleSettings::recommended_for(PeEnvironment::UserMode);
+
+    assert!(user.import_protection);
+    assert!(user.obscure_entry_point);
+    assert!(user.tls_callback_protection);
+    assert!(!user.clear_unwind_info);
+}
+
+#[test]
+fn starter_configs_use_the_recommendations_for_the_analyzed_environment() {
+    let analysis: AnalysisResult = serde_json::from_value(serde_json::json!({
+        "environment": "KernelMode",
+        "functions": [{ "rva": 0x1000, "symbol": "DriverEntry", "ref_count": 1 }],
+        "rejects": [],
+        "macros": []
+    }))
+    .unwrap();
+
+    let config = YamlConfig::from_analysis(&analysis, StarterOptions::default());
+
+    assert_eq!(
+        config.module_settings,
+        CDModuleSettings::recommended_for(PeEnvironment::KernelMode)
+    );
+    assert!(!config.module_settings.clear_unwind_info);
+}
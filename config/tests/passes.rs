@@ -0,0 +1,216 @@
+#![cfg(all(feature = "yaml", feature = "json"))]
+
+use codedefender_config::{
+    BitWidths, ObfuscationPass, ObscureConstants, ObscureReferences, SsaOrigins, Tunable,
+    YamlConfig, validate_config,
+};
+use serde_json::json;
+use std::path::PathBuf;
+
+fn ssa_origins() -> SsaOrigins {
+    SsaOrigins {
+        normal: true,
+        memop: false,
+        fp_based_memop: false,
+        sp_based_memop: false,
+    }
+}
+
+#[test]
+fn obscure_constants_sends_only_the_documented_settings() {
+    let pass = ObfuscationPass::ObscureConstants(ObscureConstants {
+        probability: 50,
+        iterations: 2,
+        bitwidths: BitWidths::all(),
+        ethnicities: ssa_origins(),
+    });
+
+    let value = serde_json::to_value(&pass).unwrap();
+
+    let keys: Vec<&str> = value
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            "bitwidths",
+            "ethnicities",
+            "iterations",
+            "probability",
+            "type"
+        ]
+    );
+}
+
+#[test]
+fn obscure_constants_ignores_unknown_key_settings() {
+    let pass: ObfuscationPass = serde_json::from_value(json!({
+        "type": "ObscureConstants",
+        "iterations": 1,
+        "bitwidths": { "bit8": true, "bit16": true, "bit32": true, "bit64": true },
+        "ethnicities": { "normal": true, "memop": false, "fp_based_memop": false, "sp_based_memop": false },
+        "key_width": 64,
+        "per_use_rekey": true
+    }))
+    .unwrap();
+
+    assert_eq!(
+        pass,
+        ObfuscationPass::ObscureConstants(ObscureConstants {
+            probability: 100,
+            iterations: 1,
+            bitwidths: BitWidths::all(),
+            ethnicities: ssa_origins(),
+        })
+    );
+}
+
+// Wire shape of `ObscureReferences` before it had settings, and the current one.
+const OBSCURE_REFERENCES_UNIT: &str = r#"{"type":"ObscureReferences"}"#;
+const OBSCURE_REFERENCES: &str = r#"{"type":"ObscureReferences","probability":40}"#;
+
+#[test]
+fn obscure_references_unit_form_applies_at_100_percent() {
+    let from_json: ObfuscationPass = serde_json::from_str(OBSCURE_REFERENCES_UNIT).unwrap();
+    let from_yaml: ObfuscationPass = serde_yaml::from_str("type: ObscureReferences").unwrap();
+
+    let expected = ObfuscationPass::ObscureReferences(ObscureReferences { probability: 100 });
+    assert_eq!(from_json, expected);
+    assert_eq!(from_yaml, expected);
+}
+
+#[test]
+fn obscure_references_with_a_probability_matches_the_wire_shape() {
+    let pass = ObfuscationPass::ObscureReferences(ObscureReferences { probability: 40 });
+
+    assert_eq!(serde_json::to_string(&pass).unwrap(), OBSCURE_REFERENCES);
+    assert_eq!(
+        serde_json::from_str::<ObfuscationPass>(OBSCURE_REFERENCES).unwrap(),
+        pass
+    );
+    let from_yaml: ObfuscationPass =
+        serde_yaml::from_str("type: ObscureReferences\nprobability: 40").unwrap();
+    assert_eq!(from_yaml, pass);
+}
+
+#[test]
+fn obscure_constants_without_a_probability_applies_at_100_percent() {
+    let pass: ObfuscationPass = serde_yaml::from_str(
+        r#"
+type: ObscureConstants
+iterations: 1
+bitwidths: { bit8: true, bit16: true, bit32: true, bit64: true }
+ethnicities: { normal: true, memop: false, fp_based_memop: false, sp_based_memop: false }
+"#,
+    )
+    .unwrap();
+
+    let ObfuscationPass::ObscureConstants(pass) = pass else {
+        panic!("unexpected pass: {pass:?}");
+    };
+    assert_eq!(pass.probability, 100);
+}
+
+#[test]
+fn obscure_references_probability_is_range_checked() {
+    let config = YamlConfig::from_yaml_str(
+        r#"
+version: "1.1.0"
+module_settings: {}
+profiles:
+  - name: app
+    compiler_preset: fast
+    passes:
+      - type: ObscureReferences
+        probability: 150
+    symbols: [!Name main]
+"#,
+    )
+    .unwrap();
+
+    let report = validate_config(&config);
+
+    let paths: Vec<&str> = report.errors().map(|e| e.path.as_str()).collect();
+    assert_eq!(paths, ["profiles[0].passes[0].probability"]);
+}
+
+#[test]
+fn tunables_match_the_pass_settings() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/passes.json");
+    let passes: Vec<ObfuscationPass> =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(passes.len(), 14);
+
+    for pass in &passes {
+        let value = serde_json::to_value(pass).unwrap();
+        let setting = |key: &str| value.get(key).map(|v| v.as_u64().unwrap() as u32);
+
+        assert_eq!(
+            pass.probability(),
+            setting("probability"),
+            "{}",
+            pass.name()
+        );
+        assert_eq!(pass.iterations(), setting("iterations"), "{}", pass.name());
+    }
+}
+
+#[test]
+fn passes_without_tunables_report_none() {
+    for pass in [
+        ObfuscationPass::IDADecompilerCrasher,
+        ObfuscationPass::AntiEmulator,
+    ] {
+        assert_eq!(pass.probability(), None);
+        assert_eq!(pass.iterations(), None);
+    }
+    let references = ObfuscationPass::ObscureReferences(ObscureReferences { probability: 30 });
+    assert_eq!(references.probability(), Some(30));
+    assert_eq!(references.iterations(), None);
+}
+
+#[test]
+fn all_passes_pairs_enabled_passes_with_their_profiles() {
+    let config = YamlConfig::from_yaml_str(
+        r#"
+version: "1.1.0"
+module_settings: {}
+profiles:
+  - name: first
+    compiler_preset: fast
+    passes:
+      - type: AntiEmulator
+      - type: IDADecompilerCrasher
+        enabled: false
+      - type: ObscureReferences
+    symbols: [!Name main]
+  - name: empty
+    compiler_preset: fast
+    passes: []
+    symbols: [!Name helper]
+  - name: second
+    compiler_preset: fast
+    passes:
+      - type: IDADecompilerCrasher
+    symbols: [!Name stub]
+"#,
+    )
+    .unwrap();
+
+    let passes: Vec<(&str, &str)> = config
+        .all_passes()
+        .map(|(profile, pass)| (profile.name.as_str(), pass.name()))
+        .collect();
+
+    assert_eq!(
+        passes,
+        [
+            ("first", "AntiEmulator"),
+            ("first", "ObscureReferences"),
+            ("second", "IDADecompilerCrasher"),
+        ]
+    );
+}
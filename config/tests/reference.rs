@@ -0,0 +1,147 @@
+#![cfg(all(feature = "yaml", feature = "json"))]
+
+use codedefender_config::{
+    AntiTamperSettings, CDCompilerSettings, CDModuleSettings, CompilerPreset, CpuFeatureLevel,
+    CustomSectionName, FakePdbString, ObfuscationPass, PassEntry, YamlConfig, YamlProfile,
+    YamlSymbol, config_reference,
+};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+// A config setting every option, so each one shows up when serialized. The struct
+// literals are written out in full, so a new field fails to compile here until it is
+// added, and then fails the drift test until it is documented.
+fn fully_populated() -> YamlConfig {
+    YamlConfig {
+        version: "1.1.0".to_owned(),
+        module_settings: CDModuleSettings {
+            ida_crasher: true,
+            import_protection: true,
+            obscure_entry_point: true,
+            clear_unwind_info: true,
+            tls_callback_protection: true,
+            fake_pdb_string: FakePdbString {
+                enabled: true,
+                value: "app.pdb".to_owned(),
+            },
+            custom_section_name: CustomSectionName {
+                enabled: true,
+                value: ".cdx".to_owned(),
+                allow_standard_name: true,
+            },
+            anti_tamper: AntiTamperSettings {
+                enabled: true,
+                anti_debug: true,
+                anti_vm: true,
+                crash_on_detection: true,
+            },
+            max_section_entropy: Some(6.0),
+            pad_sections: true,
+            min_cpu_features: Some(CpuFeatureLevel::Sse3),
+        },
+        default_compiler_settings: Some(CDCompilerSettings::default()),
+        profiles: vec![YamlProfile {
+            name: "app".to_owned(),
+            passes: vec![PassEntry {
+                pass: ObfuscationPass::AntiEmulator,
+                enabled: false,
+                targets: Some(vec![YamlSymbol::Name("main".to_owned())]),
+                min_function_size: Some(16),
+            }],
+            compiler_settings: Some(CDCompilerSettings::default()),
+            compiler_preset: Some(CompilerPreset::Fast),
+            symbols: vec![YamlSymbol::All],
+            exclude: vec![YamlSymbol::Rva(0x1000)],
+            color: Some("#ff0000".to_owned()),
+            priority: Some(1),
+            extra: BTreeMap::new(),
+        }],
+        extra: BTreeMap::new(),
+    }
+}
+
+// Every key path of `value` in the reference's notation. List elements are written `[]`,
+// compiler settings are listed once under `compiler_settings`, and symbol lists and pass
+// settings (documented on `ObfuscationPass`) are not descended into.
+fn key_paths(value: &Value, path: &str, paths: &mut BTreeSet<String>) {
+    if !path.is_empty() {
+        paths.insert(path.to_owned());
+    }
+    if path.ends_with("symbols") || path.ends_with("exclude") || path.ends_with("targets") {
+        return;
+    }
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                if path == "profiles[].passes[]"
+                    && !["enabled", "targets", "min_function_size"].contains(&key.as_str())
+                {
+                    continue;
+                }
+                let child = match path {
+                    "" => key.clone(),
+                    "default_compiler_settings" | "profiles[].compiler_settings" => {
+                        format!("compiler_settings.{}", key)
+                    }
+                    _ => format!("{}.{}", path, key),
+                };
+                key_paths(value, &child, paths);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                key_paths(item, &format!("{}[]", path), paths);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn documented() -> BTreeSet<&'static str> {
+    config_reference().iter().map(|field| field.path).collect()
+}
+
+#[test]
+fn reference_includes_key_module_settings() {
+    let documented = documented();
+
+    assert!(documented.contains("module_settings.import_protection"));
+    assert!(documented.contains("module_settings.clear_unwind_info"));
+}
+
+#[test]
+fn every_config_option_is_documented() {
+    let mut paths = BTreeSet::new();
+    key_paths(
+        &serde_json::to_value(fully_populated()).unwrap(),
+        "",
+        &mut paths,
+    );
+    let documented = documented();
+
+    // Sections holding other options are not documented themselves.
+    let missing: Vec<&String> = paths
+        .iter()
+        .filter(|path| !documented.contains(path.as_str()))
+        .filter(|path| {
+            !paths
+                .iter()
+                .any(|other| other.starts_with(&format!("{path}.")))
+        })
+        .collect();
+    assert!(missing.is_empty(), "undocumented options: {missing:?}");
+
+    let stale: Vec<&&str> = documented
+        .iter()
+        .filter(|path| !paths.contains(**path))
+        .collect();
+    assert!(
+        stale.is_empty(),
+        "documented options that don't exist: {stale:?}"
+    );
+}
+
+#[test]
+fn reference_has_no_duplicates() {
+    assert_eq!(documented().len(), config_reference().len());
+}
@@ -0,0 +1,110 @@
+#![cfg(all(feature = "yaml", feature = "json"))]
+
+use codedefender_config::rva::MAX_SAFE_INTEGER;
+use codedefender_config::{AnalysisResult, CDCompilerSettings, CDProfile, YamlSymbol};
+use serde_json::json;
+
+const EDGES: [u64; 4] = [0, MAX_SAFE_INTEGER, MAX_SAFE_INTEGER + 1, u64::MAX];
+
+fn profile(symbols: Vec<u64>) -> CDProfile {
+    CDProfile {
+        name: "app".to_owned(),
+        passes: Vec::new(),
+        compiler_settings: CDCompilerSettings::default(),
+        symbols,
+    }
+}
+
+#[test]
+fn yaml_symbol_rvas_round_trip_through_json() {
+    for rva in EDGES {
+        let symbol = YamlSymbol::Rva(rva);
+
+        let json = serde_json::to_string(&symbol).unwrap();
+
+        assert_eq!(serde_json::from_str::<YamlSymbol>(&json).unwrap(), symbol);
+    }
+}
+
+#[test]
+fn yaml_symbol_rvas_round_trip_through_yaml() {
+    for rva in EDGES {
+        let symbol = YamlSymbol::Rva(rva);
+
+        let yaml = serde_yaml::to_string(&symbol).unwrap();
+
+        assert_eq!(serde_yaml::from_str::<YamlSymbol>(&yaml).unwrap(), symbol);
+    }
+}
+
+#[test]
+fn yaml_symbol_rvas_above_2_pow_53_are_written_as_hex() {
+    assert_eq!(
+        serde_json::to_value(YamlSymbol::Rva(MAX_SAFE_INTEGER)).unwrap(),
+        json!({ "Rva": MAX_SAFE_INTEGER })
+    );
+    assert_eq!(
+        serde_json::to_value(YamlSymbol::Rva(u64::MAX)).unwrap(),
+        json!({ "Rva": "0xFFFFFFFFFFFFFFFF" })
+    );
+    let yaml = serde_yaml::to_string(&YamlSymbol::Rva(1 << 53)).unwrap();
+    assert_eq!(yaml.trim(), "!Rva '0x20000000000000'");
+}
+
+#[test]
+fn profile_symbols_are_sent_as_integers() {
+    let json = serde_json::to_value(profile(EDGES.to_vec())).unwrap();
+
+    assert_eq!(json["symbols"], json!(EDGES));
+}
+
+#[test]
+fn profile_symbols_round_trip_through_json_and_yaml() {
+    let profile = profile(EDGES.to_vec());
+
+    let json = serde_json::to_string(&profile).unwrap();
+    let yaml = serde_yaml::to_string(&profile).unwrap();
+
+    assert_eq!(serde_json::from_str::<CDProfile>(&json).unwrap(), profile);
+    assert_eq!(serde_yaml::from_str::<CDProfile>(&yaml).unwrap(), profile);
+}
+
+#[test]
+fn profile_symbols_accept_hex_and_decimal_strings() {
+    let mut value = serde_json::to_value(profile(Vec::new())).unwrap();
+    value["symbols"] = json!([0, "0xFFFFFFFFFFFFFFFF", "9007199254740992"]);
+
+    let profile: CDProfile = serde_json::from_value(value).unwrap();
+
+    assert_eq!(profile.symbols, [0, u64::MAX, 1 << 53]);
+}
+
+#[test]
+fn analysis_rvas_accept_strings_and_are_written_as_integers() {
+    let analysis: AnalysisResult = serde_json::from_value(json!({
+        "environment": "UserMode",
+        "functions": [
+            { "rva": "0xFFFFFFFFFFFFFFFF", "symbol": "high", "ref_count": 1 },
+            { "rva": 9007199254740991u64, "symbol": "safe", "ref_count": 1 }
+        ],
+        "rejects": [
+            { "rva": "9007199254740992", "symbol": "odd", "ty": "Other", "reason": "" }
+        ],
+        "macros": [{ "name": "m", "rvas": [0, "0xFFFFFFFFFFFFFFFF"] }]
+    }))
+    .unwrap();
+
+    assert_eq!(analysis.functions[0].rva, u64::MAX);
+    assert_eq!(analysis.functions[1].rva, MAX_SAFE_INTEGER);
+    assert_eq!(analysis.rejects[0].rva, 1 << 53);
+    assert_eq!(analysis.macros[0].rvas, [0, u64::MAX]);
+
+    let json = serde_json::to_value(&analysis).unwrap();
+    assert_eq!(json["functions"][0]["rva"], json!(u64::MAX));
+    assert_eq!(json["macros"][0]["rvas"], json!([0, u64::MAX]));
+    let yaml = serde_yaml::to_string(&analysis).unwrap();
+    assert_eq!(
+        serde_yaml::from_str::<AnalysisResult>(&yaml).unwrap(),
+        analysis
+    );
+}
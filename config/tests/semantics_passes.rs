@@ -0,0 +1,68 @@
+use codedefender_config::{
+    BitWidths, LoopEncodeSemantics, MAX_ITERATIONS, MixedBooleanArithmetic, MutationEngine,
+    MutationEngineExtension, Semantics, SsaOrigins, ValidationReport,
+};
+
+// Paths of the problems in a failed `validate()`.
+fn failing_paths(result: Result<(), ValidationReport>) -> Vec<String> {
+    result
+        .unwrap_err()
+        .issues
+        .into_iter()
+        .map(|i| i.path)
+        .collect()
+}
+
+#[test]
+fn defaults_run_once_over_everything() {
+    let loop_encode = LoopEncodeSemantics::default();
+    assert_eq!((loop_encode.iterations, loop_encode.probability), (1, 100));
+    assert_eq!(loop_encode.semantics, Semantics::all());
+    assert_eq!(loop_encode.bitwidths, BitWidths::all());
+    assert_eq!(loop_encode.ethnicities, SsaOrigins::all());
+
+    let mba = MixedBooleanArithmetic::default();
+    assert_eq!((mba.iterations, mba.probability), (1, 100));
+    assert_eq!(mba.semantics, Semantics::all());
+    assert_eq!(mba.bitwidths, BitWidths::all());
+    assert_eq!(mba.ethnicities, SsaOrigins::all());
+
+    let mutation = MutationEngine::default();
+    assert_eq!((mutation.iterations, mutation.probability), (1, 100));
+    assert_eq!(mutation.extension, MutationEngineExtension::Generic);
+    assert_eq!(mutation.semantics, Semantics::all());
+    assert_eq!(mutation.bitwidths, BitWidths::all());
+    assert_eq!(mutation.ethnicities, SsaOrigins::all());
+}
+
+#[test]
+fn defaults_validate() {
+    assert!(LoopEncodeSemantics::default().validate().is_ok());
+    assert!(MixedBooleanArithmetic::default().validate().is_ok());
+    assert!(MutationEngine::default().validate().is_ok());
+}
+
+#[test]
+fn out_of_range_tunables_fail_validation() {
+    let loop_encode = LoopEncodeSemantics {
+        iterations: 0,
+        ..LoopEncodeSemantics::default()
+    };
+    assert_eq!(failing_paths(loop_encode.validate()), ["iterations"]);
+
+    let mba = MixedBooleanArithmetic {
+        probability: 101,
+        ..MixedBooleanArithmetic::default()
+    };
+    assert_eq!(failing_paths(mba.validate()), ["probability"]);
+
+    let mutation = MutationEngine {
+        iterations: MAX_ITERATIONS + 1,
+        probability: 200,
+        ..MutationEngine::default()
+    };
+    assert_eq!(
+        failing_paths(mutation.validate()),
+        ["iterations", "probability"]
+    );
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "yaml")]
+
+use codedefender_config::YamlConfig;
+
+const CONFIG: &str = r#"
+version: "1.1.0"
+module_settings: {}
+profiles:
+  - name: hot
+    compiler_preset: max
+    passes:
+      - type: ObscureControlFlow
+        probability: 50
+      - type: AntiEmulator
+      - type: ObscureControlFlow
+        probability: 25
+      - type: IDADecompilerCrasher
+        enabled: false
+    symbols: [!Rva 0x1000, !Rva 0x2000, !Rva 0x1000]
+  - name: cold
+    compiler_preset: fast
+    passes: []
+    symbols: [!Rva 0x3000]
+"#;
+
+#[test]
+fn yaml_profile_summary_counts_enabled_passes_and_written_symbols() {
+    let config = YamlConfig::from_yaml_str(CONFIG).unwrap();
+
+    assert_eq!(
+        config.profiles[0].summary(),
+        "3 passes: ObscureControlFlow x2, AntiEmulator over 3 symbols"
+    );
+    assert_eq!(config.profiles[1].summary(), "0 passes: none over 1 symbol");
+}
+
+#[test]
+fn cd_profile_summary_counts_resolved_symbols() {
+    let config = YamlConfig::from_yaml_str(CONFIG)
+        .unwrap()
+        .try_into_cdconfig()
+        .unwrap();
+
+    assert_eq!(
+        config.profiles[0].summary(),
+        "3 passes: ObscureControlFlow x2, AntiEmulator over 2 symbols"
+    );
+    assert_eq!(config.profiles[1].summary(), "0 passes: none over 1 symbol");
+}
@@ -0,0 +1,139 @@
+#![cfg(feature = "yaml")]
+
+use codedefender_config::{
+    ObfuscationPass, ObscureReferences, UpgradeError, YAML_CONFIG_VERSION, YamlConfig,
+    upgrade_config, validate_config,
+};
+
+/// A config as written for 1.0.4: no newer module settings, a settings-less
+/// `ObscureReferences` and zero optimization iterations.
+const V1_0_4: &str = r#"
+version: "1.0.4"
+module_settings:
+  ida_crasher: false
+  import_protection: true
+  obscure_entry_point: false
+  clear_unwind_info: false
+  fake_pdb_string:
+    enabled: false
+    value: ""
+  custom_section_name:
+    enabled: false
+    value: ""
+profiles:
+  - name: hot
+    passes:
+      - type: ObscureReferences
+      - type: ObscureControlFlow
+        probability: 10
+    compiler_settings:
+      assembler_settings:
+        shuffle_basic_blocks: true
+        instruction_prefix: ""
+        random_prefix_chance: 0
+      optimization_settings:
+        constant_propagation: true
+        instruction_combine: true
+        dead_code_elim: true
+        prune_useless_block_params: true
+        iterations: 0
+      lifter_settings:
+        lift_calls: true
+        calling_convention: WindowsAbi
+        max_stack_copy_size: 1024
+        split_on_calls_fallback: true
+    symbols:
+      - !Name main
+  - name: cold
+    passes: []
+    compiler_settings:
+      assembler_settings:
+        shuffle_basic_blocks: false
+        instruction_prefix: ""
+        random_prefix_chance: 0
+      optimization_settings:
+        constant_propagation: true
+        instruction_combine: true
+        dead_code_elim: true
+        prune_useless_block_params: true
+        iterations: 3
+      lifter_settings:
+        lift_calls: true
+        calling_convention: WindowsAbi
+        max_stack_copy_size: 1024
+        split_on_calls_fallback: true
+    symbols: []
+"#;
+
+fn iterations(config: &YamlConfig, profile: usize) -> u32 {
+    config.profiles[profile]
+        .compiler_settings
+        .as_ref()
+        .unwrap()
+        .optimization_settings
+        .iterations
+}
+
+#[test]
+fn old_config_upgrades_to_a_valid_current_config() {
+    let config = YamlConfig::from_yaml_str(&upgrade_config(V1_0_4).unwrap()).unwrap();
+
+    assert_eq!(config.version, YAML_CONFIG_VERSION);
+    assert_eq!(iterations(&config, 0), 1);
+    assert_eq!(iterations(&config, 1), 3);
+    assert_eq!(
+        config.profiles[0].passes[0].pass,
+        ObfuscationPass::ObscureReferences(ObscureReferences { probability: 100 })
+    );
+    assert!(!config.module_settings.tls_callback_protection);
+    let report = validate_config(&config);
+    assert!(!report.has_errors(), "{report:?}");
+}
+
+#[test]
+fn old_config_does_not_validate_before_upgrading() {
+    let config = YamlConfig::from_yaml_str(V1_0_4).unwrap();
+
+    let report = validate_config(&config);
+
+    assert!(
+        report
+            .errors()
+            .any(|e| e.path == "profiles[0].compiler_settings.optimization_settings.iterations"),
+        "{report:?}"
+    );
+}
+
+#[test]
+fn upgrade_round_trips() {
+    let upgraded = upgrade_config(V1_0_4).unwrap();
+
+    assert_eq!(upgrade_config(&upgraded).unwrap(), upgraded);
+    assert_eq!(
+        YamlConfig::from_yaml_str(&upgraded)
+            .unwrap()
+            .to_yaml_string()
+            .unwrap(),
+        upgraded
+    );
+}
+
+#[test]
+fn missing_version_is_an_error() {
+    let old = V1_0_4.replace("version: \"1.0.4\"\n", "");
+
+    assert!(matches!(
+        upgrade_config(&old),
+        Err(UpgradeError::MissingVersion)
+    ));
+}
+
+#[test]
+fn unknown_version_is_an_error() {
+    let old = V1_0_4.replace("1.0.4", "1.0.1");
+
+    match upgrade_config(&old) {
+        Err(UpgradeError::UnsupportedVersion(version)) => assert_eq!(version, "1.0.1"),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
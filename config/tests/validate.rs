@@ -0,0 +1,634 @@
+#![cfg(feature = "yaml")]
+
+use codedefender_config::{
+    BitWidths, CDCompilerSettings, CompilerPreset, MAX_ITERATIONS, MixedBooleanArithmetic,
+    MutationEngine, ObfuscationPass, ObscureConstants, ObscureControlFlow, OpaqueBlockDuplication,
+    PeEnvironment, Semantics, Severity, SsaOrigins, TetherExtraction, ValidationError,
+    ValidationReport, VirtualizationProtection, YamlConfig, validate_config, validate_environment,
+};
+
+const CONFIG: &str = r#"
+version: "1.1.0"
+module_settings: {}
+profiles:
+  - name: app
+    compiler_preset: fast
+    passes: []
+    symbols: [!Name main]
+"#;
+
+fn config() -> YamlConfig {
+    YamlConfig::from_yaml_str(CONFIG).unwrap()
+}
+
+// The config with the profile's preset written out as explicit compiler settings.
+fn config_with_settings(edit: impl FnOnce(&mut CDCompilerSettings)) -> YamlConfig {
+    let mut config = config();
+    let mut settings = CDCompilerSettings::from_preset(CompilerPreset::Fast);
+    edit(&mut settings);
+    config.profiles[0].compiler_preset = None;
+    config.profiles[0].compiler_settings = Some(settings);
+    config
+}
+
+// The config with a single VirtualizationProtection pass.
+fn config_with_virtualization(complexity: u32, probability: u32) -> YamlConfig {
+    let mut config = config();
+    config.profiles[0].passes = vec![
+        ObfuscationPass::VirtualizationProtection(VirtualizationProtection {
+            complexity,
+            handler_duplication: true,
+            probability,
+        })
+        .into(),
+    ];
+    config
+}
+
+// Issues reported for `path`.
+fn issues_at<'a>(report: &'a ValidationReport, path: &str) -> Vec<&'a ValidationError> {
+    report.issues.iter().filter(|i| i.path == path).collect()
+}
+
+#[test]
+fn tls_callback_protection_is_accepted_for_user_mode() {
+    let mut config = config();
+    config.module_settings.tls_callback_protection = true;
+
+    let report = validate_environment(&config, PeEnvironment::UserMode);
+
+    assert!(report.is_empty(), "{report:?}");
+}
+
+#[test]
+fn tls_callback_protection_warns_for_other_environments() {
+    let mut config = config();
+    config.module_settings.tls_callback_protection = true;
+
+    for environment in [PeEnvironment::KernelMode, PeEnvironment::UEFI] {
+        let report = validate_environment(&config, environment);
+
+        let issues = issues_at(&report, "module_settings.tls_callback_protection");
+        assert_eq!(issues.len(), 1, "{report:?}");
+        assert_eq!(issues[0].severity, Severity::Warning);
+        assert!(!report.has_errors());
+    }
+}
+
+#[test]
+fn nop_insertion_chance_within_range_is_accepted() {
+    for chance in [0.0, 0.5, 1.0] {
+        let config = config_with_settings(|settings| {
+            settings.assembler_settings.nop_insertion = true;
+            settings.assembler_settings.nop_insertion_chance = chance;
+        });
+
+        let report = validate_config(&config);
+
+        assert!(!report.has_errors(), "{chance}: {report:?}");
+    }
+}
+
+#[test]
+fn nop_insertion_chance_out_of_range_is_rejected() {
+    for chance in [-0.1, 1.5] {
+        let config = config_with_settings(|settings| {
+            settings.assembler_settings.nop_insertion = true;
+            settings.assembler_settings.nop_insertion_chance = chance;
+        });
+
+        let report = validate_config(&config);
+
+        let issues = issues_at(
+            &report,
+            "profiles[0].compiler_settings.assembler_settings.nop_insertion_chance",
+        );
+        assert_eq!(issues.len(), 1, "{chance}: {report:?}");
+        assert_eq!(issues[0].severity, Severity::Error);
+    }
+}
+
+#[test]
+fn virtualization_within_range_is_accepted() {
+    for complexity in [1, 5] {
+        let config = config_with_virtualization(complexity, 100);
+
+        let report = validate_config(&config);
+
+        assert!(!report.has_errors(), "{complexity}: {report:?}");
+        assert!(validate_environment(&config, PeEnvironment::UserMode).is_empty());
+    }
+}
+
+#[test]
+fn virtualization_out_of_range_is_rejected() {
+    for (complexity, probability, path) in [
+        (0, 50, "profiles[0].passes[0].complexity"),
+        (6, 50, "profiles[0].passes[0].complexity"),
+        (3, 101, "profiles[0].passes[0].probability"),
+    ] {
+        let report = validate_config(&config_with_virtualization(complexity, probability));
+
+        let issues = issues_at(&report, path);
+        assert_eq!(issues.len(), 1, "{path}: {report:?}");
+        assert_eq!(issues[0].severity, Severity::Error);
+    }
+}
+
+#[test]
+fn virtualization_warns_outside_user_mode() {
+    let config = config_with_virtualization(3, 50);
+
+    for environment in [PeEnvironment::KernelMode, PeEnvironment::UEFI] {
+        let report = validate_environment(&config, environment);
+
+        let issues = issues_at(&report, "profiles[0].passes[0]");
+        assert_eq!(issues.len(), 1, "{report:?}");
+        assert_eq!(issues[0].severity, Severity::Warning);
+    }
+}
+
+#[test]
+fn anti_tamper_with_a_detection_is_accepted() {
+    for (anti_debug, anti_vm) in [(true, false), (false, true), (true, true)] {
+        let mut config = config();
+        let anti_tamper = &mut config.module_settings.anti_tamper;
+        anti_tamper.enabled = true;
+        anti_tamper.anti_debug = anti_debug;
+        anti_tamper.anti_vm = anti_vm;
+
+        let report = validate_config(&config);
+
+        assert!(report.is_empty(), "{report:?}");
+    }
+}
+
+#[test]
+fn anti_tamper_without_a_detection_warns() {
+    let mut config = config();
+    config.module_settings.anti_tamper.enabled = true;
+    config.module_settings.anti_tamper.crash_on_detection = true;
+
+    let report = validate_config(&config);
+
+    let issues = issues_at(&report, "module_settings.anti_tamper");
+    assert_eq!(issues.len(), 1, "{report:?}");
+    assert_eq!(issues[0].severity, Severity::Warning);
+}
+
+#[test]
+fn config_without_anti_tamper_loads_it_disabled() {
+    let config = config();
+
+    assert!(!config.module_settings.anti_tamper.enabled);
+    assert!(validate_config(&config).is_empty());
+}
+
+#[test]
+fn iterations_within_range_are_accepted() {
+    for iterations in [1, MAX_ITERATIONS] {
+        let mut config = config_with_settings(|settings| {
+            settings.optimization_settings.iterations = iterations;
+        });
+        config.profiles[0].passes = vec![
+            ObfuscationPass::OpaqueBlockDuplication(OpaqueBlockDuplication {
+                iterations,
+                probability: 50,
+            })
+            .into(),
+        ];
+
+        let report = validate_config(&config);
+
+        assert!(!report.has_errors(), "{iterations}: {report:?}");
+    }
+}
+
+#[test]
+fn zero_or_absurd_iterations_are_rejected() {
+    for iterations in [0, MAX_ITERATIONS + 1, 1_000_000] {
+        let mut config = config_with_settings(|settings| {
+            settings.optimization_settings.iterations = iterations;
+        });
+        config.profiles[0].passes = vec![
+            ObfuscationPass::OpaqueBlockDuplication(OpaqueBlockDuplication {
+                iterations,
+                probability: 50,
+            })
+            .into(),
+        ];
+
+        let report = validate_config(&config);
+
+        for path in [
+            "profiles[0].compiler_settings.optimization_settings.iterations",
+            "profiles[0].passes[0].iterations",
+        ] {
+            let issues = issues_at(&report, path);
+            assert_eq!(issues.len(), 1, "{iterations} at {path}: {report:?}");
+            assert_eq!(issues[0].severity, Severity::Error);
+            assert_eq!(issues[0].value, Some(iterations.to_string()));
+        }
+    }
+}
+
+#[test]
+fn example_config_validates() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../utils/example/config.yaml");
+    let config = YamlConfig::from_yaml_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+    let report = validate_config(&config);
+
+    assert!(!report.has_errors(), "{report:?}");
+    let settings = config.profiles[0].compiler_settings.as_ref().unwrap();
+    assert_eq!(settings.optimization_settings.iterations, 1);
+}
+
+#[test]
+fn max_section_entropy_within_range_is_accepted() {
+    for entropy in [0.0, 6.5, 8.0] {
+        let mut config = config();
+        config.module_settings.max_section_entropy = Some(entropy);
+
+        let report = validate_config(&config);
+
+        assert!(report.is_empty(), "{entropy}: {report:?}");
+    }
+}
+
+#[test]
+fn max_section_entropy_out_of_range_is_rejected() {
+    for entropy in [-0.5, 8.5, f64::NAN] {
+        let mut config = config();
+        config.module_settings.max_section_entropy = Some(entropy);
+
+        let report = validate_config(&config);
+
+        let issues = issues_at(&report, "module_settings.max_section_entropy");
+        assert_eq!(issues.len(), 1, "{entropy}: {report:?}");
+        assert_eq!(issues[0].severity, Severity::Error);
+    }
+}
+
+// The config with `fake_pdb_string` enabled and set to `value`.
+fn config_with_fake_pdb(value: &str) -> YamlConfig {
+    let mut config = config();
+    config.module_settings.fake_pdb_string.enabled = true;
+    config.module_settings.fake_pdb_string.value = value.to_owned();
+    config
+}
+
+const FAKE_PDB_PATH: &str = "module_settings.fake_pdb_string.value";
+
+#[test]
+fn plausible_fake_pdb_paths_are_accepted() {
+    for value in [r"C:\build\release\app.pdb", "APP.PDB"] {
+        let report = validate_config(&config_with_fake_pdb(value));
+
+        assert!(report.is_empty(), "{value}: {report:?}");
+    }
+}
+
+#[test]
+fn implausible_fake_pdb_paths_warn() {
+    for value in ["app.exe", "app\t.pdb"] {
+        let report = validate_config(&config_with_fake_pdb(value));
+
+        let issues = issues_at(&report, FAKE_PDB_PATH);
+        assert_eq!(issues.len(), 1, "{value:?}: {report:?}");
+        assert_eq!(issues[0].severity, Severity::Warning);
+    }
+}
+
+#[test]
+fn unusable_fake_pdb_paths_are_rejected() {
+    let too_long = format!("{}.pdb", "a".repeat(260));
+    for value in ["app\0.pdb", too_long.as_str()] {
+        let report = validate_config(&config_with_fake_pdb(value));
+
+        let issues = issues_at(&report, FAKE_PDB_PATH);
+        assert_eq!(issues.len(), 1, "{value:?}: {report:?}");
+        assert_eq!(issues[0].severity, Severity::Error);
+    }
+}
+
+#[test]
+fn disabled_fake_pdb_string_is_not_checked() {
+    let mut config = config_with_fake_pdb("app\0");
+    config.module_settings.fake_pdb_string.enabled = false;
+
+    assert!(validate_config(&config).is_empty());
+}
+
+#[test]
+fn nested_problems_are_reported_at_their_field_paths() {
+    let config = YamlConfig::from_yaml_str(
+        r#"
+version: "1.1.0"
+module_settings:
+  custom_section_name:
+    enabled: true
+    value: ".toolongname"
+profiles:
+  - name: app
+    compiler_preset: fast
+    passes: []
+    symbols: [!Name main]
+  - name: app
+    compiler_preset: fast
+    passes:
+      - type: AntiEmulator
+      - type: ObscureControlFlow
+        probability: 150
+    symbols: [!Name helper]
+"#,
+    )
+    .unwrap();
+
+    let report = validate_config(&config);
+
+    let paths: Vec<&str> = report.errors().map(|e| e.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "profiles[1].name",
+            "module_settings.custom_section_name.value",
+            "profiles[1].passes[1].probability",
+        ],
+        "{report:?}"
+    );
+    let probability = issues_at(&report, "profiles[1].passes[1].probability")[0];
+    assert_eq!(probability.value.as_deref(), Some("150"));
+    assert_eq!(
+        probability.to_string(),
+        "error: profiles[1].passes[1].probability = 150: must be between 0 and 100"
+    );
+}
+
+#[test]
+fn report_displays_one_issue_per_line() {
+    let mut config = config();
+    config.module_settings.max_section_entropy = Some(9.0);
+    config.module_settings.anti_tamper.enabled = true;
+
+    let report = validate_config(&config);
+
+    let lines: Vec<String> = report.to_string().lines().map(str::to_owned).collect();
+    let expected: Vec<String> = report.issues.iter().map(|i| i.to_string()).collect();
+    assert_eq!(report.issues.len(), 2, "{report:?}");
+    assert_eq!(lines, expected);
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.starts_with("error: module_settings.max_section_entropy = 9: "))
+    );
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.starts_with("warning: module_settings.anti_tamper: "))
+    );
+}
+
+// The config with the given passes, all enabled.
+fn config_with_passes(passes: Vec<ObfuscationPass>) -> YamlConfig {
+    let mut config = config();
+    config.profiles[0].passes = passes.into_iter().map(Into::into).collect();
+    config
+}
+
+#[test]
+fn passes_supported_everywhere_are_accepted_on_uefi() {
+    let config = config_with_passes(vec![ObfuscationPass::ObscureControlFlow(
+        ObscureControlFlow { probability: 50 },
+    )]);
+
+    let report = validate_environment(&config, PeEnvironment::UEFI);
+
+    assert!(report.is_empty(), "{report:?}");
+}
+
+#[test]
+fn unsupported_passes_are_rejected_for_the_environment() {
+    let tether = ObfuscationPass::TetherExtraction(TetherExtraction {
+        min_extract_len: 16,
+        endpoint: "tether.example.com".to_owned(),
+        port: 443,
+        server_public_key: "key".to_owned(),
+    });
+    let config = config_with_passes(vec![
+        ObfuscationPass::ObscureControlFlow(ObscureControlFlow { probability: 50 }),
+        ObfuscationPass::AntiEmulator,
+        tether,
+    ]);
+
+    let uefi = validate_environment(&config, PeEnvironment::UEFI);
+    let kernel = validate_environment(&config, PeEnvironment::KernelMode);
+
+    let rejected = |report: &ValidationReport| -> Vec<(String, Option<String>)> {
+        report
+            .errors()
+            .map(|e| (e.path.clone(), e.value.clone()))
+            .collect()
+    };
+    assert_eq!(
+        rejected(&uefi),
+        [
+            (
+                "profiles[0].passes[1]".to_owned(),
+                Some("AntiEmulator".to_owned())
+            ),
+            (
+                "profiles[0].passes[2]".to_owned(),
+                Some("TetherExtraction".to_owned())
+            ),
+        ]
+    );
+    assert_eq!(
+        rejected(&kernel),
+        [(
+            "profiles[0].passes[2]".to_owned(),
+            Some("TetherExtraction".to_owned())
+        )]
+    );
+    assert!(validate_environment(&config, PeEnvironment::UserMode).is_empty());
+}
+
+#[test]
+fn disabled_unsupported_passes_are_ignored() {
+    let mut config = config_with_passes(vec![ObfuscationPass::AntiEmulator]);
+    config.profiles[0].passes[0].enabled = false;
+
+    let report = validate_environment(&config, PeEnvironment::UEFI);
+
+    assert!(report.is_empty(), "{report:?}");
+}
+
+#[test]
+fn clear_unwind_info_warns_that_exceptions_break() {
+    let mut config = config();
+    config.module_settings.clear_unwind_info = true;
+
+    let report = validate_config(&config);
+
+    let issues = issues_at(&report, "module_settings.clear_unwind_info");
+    assert_eq!(issues.len(), 1, "{report:?}");
+    assert_eq!(issues[0].severity, Severity::Warning);
+    assert!(issues[0].message.contains("exception handling"));
+    assert!(!report.has_errors());
+
+    config.module_settings.clear_unwind_info = false;
+    assert!(validate_config(&config).is_empty());
+}
+
+#[test]
+fn compiler_preset_with_compiler_settings_is_rejected() {
+    let mut config = config();
+    config.profiles[0].compiler_settings = Some(CDCompilerSettings::default());
+
+    let report = validate_config(&config);
+
+    let issues = issues_at(&report, "profiles[0].compiler_preset");
+    assert_eq!(issues.len(), 1, "{report:?}");
+    assert_eq!(issues[0].severity, Severity::Error);
+    assert_eq!(
+        issues[0].to_string(),
+        "error: profiles[0].compiler_preset: must not be combined with `compiler_settings`"
+    );
+}
+
+#[test]
+fn validate_reports_every_violation_at_once() {
+    let config = YamlConfig::from_yaml_str(
+        r#"
+version: "9.0.0"
+module_settings:
+  fake_pdb_string:
+    enabled: true
+    value: "app.exe"
+  custom_section_name:
+    enabled: true
+    value: ".waytoolong"
+profiles:
+  - name: app
+    color: red
+    compiler_preset: fast
+    passes: []
+    symbols: [!Name main]
+  - name: app
+    compiler_preset: fast
+    passes:
+      - type: ObscureControlFlow
+        probability: 150
+      - type: ObscureReferences
+        probability: 0
+    symbols: [!Name helper]
+"#,
+    )
+    .unwrap();
+
+    let report = config.validate().unwrap_err();
+
+    let mut found: Vec<(Severity, &str)> = report
+        .issues
+        .iter()
+        .map(|i| (i.severity, i.path.as_str()))
+        .collect();
+    found.sort_by_key(|&(_, path)| path);
+    assert_eq!(
+        found,
+        [
+            (Severity::Error, "module_settings.custom_section_name.value"),
+            (Severity::Warning, "module_settings.fake_pdb_string.value"),
+            (Severity::Warning, "profiles[0].color"),
+            (Severity::Warning, "profiles[0].passes"),
+            (Severity::Error, "profiles[1].name"),
+            (Severity::Error, "profiles[1].passes[0].probability"),
+            (Severity::Warning, "profiles[1].passes[1].probability"),
+            (Severity::Error, "version"),
+        ],
+        "{report}"
+    );
+}
+
+fn no_semantics() -> Semantics {
+    Semantics {
+        add: false,
+        sub: false,
+        and: false,
+        xor: false,
+        or: false,
+        not: false,
+        neg: false,
+    }
+}
+
+fn no_bitwidths() -> BitWidths {
+    BitWidths {
+        bit8: false,
+        bit16: false,
+        bit32: false,
+        bit64: false,
+    }
+}
+
+#[test]
+fn semantics_and_bitwidths_report_when_empty() {
+    assert!(no_semantics().is_empty());
+    assert!(no_bitwidths().is_empty());
+    assert!(!Semantics::all().is_empty());
+    assert!(!BitWidths::all().is_empty());
+    assert!(
+        !Semantics {
+            neg: true,
+            ..no_semantics()
+        }
+        .is_empty()
+    );
+    assert!(
+        !BitWidths {
+            bit64: true,
+            ..no_bitwidths()
+        }
+        .is_empty()
+    );
+}
+
+#[test]
+fn passes_with_nothing_to_transform_warn() {
+    let config = config_with_passes(vec![
+        ObfuscationPass::MixedBooleanArithmetic(MixedBooleanArithmetic {
+            semantics: no_semantics(),
+            ..MixedBooleanArithmetic::default()
+        }),
+        ObfuscationPass::MutationEngine(MutationEngine {
+            bitwidths: no_bitwidths(),
+            ..MutationEngine::default()
+        }),
+        ObfuscationPass::ObscureConstants(ObscureConstants {
+            probability: 100,
+            iterations: 1,
+            bitwidths: no_bitwidths(),
+            ethnicities: SsaOrigins::all(),
+        }),
+        ObfuscationPass::MixedBooleanArithmetic(MixedBooleanArithmetic::default()),
+    ]);
+
+    let report = validate_config(&config);
+
+    let warnings: Vec<&str> = report.warnings().map(|w| w.path.as_str()).collect();
+    assert_eq!(
+        warnings,
+        [
+            "profiles[0].passes[0].semantics",
+            "profiles[0].passes[1].bitwidths",
+            "profiles[0].passes[2].bitwidths",
+        ],
+        "{report:?}"
+    );
+    assert!(!report.has_errors());
+    assert!(
+        report.issues[0]
+            .message
+            .contains("MixedBooleanArithmetic in profile `app`")
+    );
+}
@@ -0,0 +1,267 @@
+//! Golden tests pinning the JSON exchanged with the API.
+//!
+//! A failure means the wire format changed. If the change is intended, rerun the tests with
+//! `UPDATE_GOLDEN=1` to rewrite the files under `tests/fixtures/`, and review their diff.
+
+#![cfg(feature = "json")]
+
+use codedefender_config::{
+    AnalysisResult, AntiTamperSettings, BitWidths, CDCompilerSettings, CDConfig, CDModuleSettings,
+    CDProfile, CompilerPreset, CpuFeatureLevel, CustomSectionName, FakePdbString, LeaEncodeImm,
+    LoopEncodeSemantics, MixedBooleanArithmetic, MutationEngine, MutationEngineExtension,
+    ObfuscationPass, ObscureConstants, ObscureControlFlow, ObscureReferences,
+    OpaqueBlockDuplication, PeEnvironment, Semantics, SplitBlockPass, SsaOrigins,
+    SuppressConstants, TetherExtraction, VirtualizationProtection,
+};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+// Compares `actual` with the JSON in the fixture, rewriting the fixture first with
+// `UPDATE_GOLDEN` set. Returns the fixture's text.
+fn assert_golden(name: &str, actual: &serde_json::Value) -> String {
+    let path = fixture_path(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        let text = serde_json::to_string_pretty(actual).unwrap();
+        std::fs::write(&path, text + "\n").unwrap();
+    }
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let expected: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(
+        actual, &expected,
+        "{name} changed; rerun with UPDATE_GOLDEN=1 if this is intended"
+    );
+    text
+}
+
+// Checks the JSON of `value` against the fixture, and that the fixture reads back as
+// `value`.
+fn assert_round_trips_golden<T: Serialize + DeserializeOwned + PartialEq + Debug>(
+    name: &str,
+    value: &T,
+) {
+    let text = assert_golden(name, &serde_json::to_value(value).unwrap());
+    assert_eq!(&serde_json::from_str::<T>(&text).unwrap(), value);
+}
+
+// Module settings as they appear in a serialized `CDConfig`.
+fn wire_module_settings(settings: CDModuleSettings) -> serde_json::Value {
+    let config = CDConfig {
+        module_settings: settings,
+        profiles: Vec::new(),
+    };
+    serde_json::to_value(&config).unwrap()["module_settings"].take()
+}
+
+fn some_semantics() -> Semantics {
+    Semantics {
+        add: true,
+        sub: true,
+        and: false,
+        xor: true,
+        or: false,
+        not: true,
+        neg: false,
+    }
+}
+
+fn some_bitwidths() -> BitWidths {
+    BitWidths {
+        bit8: false,
+        bit16: true,
+        bit32: true,
+        bit64: true,
+    }
+}
+
+fn some_origins() -> SsaOrigins {
+    SsaOrigins {
+        normal: true,
+        memop: true,
+        fp_based_memop: false,
+        sp_based_memop: false,
+    }
+}
+
+// One of every pass, with settings that differ from their defaults.
+fn every_pass() -> Vec<ObfuscationPass> {
+    vec![
+        ObfuscationPass::LoopEncodeSemantics(LoopEncodeSemantics {
+            iterations: 2,
+            probability: 60,
+            semantics: some_semantics(),
+            bitwidths: some_bitwidths(),
+            ethnicities: some_origins(),
+        }),
+        ObfuscationPass::MixedBooleanArithmetic(MixedBooleanArithmetic {
+            iterations: 3,
+            probability: 70,
+            semantics: some_semantics(),
+            bitwidths: some_bitwidths(),
+            ethnicities: some_origins(),
+        }),
+        ObfuscationPass::MutationEngine(MutationEngine {
+            iterations: 1,
+            probability: 80,
+            extension: MutationEngineExtension::SSE3,
+            semantics: some_semantics(),
+            bitwidths: some_bitwidths(),
+            ethnicities: some_origins(),
+        }),
+        ObfuscationPass::TetherExtraction(TetherExtraction {
+            min_extract_len: 4,
+            endpoint: "tether.example.com".to_owned(),
+            port: 8443,
+            server_public_key: "ab".repeat(32),
+        }),
+        ObfuscationPass::SplitBlockPass(SplitBlockPass { threshold: 12 }),
+        ObfuscationPass::OpaqueBlockDuplication(OpaqueBlockDuplication {
+            iterations: 2,
+            probability: 30,
+        }),
+        ObfuscationPass::ObscureControlFlow(ObscureControlFlow { probability: 50 }),
+        ObfuscationPass::LeaEncodeImm(LeaEncodeImm {
+            probability: 40,
+            ethnicities: some_origins(),
+        }),
+        ObfuscationPass::ObscureConstants(ObscureConstants {
+            probability: 90,
+            iterations: 1,
+            bitwidths: some_bitwidths(),
+            ethnicities: some_origins(),
+        }),
+        ObfuscationPass::SuppressConstants(SuppressConstants {
+            ethnicities: some_origins(),
+        }),
+        ObfuscationPass::VirtualizationProtection(VirtualizationProtection {
+            complexity: 3,
+            handler_duplication: true,
+            probability: 20,
+        }),
+        ObfuscationPass::ObscureReferences(ObscureReferences { probability: 75 }),
+        ObfuscationPass::IDADecompilerCrasher,
+        ObfuscationPass::AntiEmulator,
+    ]
+}
+
+fn module_settings() -> CDModuleSettings {
+    CDModuleSettings {
+        ida_crasher: true,
+        import_protection: true,
+        obscure_entry_point: false,
+        clear_unwind_info: true,
+        tls_callback_protection: true,
+        fake_pdb_string: FakePdbString {
+            enabled: true,
+            value: "C:\\build\\app.pdb".to_owned(),
+        },
+        custom_section_name: CustomSectionName {
+            enabled: true,
+            value: ".cdx".to_owned(),
+            // Local settings are not part of the wire format.
+            allow_standard_name: false,
+        },
+        anti_tamper: AntiTamperSettings {
+            enabled: true,
+            anti_debug: true,
+            anti_vm: false,
+            crash_on_detection: true,
+        },
+        max_section_entropy: Some(6.5),
+        pad_sections: true,
+        min_cpu_features: None,
+    }
+}
+
+fn config() -> CDConfig {
+    CDConfig {
+        module_settings: module_settings(),
+        profiles: vec![
+            CDProfile {
+                name: "hot".to_owned(),
+                passes: every_pass(),
+                compiler_settings: CDCompilerSettings::from_preset(CompilerPreset::Max),
+                symbols: vec![0x1000, 0x2040, 0x1_0000_0000],
+            },
+            CDProfile {
+                name: "cold".to_owned(),
+                passes: vec![ObfuscationPass::AntiEmulator],
+                compiler_settings: CDCompilerSettings::from_preset(CompilerPreset::Fast),
+                symbols: Vec::new(),
+            },
+        ],
+    }
+}
+
+#[test]
+fn cdconfig_matches_the_golden_file() {
+    assert_round_trips_golden("cdconfig.json", &config());
+}
+
+#[test]
+fn module_settings_match_the_golden_file() {
+    let text = assert_golden(
+        "module_settings.json",
+        &wire_module_settings(module_settings()),
+    );
+
+    assert_eq!(
+        serde_json::from_str::<CDModuleSettings>(&text).unwrap(),
+        module_settings()
+    );
+}
+
+#[test]
+fn passes_match_the_golden_file() {
+    assert_round_trips_golden("passes.json", &every_pass());
+}
+
+#[test]
+fn local_settings_are_not_part_of_the_wire_format() {
+    let mut settings = module_settings();
+    settings.custom_section_name.allow_standard_name = true;
+    settings.min_cpu_features = Some(CpuFeatureLevel::Sse42);
+
+    assert_eq!(
+        wire_module_settings(settings),
+        wire_module_settings(module_settings())
+    );
+}
+
+#[test]
+fn analysis_result_reads_the_golden_payload() {
+    let text = std::fs::read_to_string(fixture_path("analysis_result.json")).unwrap();
+
+    let analysis: AnalysisResult = serde_json::from_str(&text).unwrap();
+
+    assert_eq!(analysis.environment, PeEnvironment::UserMode);
+    assert_eq!(analysis.functions.len(), 3);
+    let main = &analysis.functions[0];
+    assert_eq!(
+        (main.rva, main.symbol.as_str(), main.ref_count),
+        (0x1000, "main", 12)
+    );
+    assert_eq!(main.section.as_deref(), Some(".text"));
+    assert_eq!(main.size, Some(384));
+    // Older servers report neither the section nor the size.
+    assert_eq!(analysis.functions[2].section, None);
+    assert_eq!(analysis.functions[2].size, None);
+    assert_eq!(analysis.rejects.len(), 1);
+    assert_eq!(analysis.rejects[0].rva, 0x4000);
+    assert_eq!(analysis.rejects[0].ty, "ReadWriteToCode");
+    assert_eq!(analysis.macros.len(), 1);
+    assert_eq!(analysis.macros[0].name, "licensing");
+    assert_eq!(analysis.macros[0].rvas, [0x1000, 0x2000]);
+    assert_eq!(
+        serde_json::to_value(&analysis).unwrap()["functions"][0]["rva"],
+        4096
+    );
+}
@@ -0,0 +1,193 @@
+//! Round trips of configs through YAML.
+//!
+//! Every pass is read from the JSON golden file, so a pass added to `wire_format.rs` is
+//! also checked here.
+
+#![cfg(all(feature = "yaml", feature = "json"))]
+
+use codedefender_config::{
+    CDCompilerSettings, CompilerPreset, ObfuscationPass, PassEntry, YamlConfig, YamlSymbol,
+};
+use std::path::PathBuf;
+
+const CONFIG: &str = r##"
+version: "1.1.0"
+module_settings:
+  ida_crasher: true
+  import_protection: true
+  obscure_entry_point: false
+  clear_unwind_info: false
+  tls_callback_protection: true
+  fake_pdb_string:
+    enabled: true
+    value: "C:\\build\\app.pdb"
+  custom_section_name:
+    enabled: true
+    value: ".text"
+    allow_standard_name: true
+  anti_tamper:
+    enabled: true
+    anti_debug: true
+    anti_vm: false
+    crash_on_detection: true
+  max_section_entropy: 6.5
+  pad_sections: true
+  min_cpu_features: Sse42
+default_compiler_settings:
+  assembler_settings:
+    shuffle_basic_blocks: true
+    instruction_prefix: "0x3E"
+    random_prefix_chance: 0.25
+  optimization_settings:
+    constant_propagation: true
+    instruction_combine: false
+    dead_code_elim: true
+    prune_useless_block_params: false
+    iterations: 3
+  lifter_settings:
+    lift_calls: false
+    calling_convention: WindowsAbi
+    max_stack_copy_size: 512
+    split_on_calls_fallback: true
+profiles:
+  - name: hot
+    priority: -1
+    color: "#FF8800"
+    compiler_preset: max
+    passes:
+      - type: AntiEmulator
+      - type: IDADecompilerCrasher
+        enabled: false
+      - type: ObscureControlFlow
+        probability: 50
+        targets: [!Name main, !Rva 0x2000]
+        min_function_size: 64
+    symbols: [!Name main, !Rva 0x2000, !Rva 0xFFFFFFFFFFFFFFFF, !Section ".crypto"]
+    exclude: [!Name main_stub]
+    owner: crypto-team
+  - name: cold
+    passes:
+      - type: ObscureReferences
+        probability: 75
+    symbols: [All, !RefCountAtLeast 10]
+editor:
+  last_opened: "2024-01-01"
+"##;
+
+fn every_pass() -> Vec<ObfuscationPass> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/passes.json");
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn reload(config: &YamlConfig) -> YamlConfig {
+    YamlConfig::from_yaml_str(&config.to_yaml_string().unwrap()).unwrap()
+}
+
+#[test]
+fn every_pass_round_trips() {
+    for pass in every_pass() {
+        let yaml = serde_yaml::to_string(&pass).unwrap();
+
+        assert!(yaml.starts_with("type: "), "{yaml}");
+        assert_eq!(
+            serde_yaml::from_str::<ObfuscationPass>(&yaml).unwrap(),
+            pass
+        );
+    }
+}
+
+#[test]
+fn unit_passes_are_a_tagged_mapping() {
+    for pass in [
+        ObfuscationPass::AntiEmulator,
+        ObfuscationPass::IDADecompilerCrasher,
+    ] {
+        let yaml = serde_yaml::to_string(&pass).unwrap();
+
+        assert_eq!(yaml, format!("type: {}\n", pass.name()));
+        assert_eq!(
+            serde_yaml::from_str::<ObfuscationPass>(&yaml).unwrap(),
+            pass
+        );
+    }
+}
+
+#[test]
+fn every_pass_entry_round_trips_in_a_profile() {
+    let mut config = YamlConfig::from_yaml_str(CONFIG).unwrap();
+    config.profiles[0].passes = every_pass()
+        .into_iter()
+        .enumerate()
+        .map(|(i, pass)| PassEntry {
+            pass,
+            enabled: i % 2 == 0,
+            targets: (i % 3 == 0).then(|| vec![YamlSymbol::Name("main".to_owned())]),
+            min_function_size: (i % 4 == 0).then_some(32),
+        })
+        .collect();
+
+    assert_eq!(reload(&config), config);
+}
+
+#[test]
+fn full_config_round_trips() {
+    let config = YamlConfig::from_yaml_str(CONFIG).unwrap();
+
+    let reloaded = reload(&config);
+
+    assert_eq!(reloaded, config);
+    assert_eq!(
+        reloaded.to_yaml_string().unwrap(),
+        config.to_yaml_string().unwrap()
+    );
+    assert_eq!(
+        reloaded.profiles[0].compiler_preset,
+        Some(CompilerPreset::Max)
+    );
+    assert_eq!(
+        reloaded.profiles[0].symbols[2],
+        YamlSymbol::Rva(0xFFFF_FFFF_FFFF_FFFF)
+    );
+    assert!(reloaded.profiles[0].extra.contains_key("owner"));
+    assert!(reloaded.extra.contains_key("editor"));
+}
+
+#[test]
+fn explicit_compiler_settings_round_trip() {
+    let mut config = YamlConfig::from_yaml_str(CONFIG).unwrap();
+    config.profiles[1].compiler_settings =
+        Some(CDCompilerSettings::from_preset(CompilerPreset::Fast));
+    config.default_compiler_settings = None;
+
+    assert_eq!(reload(&config), config);
+}
+
+#[test]
+fn unknown_keys_keep_their_values_through_a_round_trip() {
+    let config = YamlConfig::from_yaml_str(CONFIG).unwrap();
+
+    let reloaded = reload(&config);
+
+    let owner = &reloaded.profiles[0].extra["owner"];
+    assert_eq!(owner.as_str(), Some("crypto-team"));
+    let editor = &reloaded.extra["editor"];
+    assert_eq!(editor["last_opened"].as_str(), Some("2024-01-01"));
+    // Known keys are parsed into their fields and never land in `extra`.
+    assert_eq!(
+        reloaded.extra.keys().collect::<Vec<_>>(),
+        ["editor"],
+        "{:?}",
+        reloaded.extra
+    );
+    assert_eq!(
+        reloaded.profiles[0].extra.keys().collect::<Vec<_>>(),
+        ["owner"]
+    );
+    assert!(reloaded.profiles[1].extra.is_empty());
+    assert!(
+        config
+            .to_yaml_string()
+            .unwrap()
+            .contains("owner: crypto-team")
+    );
+}
@@ -0,0 +1,101 @@
+use crate::UtilsError;
+use codedefender_config::{AnalysisFunction, AnalysisReject, AnalysisResult, CDProfile};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+
+// Log every function rejected by analysis, grouped by rejection type, so users can
+// see why a symbol they expected to protect was not resolved.
+pub fn log_rejects(analysis: &AnalysisResult) {
+    let mut by_type: BTreeMap<&str, Vec<&AnalysisReject>> = BTreeMap::new();
+    for reject in &analysis.rejects {
+        by_type.entry(reject.ty.as_str()).or_default().push(reject);
+    }
+
+    for (ty, rejects) in by_type {
+        log::info!(ty = ty, count = rejects.len(); "{} function(s) rejected for {}", rejects.len(), ty);
+        for reject in rejects {
+            log::info!(
+                ty = ty, rva = reject.rva, symbol = reject.symbol.as_str();
+                "  {:X} {}: {}",
+                reject.rva,
+                reject.symbol,
+                reject.reason
+            );
+            if let Some(remediation) = reject.remediation() {
+                log::info!(ty = ty, rva = reject.rva; "    hint: {}", remediation);
+            }
+        }
+    }
+}
+
+// Write the analyzed functions followed by the rejected ones as CSV, with the
+// columns `rva,symbol,ref_count,section,kind,reject_type,reject_reason`. RVAs are
+// hex (`0x1010`), `kind` is `function` or `reject`, and columns that don't apply to
+// a row are left empty.
+pub fn analysis_to_csv<W: Write>(analysis: &AnalysisResult, w: W) -> io::Result<()> {
+    let mut writer = csv::Writer::from_writer(w);
+    writer.write_record([
+        "rva",
+        "symbol",
+        "ref_count",
+        "section",
+        "kind",
+        "reject_type",
+        "reject_reason",
+    ])?;
+
+    for function in &analysis.functions {
+        writer.write_record([
+            format!("0x{:X}", function.rva).as_str(),
+            &function.symbol,
+            &function.ref_count.to_string(),
+            function.section.as_deref().unwrap_or_default(),
+            "function",
+            "",
+            "",
+        ])?;
+    }
+    for reject in &analysis.rejects {
+        writer.write_record([
+            format!("0x{:X}", reject.rva).as_str(),
+            &reject.symbol,
+            "",
+            "",
+            "reject",
+            &reject.ty,
+            &reject.reason,
+        ])?;
+    }
+
+    writer.flush()
+}
+
+// The analyzed functions a resolved profile targets, in the order of its symbols,
+// to check what globs, `All` and exclusions actually selected. Targeted RVAs
+// without an analyzed function (force-resolved rejects) are left out.
+pub fn protected_functions<'a>(
+    profile: &CDProfile,
+    analysis: &'a AnalysisResult,
+) -> Vec<&'a AnalysisFunction> {
+    let mut by_rva: HashMap<u64, &AnalysisFunction> = HashMap::new();
+    for function in &analysis.functions {
+        by_rva.entry(function.rva).or_insert(function);
+    }
+    profile
+        .symbols
+        .iter()
+        .filter_map(|rva| by_rva.get(rva).copied())
+        .collect()
+}
+
+// Fail early on an analysis that found nothing, instead of letting every symbol
+// fail to resolve.
+pub fn check_analysis(analysis: &AnalysisResult) -> Result<(), UtilsError> {
+    if analysis.is_empty() {
+        log::error!(
+            "Analysis found no functions; the binary may be unsupported, or its PDB may be missing"
+        );
+        return Err(UtilsError::EmptyAnalysis);
+    }
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use crate::interrupt::interrupted;
+use crate::{UtilsError, build_config, check_input, check_verified};
+use codedefender_api::{
+    ApiClient, ApiError, ArtifactManifest, DefendOptions, DownloadStatus, IdempotencyKey,
+    PollError, PollStrategy, analyze_program, cancel, defend_with, download, poll_until_ready,
+    upload_files, verify_upload,
+};
+use codedefender_config::YamlConfig;
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// A binary to obfuscate as part of `defend_many`.
+#[derive(Debug, Clone)]
+pub struct DefendJob {
+    // Name used in logs, usually the input file name.
+    pub name: String,
+    pub binary: Vec<u8>,
+    pub pdb: Option<Vec<u8>>,
+    pub config: YamlConfig,
+}
+
+// Why a single job of `defend_many` failed.
+#[derive(Debug)]
+pub enum JobError {
+    // The input was rejected or the config could not be built.
+    Build(UtilsError),
+    // Uploading, analyzing or submitting the binary failed.
+    Api(ApiError),
+    // Waiting for the obfuscated binary failed or timed out.
+    Poll(PollError),
+    // The job was stopped by `interrupt` (Ctrl-C). A submitted execution was cancelled.
+    Interrupted,
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobError::Build(e) => write!(f, "{}", e),
+            JobError::Api(e) => write!(f, "{}", e),
+            JobError::Poll(e) => write!(f, "{}", e),
+            JobError::Interrupted => write!(f, "interrupted"),
+        }
+    }
+}
+
+impl std::error::Error for JobError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JobError::Build(e) => Some(e),
+            JobError::Api(e) => Some(e),
+            JobError::Poll(e) => Some(e),
+            JobError::Interrupted => None,
+        }
+    }
+}
+
+// Output of a successful job: the downloaded archive and its manifest, if any.
+pub type JobOutput = (Vec<u8>, Option<ArtifactManifest>);
+
+// Upload, verify, analyze, obfuscate and download several binaries, with at most
+// `concurrency` jobs in flight. Every job runs to completion or failure on its
+// own, and results are returned in job order. Each in-flight job polls the
+// rate-limited download endpoint on its own, so raise `poll.interval` with
+// `concurrency` to stay under the limit.
+pub fn defend_many(
+    jobs: Vec<DefendJob>,
+    concurrency: usize,
+    poll: &PollStrategy,
+    client: &ApiClient,
+    api_key: &str,
+) -> Vec<Result<JobOutput, JobError>> {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<JobOutput, JobError>>>> =
+        Mutex::new(jobs.iter().map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..concurrency.clamp(1, jobs.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(job) = jobs.get(i) else {
+                        break;
+                    };
+                    let result = run_job(job, poll, client, api_key);
+                    match &result {
+                        Ok(_) => log::info!(job = job.name.as_str(); "Obfuscated {}", job.name),
+                        Err(e) => {
+                            log::error!(job = job.name.as_str(); "Failed to obfuscate {}: {}", job.name, e)
+                        }
+                    }
+                    results.lock().unwrap()[i] = Some(result);
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every job is run by a worker"))
+        .collect()
+}
+
+fn run_job(
+    job: &DefendJob,
+    poll: &PollStrategy,
+    client: &ApiClient,
+    api_key: &str,
+) -> Result<JobOutput, JobError> {
+    check_input(&job.binary).map_err(JobError::Build)?;
+    let (file_id, pdb_id) = upload_files(job.binary.clone(), job.pdb.clone(), client, api_key)
+        .map_err(JobError::Api)?;
+    // Servers without the verify endpoint answer 404; analysis then reports bad input.
+    match verify_upload(&file_id, client, api_key) {
+        Ok(verified) => check_verified(&verified).map_err(JobError::Build)?,
+        Err(ApiError::Status { status, .. }) if status.as_u16() == 404 => {
+            log::debug!(job = job.name.as_str(); "Upload verification is not supported, skipping it")
+        }
+        Err(e) => return Err(JobError::Api(e)),
+    }
+    let analysis =
+        analyze_program(file_id.clone(), pdb_id, client, api_key).map_err(JobError::Api)?;
+    let config = build_config(&job.config, &analysis).map_err(JobError::Build)?;
+    if interrupted() {
+        return Err(JobError::Interrupted);
+    }
+    // One key per job: if the submission fails in transit, it is retried once with the
+    // same key, so the server does not start the job twice if it got the first request.
+    let options = DefendOptions {
+        idempotency_key: Some(IdempotencyKey::generate()),
+    };
+    let execution_id =
+        match defend_with(file_id.clone(), config.clone(), &options, client, api_key) {
+            Err(ApiError::Http(e)) => {
+                log::warn!(job = job.name.as_str(); "Retrying submission of {}: {}", job.name, e);
+                defend_with(file_id, config, &options, client, api_key)
+            }
+            result => result,
+        }
+        .map_err(JobError::Api)?;
+    let result = poll_until_ready(
+        || {
+            // Stops polling; the error is replaced by `JobError::Interrupted` below.
+            if interrupted() {
+                return DownloadStatus::Failed(ApiError::Io(
+                    std::io::ErrorKind::Interrupted.into(),
+                ));
+            }
+            download(execution_id.clone(), client, api_key)
+        },
+        poll,
+    );
+    if interrupted() {
+        log::warn!(job = job.name.as_str(); "Interrupted, cancelling execution {}", execution_id);
+        if let Err(e) = cancel(execution_id, client, api_key) {
+            log::error!(job = job.name.as_str(); "Failed to cancel {}: {}", job.name, e);
+        }
+        return Err(JobError::Interrupted);
+    }
+    result.map_err(JobError::Poll)
+}
@@ -0,0 +1,117 @@
+use codedefender_api::AnalyzeOptions;
+use codedefender_config::{AnalysisResult, YAML_CONFIG_VERSION};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// On-disk cache of analysis results, keyed by the hash of the analyzed binary, its
+// PDB and the analysis options. Re-running with an unchanged binary then skips the upload and analysis.
+#[derive(Debug, Clone)]
+pub struct AnalysisCache {
+    dir: PathBuf,
+}
+
+impl AnalysisCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // Cache key of a binary, its optional PDB and the options it is analyzed with.
+    // The config version is part of the key so results cached by an older format are
+    // not reused.
+    pub fn key(binary: &[u8], pdb: Option<&[u8]>, options: &AnalyzeOptions) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(YAML_CONFIG_VERSION.as_bytes());
+        hasher.update([0]);
+        hasher.update(Sha256::digest(binary));
+        if let Some(pdb) = pdb {
+            hasher.update(Sha256::digest(pdb));
+        }
+        for (name, value) in options.query_params() {
+            hasher.update([0]);
+            hasher.update(name.as_bytes());
+            hasher.update([b'=']);
+            hasher.update(value.as_bytes());
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    // Cached result for `key`. A missing or unreadable entry is a miss.
+    pub fn load(&self, key: &str) -> Option<AnalysisResult> {
+        let path = self.path(key);
+        let bytes = fs::read(&path).ok()?;
+        match serde_json::from_slice(&bytes) {
+            Ok(analysis) => Some(analysis),
+            Err(e) => {
+                log::warn!(path = path.display().to_string(); "Ignoring corrupt analysis cache entry {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub fn store(&self, key: &str, analysis: &AnalysisResult) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_vec(analysis).map_err(io::Error::other)?;
+        fs::write(self.path(key), json)
+    }
+}
+
+// Flags enabling the analysis cache.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct CacheOptions {
+    // Directory to cache analysis results in, keyed by the input binary and PDB.
+    #[arg(long, value_name = "DIR")]
+    pub analysis_cache: Option<PathBuf>,
+    // Ignore `--analysis-cache` and always analyze.
+    #[arg(long)]
+    pub no_cache: bool,
+}
+
+impl CacheOptions {
+    pub fn cache(&self) -> Option<AnalysisCache> {
+        if self.no_cache {
+            return None;
+        }
+        self.analysis_cache.as_ref().map(AnalysisCache::new)
+    }
+}
+
+// Return the cached analysis of `binary` and `pdb` with `options` if there is one,
+// otherwise run `analyze` (which should analyze with `options`) and cache its result. Without a cache this just runs `analyze`. Failing
+// to write the cache only logs a warning.
+pub fn analyze_cached<E>(
+    cache: Option<&AnalysisCache>,
+    binary: &[u8],
+    pdb: Option<&[u8]>,
+    options: &AnalyzeOptions,
+    analyze: impl FnOnce() -> Result<AnalysisResult, E>,
+) -> Result<AnalysisResult, E> {
+    let Some(cache) = cache else {
+        return analyze();
+    };
+
+    let key = AnalysisCache::key(binary, pdb, options);
+    if let Some(analysis) = cache.load(&key) {
+        log::info!(key = key.as_str(); "Using cached analysis from {}", cache.dir().display());
+        return Ok(analysis);
+    }
+
+    let analysis = analyze()?;
+    if let Err(e) = cache.store(&key, &analysis) {
+        log::warn!(key = key.as_str(); "Failed to write analysis cache in {}: {}", cache.dir().display(), e);
+    }
+    Ok(analysis)
+}
@@ -0,0 +1,163 @@
+use std::fmt;
+use std::path::PathBuf;
+
+// Errors returned while checking inputs and building the config submitted to the API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UtilsError {
+    // Analysis found neither functions nor rejects.
+    EmptyAnalysis,
+    // No function or reject has this name.
+    MissingSymbol(String),
+    // No function or targetable reject starts at this RVA.
+    InvalidRva(u64),
+    // The symbol matched a function rejected by analysis that can't be targeted.
+    RejectedSymbol {
+        symbol: String,
+        reason: String,
+    },
+    // A section was targeted, but analysis didn't report function sections.
+    MissingSectionInfo(String),
+    // The profile sets both `compiler_settings` and `compiler_preset`.
+    ConflictingCompilerSettings(String),
+    // The profile has no compiler settings and the config has no default.
+    MissingCompilerSettings(String),
+    // A macro adds an RVA the profile excludes, with `strict_exclusions` set.
+    ExcludedMacroSymbol {
+        profile: String,
+        rva: u64,
+    },
+    // An exclusion of the profile matches no symbol, with `strict_exclusions` set.
+    UnmatchedExclusion {
+        profile: String,
+        symbol: String,
+    },
+    // A pass targets RVAs outside its profile's resolved symbols.
+    TargetsOutsideProfile {
+        profile: String,
+        pass: String,
+        rvas: Vec<u64>,
+    },
+    // Every profile failed to resolve, with `skip_failed_profiles` set.
+    NoProfilesResolved,
+    // Two profiles of the built config have this name.
+    DuplicateProfileName(String),
+    // Profiles were selected by names that match no profile.
+    UnknownProfiles {
+        unknown: Vec<String>,
+        available: Vec<String>,
+    },
+    // The config file could not be read.
+    ReadConfig {
+        path: PathBuf,
+        message: String,
+    },
+    // The config file is not a valid YAML or JSON config.
+    ParseConfig {
+        path: PathBuf,
+        message: String,
+    },
+    // The config failed validation, counting warnings with `fail_on_warning`.
+    InvalidConfig {
+        errors: usize,
+        warnings: usize,
+    },
+    // Watching files for changes failed.
+    #[cfg(feature = "watch")]
+    Watch(String),
+    // The server could not decode the uploaded binary.
+    UploadRejected(String),
+    // The input file is empty.
+    EmptyInput,
+    // The input file lacks the MZ or PE signature.
+    NotPe,
+}
+
+impl fmt::Display for UtilsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UtilsError::EmptyAnalysis => write!(
+                f,
+                "Analysis found no functions; the binary may be unsupported, or its PDB may be missing"
+            ),
+            UtilsError::MissingSymbol(symbol) => write!(f, "Missing symbol `{}`", symbol),
+            UtilsError::InvalidRva(rva) => write!(f, "Invalid RVA {:X}", rva),
+            UtilsError::RejectedSymbol { symbol, reason } => {
+                write!(
+                    f,
+                    "Symbol `{}` was rejected by analysis: {}",
+                    symbol, reason
+                )
+            }
+            UtilsError::MissingSectionInfo(symbol) => write!(
+                f,
+                "Cannot target {}, analysis did not report function sections",
+                symbol
+            ),
+            UtilsError::ConflictingCompilerSettings(profile) => write!(
+                f,
+                "Profile `{}` sets both compiler_settings and compiler_preset",
+                profile
+            ),
+            UtilsError::MissingCompilerSettings(profile) => write!(
+                f,
+                "Profile `{}` has no compiler_settings and no default_compiler_settings is set",
+                profile
+            ),
+            UtilsError::ExcludedMacroSymbol { profile, rva } => write!(
+                f,
+                "Macro adds RVA {:X} to profile `{}`, which excludes it",
+                rva, profile
+            ),
+            UtilsError::UnmatchedExclusion { profile, symbol } => write!(
+                f,
+                "Exclusion `{}` in profile `{}` matches nothing",
+                symbol, profile
+            ),
+            UtilsError::TargetsOutsideProfile {
+                profile,
+                pass,
+                rvas,
+            } => write!(
+                f,
+                "{} in profile `{}` targets {} symbol(s) the profile does not",
+                pass,
+                profile,
+                rvas.len()
+            ),
+            UtilsError::NoProfilesResolved => write!(f, "No profiles resolved"),
+            UtilsError::DuplicateProfileName(profile) => {
+                write!(f, "Several profiles are named `{}`", profile)
+            }
+            UtilsError::UnknownProfiles { unknown, available } => write!(
+                f,
+                "Unknown profile(s) {}; available: {}",
+                unknown.join(", "),
+                available.join(", ")
+            ),
+            UtilsError::ReadConfig { path, message } => {
+                write!(f, "Failed to read config {}: {}", path.display(), message)
+            }
+            UtilsError::ParseConfig { path, message } => {
+                write!(f, "Failed to parse config {}: {}", path.display(), message)
+            }
+            UtilsError::InvalidConfig { errors, warnings } => write!(
+                f,
+                "Config failed validation with {} error(s) and {} warning(s)",
+                errors, warnings
+            ),
+            #[cfg(feature = "watch")]
+            UtilsError::Watch(message) => write!(f, "Failed to watch files: {}", message),
+            UtilsError::UploadRejected(reason) => {
+                write!(
+                    f,
+                    "The server could not decode the uploaded binary: {}",
+                    reason
+                )
+            }
+            UtilsError::EmptyInput => write!(f, "Empty input file"),
+            UtilsError::NotPe => write!(f, "Input file is not a PE image"),
+        }
+    }
+}
+
+impl std::error::Error for UtilsError {}
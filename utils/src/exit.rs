@@ -0,0 +1,69 @@
+use crate::{JobError, UtilsError};
+use codedefender_api::{ApiError, PollError};
+use std::process::ExitCode;
+
+// Process exit codes for command-line tools built on this crate, so scripts can
+// tell failures apart. Success is 0.
+//
+// | Code | Meaning                                                        |
+// |------|----------------------------------------------------------------|
+// | 1    | Any other failure, e.g. watching files failed                  |
+// | 2    | The config, input binary or symbols are invalid                |
+// | 3    | A request failed, or the server rejected it                    |
+// | 4    | The server could not obfuscate the binary                      |
+// | 5    | The obfuscation did not complete within the maximum wait       |
+// | 130  | Interrupted by Ctrl-C (128 + SIGINT, as shells report it)      |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureCode {
+    Other = 1,
+    Config = 2,
+    Network = 3,
+    Obfuscation = 4,
+    Timeout = 5,
+    Interrupted = 130,
+}
+
+impl FailureCode {
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+}
+
+impl From<FailureCode> for ExitCode {
+    fn from(code: FailureCode) -> Self {
+        ExitCode::from(code.code())
+    }
+}
+
+impl UtilsError {
+    // Exit code for a command that failed with this error.
+    pub fn failure_code(&self) -> FailureCode {
+        match self {
+            #[cfg(feature = "watch")]
+            UtilsError::Watch(_) => FailureCode::Other,
+            _ => FailureCode::Config,
+        }
+    }
+}
+
+impl JobError {
+    // Exit code for a command that failed with this error.
+    pub fn failure_code(&self) -> FailureCode {
+        match self {
+            JobError::Build(e) => e.failure_code(),
+            JobError::Api(e) => api_failure_code(e),
+            JobError::Poll(PollError::Failed(e)) => api_failure_code(e),
+            JobError::Poll(PollError::TimedOut(_)) => FailureCode::Timeout,
+            JobError::Interrupted => FailureCode::Interrupted,
+        }
+    }
+}
+
+fn api_failure_code(error: &ApiError) -> FailureCode {
+    match error {
+        ApiError::Defend { .. } => FailureCode::Obfuscation,
+        // The input or a local file is at fault, not the connection.
+        ApiError::TooLarge { .. } | ApiError::Io(_) => FailureCode::Config,
+        _ => FailureCode::Network,
+    }
+}
@@ -0,0 +1,124 @@
+use codedefender_config::{AnalysisReject, AnalysisResult};
+use std::collections::{HashMap, HashSet};
+
+// Lookup tables built once from an analysis, so resolving many symbols doesn't scan
+// every function for each of them.
+pub struct AnalysisIndex<'a> {
+    analysis: &'a AnalysisResult,
+    by_name: HashMap<&'a str, u64>,
+    valid_rvas: HashSet<u64>,
+    function_rvas: HashSet<u64>,
+    rejects_by_name: HashMap<&'a str, &'a AnalysisReject>,
+    rejects_by_rva: HashMap<u64, &'a AnalysisReject>,
+}
+
+impl<'a> AnalysisIndex<'a> {
+    pub fn new(analysis: &'a AnalysisResult) -> Self {
+        let mut by_name = HashMap::with_capacity(analysis.functions.len());
+        let mut valid_rvas = HashSet::with_capacity(analysis.functions.len());
+        let mut function_rvas = HashSet::with_capacity(analysis.functions.len());
+        let mut rejects_by_name = HashMap::with_capacity(analysis.rejects.len());
+        let mut rejects_by_rva = HashMap::with_capacity(analysis.rejects.len());
+
+        for function in &analysis.functions {
+            by_name
+                .entry(function.symbol.as_str())
+                .or_insert(function.rva);
+            valid_rvas.insert(function.rva);
+            function_rvas.insert(function.rva);
+        }
+        // Force-resolvable rejects can be targeted, but a function of the same
+        // name always takes precedence. Force-resolvable rejects are indexed
+        // first so they win over other rejects of the same name or RVA.
+        let (forced, others): (Vec<_>, Vec<_>) = analysis
+            .rejects
+            .iter()
+            .partition(|r| r.is_force_resolvable());
+        for reject in &forced {
+            by_name.entry(reject.symbol.as_str()).or_insert(reject.rva);
+            valid_rvas.insert(reject.rva);
+        }
+        for reject in forced.into_iter().chain(others) {
+            rejects_by_name
+                .entry(reject.symbol.as_str())
+                .or_insert(reject);
+            rejects_by_rva.entry(reject.rva).or_insert(reject);
+        }
+
+        Self {
+            analysis,
+            by_name,
+            valid_rvas,
+            function_rvas,
+            rejects_by_name,
+            rejects_by_rva,
+        }
+    }
+
+    pub fn analysis(&self) -> &'a AnalysisResult {
+        self.analysis
+    }
+
+    // RVA of the function (or force-resolvable reject) with the given name.
+    pub fn rva_of(&self, name: &str) -> Option<u64> {
+        self.by_name.get(name).copied()
+    }
+
+    // Every function RVA followed by the force-resolvable reject RVAs, in analysis
+    // order.
+    pub fn all_rvas(&self) -> impl Iterator<Item = u64> + '_ {
+        self.analysis.functions.iter().map(|f| f.rva).chain(
+            self.analysis
+                .rejects
+                .iter()
+                .filter(|r| r.is_force_resolvable())
+                .map(|r| r.rva),
+        )
+    }
+
+    // RVAs of the functions in the given section, or `None` if analysis didn't
+    // report section membership for any function.
+    pub fn section_rvas(&self, section: &str) -> Option<Vec<u64>> {
+        let functions = &self.analysis.functions;
+        if functions.iter().all(|f| f.section.is_none()) {
+            return None;
+        }
+        Some(
+            functions
+                .iter()
+                .filter(|f| f.section.as_deref() == Some(section))
+                .map(|f| f.rva)
+                .collect(),
+        )
+    }
+
+    // RVAs of the analyzed functions referenced at least `count` times, in
+    // analysis order.
+    pub fn rvas_with_ref_count_at_least(&self, count: usize) -> Vec<u64> {
+        self.analysis
+            .functions
+            .iter()
+            .filter(|f| f.ref_count >= count)
+            .map(|f| f.rva)
+            .collect()
+    }
+
+    pub fn is_valid_rva(&self, rva: u64) -> bool {
+        self.valid_rvas.contains(&rva)
+    }
+
+    // Whether an analyzed (not rejected) function starts at the RVA.
+    pub fn is_function(&self, rva: u64) -> bool {
+        self.function_rvas.contains(&rva)
+    }
+
+    // Reject with the given name, preferring force-resolvable ones.
+    pub fn reject_named(&self, name: &str) -> Option<&'a AnalysisReject> {
+        self.rejects_by_name.get(name).copied()
+    }
+
+    // Reject at the given RVA, preferring force-resolvable ones.
+    pub fn reject_at(&self, rva: u64) -> Option<&'a AnalysisReject> {
+        self.rejects_by_rva.get(&rva).copied()
+    }
+}
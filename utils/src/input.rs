@@ -0,0 +1,116 @@
+use crate::UtilsError;
+use codedefender_config::{VerifyResult, YamlConfig};
+use std::path::{Path, PathBuf};
+
+// Look for the PDB MSVC writes next to a binary, `<input_stem>.pdb` in the same
+// directory. Returns `None` (and logs it) when there is none, since analysis works
+// without symbols.
+pub fn find_adjacent_pdb(input: &Path) -> Option<PathBuf> {
+    let pdb = input.with_extension("pdb");
+    if pdb != input && pdb.is_file() {
+        log::info!(pdb = pdb.display().to_string(); "Found PDB `{}`", pdb.display());
+        Some(pdb)
+    } else {
+        log::info!(
+            "No PDB found next to `{}`, proceeding without symbols",
+            input.display()
+        );
+        None
+    }
+}
+
+// Pick the PDB to upload: an explicitly given one always wins, otherwise the
+// adjacent PDB is used when `auto` is set.
+pub fn resolve_pdb_path(input: &Path, explicit: Option<&Path>, auto: bool) -> Option<PathBuf> {
+    match explicit {
+        Some(path) => Some(path.to_path_buf()),
+        None if auto => find_adjacent_pdb(input),
+        None => None,
+    }
+}
+
+// Whether `bytes` starts like a PE image: the `MZ` DOS magic, and the `PE\0\0`
+// signature at the offset stored in the DOS header's `e_lfanew` field.
+pub fn looks_like_pe(bytes: &[u8]) -> bool {
+    const E_LFANEW_OFFSET: usize = 0x3C;
+
+    if !bytes.starts_with(b"MZ") {
+        return false;
+    }
+    let Some(e_lfanew) = bytes.get(E_LFANEW_OFFSET..E_LFANEW_OFFSET + 4) else {
+        return false;
+    };
+    let pe_offset = u32::from_le_bytes(e_lfanew.try_into().unwrap()) as usize;
+    bytes
+        .get(pe_offset..)
+        .is_some_and(|rest| rest.starts_with(b"PE\0\0"))
+}
+
+// Check the input binary before uploading it, so an empty or non-PE file fails
+// right away instead of as a confusing analysis error.
+pub fn check_input(bytes: &[u8]) -> Result<(), UtilsError> {
+    if bytes.is_empty() {
+        log::error!("Input file is empty");
+        return Err(UtilsError::EmptyInput);
+    }
+    if !looks_like_pe(bytes) {
+        log::error!("Input file is not a PE image (missing MZ or PE signature)");
+        return Err(UtilsError::NotPe);
+    }
+    Ok(())
+}
+
+// Fail fast when the server could not decode an uploaded binary, as reported by
+// `codedefender_api::verify_upload`, instead of waiting for analysis to fail.
+pub fn check_verified(result: &VerifyResult) -> Result<(), UtilsError> {
+    if result.valid {
+        return Ok(());
+    }
+    let reason = result.reason.as_deref().unwrap_or("no reason given");
+    log::error!(
+        "The server could not decode the uploaded binary: {}",
+        reason
+    );
+    Err(UtilsError::UploadRejected(reason.to_owned()))
+}
+
+// Format of a config file read by `load_config`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ConfigFormat {
+    // JSON for a `.json` extension, YAML otherwise.
+    #[default]
+    Auto,
+    Yaml,
+    Json,
+}
+
+// Read and parse a config file. Both formats deserialize into the same `YamlConfig`.
+pub fn load_config(path: &Path, format: ConfigFormat) -> Result<YamlConfig, UtilsError> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        log::error!(path = path.display().to_string(); "Failed to read config {}: {}", path.display(), e);
+        UtilsError::ReadConfig {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        }
+    })?;
+
+    let is_json = match format {
+        ConfigFormat::Auto => path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json")),
+        ConfigFormat::Yaml => false,
+        ConfigFormat::Json => true,
+    };
+    let parsed = if is_json {
+        YamlConfig::from_json_str(&contents)
+    } else {
+        YamlConfig::from_yaml_str(&contents)
+    };
+    parsed.map_err(|e| {
+        log::error!(path = path.display().to_string(); "Failed to parse config {}: {}", path.display(), e);
+        UtilsError::ParseConfig {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        }
+    })
+}
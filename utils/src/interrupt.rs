@@ -0,0 +1,19 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+// Install a Ctrl-C handler that calls `interrupt`. Running `defend_many` jobs then
+// cancel their execution on the server instead of leaving it to use up quota, and
+// jobs that have not submitted yet stop before submitting. Call once at startup.
+pub fn install_interrupt_handler() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(interrupt)
+}
+
+// Ask every running job to stop, as Ctrl-C does once `install_interrupt_handler` ran.
+pub fn interrupt() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+pub(crate) fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
@@ -1,56 +1,668 @@
-use codedefender_config::{AnalysisResult, YamlSymbol};
+use codedefender_config::{
+    AnalysisReject, AnalysisResult, CDConfig, CDProfile, Limits, Severity, YamlConfig, YamlProfile,
+    YamlSymbol, split_profile,
+};
+use std::collections::{HashMap, HashSet};
+
+mod analysis;
+mod batch;
+mod cache;
+mod error;
+mod exit;
+mod index;
+mod input;
+mod interrupt;
+mod logging;
+mod output;
+mod report;
+#[cfg(feature = "watch")]
+mod watch;
+
+pub use analysis::{analysis_to_csv, check_analysis, log_rejects, protected_functions};
+pub use batch::{DefendJob, JobError, JobOutput, defend_many};
+pub use cache::{AnalysisCache, CacheOptions, analyze_cached};
+pub use error::UtilsError;
+pub use exit::FailureCode;
+pub use index::AnalysisIndex;
+pub use input::{
+    ConfigFormat, check_input, check_verified, find_adjacent_pdb, load_config, looks_like_pe,
+    resolve_pdb_path,
+};
+pub use interrupt::{install_interrupt_handler, interrupt};
+pub use logging::{LogFormat, build_logger, init_logger};
+pub use output::{extract_archive, write_output};
+pub use report::{ObfuscationReport, ProfileReport};
+#[cfg(feature = "watch")]
+pub use watch::watch_files;
+
+// Build the config submitted to the API from a YAML config. Symbols are resolved
+// against the analysis, and macro profiles found during analysis are folded into
+// the profile with the same name, minus the symbols the profile excludes.
+// Profiles without their own compiler settings or preset inherit the config's
+// default compiler settings. Profiles are emitted in priority order, with their
+// symbols sorted ascending so the same inputs always produce the same config. A
+// symbol shared by several profiles stays in the one applied first. A
+// profile with passes restricted to `targets` is split into one profile per set
+// of applying passes, see `CDProfile::split_by_pass_targets`.
+// An analysis that found nothing fails right away, see `check_analysis`.
+pub fn build_config(
+    config: &YamlConfig,
+    analysis: &AnalysisResult,
+) -> Result<CDConfig, UtilsError> {
+    build_config_with(config, analysis, &BuildOptions::default())
+}
+
+// Options changing how `build_config_with` handles failures.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct BuildOptions {
+    // Drop profiles whose symbols fail to resolve instead of failing the build.
+    // The build still fails if no profile is left.
+    #[arg(long)]
+    pub skip_failed_profiles: bool,
+    // Fail the build when an analysis macro adds a symbol its profile excludes, or
+    // when an exclusion matches nothing, instead of only warning.
+    #[arg(long)]
+    pub strict_exclusions: bool,
+    // Split profiles with more than `limits.max_symbols_per_profile` symbols into
+    // several profiles with the same passes, so the config passes `check_limits`.
+    #[arg(long)]
+    pub auto_split: bool,
+    // Limits the built config is split to fit with `auto_split`.
+    #[arg(skip)]
+    pub limits: Limits,
+}
+
+// Same as `build_config`, with options.
+pub fn build_config_with(
+    config: &YamlConfig,
+    analysis: &AnalysisResult,
+    options: &BuildOptions,
+) -> Result<CDConfig, UtilsError> {
+    check_analysis(analysis)?;
+    let ordered = config.profiles_by_priority();
+    let resolved = resolve_each_profile(&ordered, analysis);
+    let mut profiles = Vec::with_capacity(ordered.len());
+    let mut skipped = 0;
+    // Names of split-off profiles must not clash with any profile of the config.
+    let mut taken: HashSet<String> = config.profiles.iter().map(|p| p.name.clone()).collect();
+    // Symbols of the profiles built so far, which later profiles don't get.
+    let mut claimed = HashSet::new();
+    let target_index = config
+        .profiles
+        .iter()
+        .flat_map(|p| &p.passes)
+        .any(|entry| entry.targets.is_some())
+        .then(|| AnalysisIndex::new(analysis));
+    let function_sizes = config
+        .profiles
+        .iter()
+        .flat_map(|p| &p.passes)
+        .any(|entry| entry.min_function_size.is_some())
+        .then(|| function_sizes(analysis));
+
+    for (profile, result) in ordered.into_iter().zip(resolved) {
+        let ResolvedProfile {
+            mut symbols,
+            excluded,
+            unmatched,
+        } = match result {
+            Ok(resolved) => resolved,
+            Err(e) if options.skip_failed_profiles => {
+                log::warn!(
+                    profile = profile.name.as_str();
+                    "Skipping profile `{}`, its symbols failed to resolve: {}",
+                    profile.name,
+                    e
+                );
+                skipped += 1;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        if options.strict_exclusions
+            && let Some(symbol) = unmatched.into_iter().next()
+        {
+            return Err(UtilsError::UnmatchedExclusion {
+                profile: profile.name.clone(),
+                symbol: symbol.to_string(),
+            });
+        }
+
+        if profile.compiler_settings.is_some() && profile.compiler_preset.is_some() {
+            log::error!(
+                profile = profile.name.as_str();
+                "Profile `{}` sets both compiler_settings and compiler_preset",
+                profile.name
+            );
+            return Err(UtilsError::ConflictingCompilerSettings(
+                profile.name.clone(),
+            ));
+        }
+        let Some(compiler_settings) = config.compiler_settings_for(profile) else {
+            log::error!(
+                profile = profile.name.as_str();
+                "Profile `{}` has no compiler_settings and no default_compiler_settings is set",
+                profile.name
+            );
+            return Err(UtilsError::MissingCompilerSettings(profile.name.clone()));
+        };
+
+        for macro_profile in analysis.macros.iter().filter(|m| m.name == profile.name) {
+            for &rva in &macro_profile.rvas {
+                if !excluded.contains(&rva) {
+                    symbols.push(rva);
+                    continue;
+                }
+                if options.strict_exclusions {
+                    log::error!(
+                        profile = profile.name.as_str(), rva = rva;
+                        "Macro adds RVA {:X} to profile `{}`, which excludes it",
+                        rva,
+                        profile.name
+                    );
+                    return Err(UtilsError::ExcludedMacroSymbol {
+                        profile: profile.name.clone(),
+                        rva,
+                    });
+                }
+                log::warn!(
+                    profile = profile.name.as_str(), rva = rva;
+                    "Dropping RVA {:X} added by a macro to profile `{}`, which excludes it",
+                    rva,
+                    profile.name
+                );
+            }
+        }
+        dedup_rvas(&mut symbols);
+        symbols.sort_unstable();
+        let shared: HashSet<u64> = symbols
+            .iter()
+            .copied()
+            .filter(|rva| !claimed.insert(*rva))
+            .collect();
+        if !shared.is_empty() {
+            symbols.retain(|rva| !shared.contains(rva));
+            log::warn!(
+                profile = profile.name.as_str();
+                "Dropping {} symbol(s) from profile `{}` that a profile applied earlier already has",
+                shared.len(),
+                profile.name
+            );
+        }
+
+        let mut passes = Vec::new();
+        for entry in profile.passes.iter().filter(|entry| entry.enabled) {
+            let targets: Option<HashSet<u64>> = match (&entry.targets, &target_index) {
+                (Some(targets), Some(index)) => {
+                    let mut resolved = resolve_symbols_indexed(targets, index)?;
+                    resolved.retain(|rva| !shared.contains(rva));
+                    let outside: Vec<u64> = resolved
+                        .iter()
+                        .copied()
+                        .filter(|rva| symbols.binary_search(rva).is_err())
+                        .collect();
+                    if !outside.is_empty() {
+                        log::error!(
+                            profile = profile.name.as_str(), pass = entry.pass.name();
+                            "{} in profile `{}` targets {} symbol(s) the profile does not",
+                            entry.pass.name(),
+                            profile.name,
+                            outside.len()
+                        );
+                        return Err(UtilsError::TargetsOutsideProfile {
+                            profile: profile.name.clone(),
+                            pass: entry.pass.name().to_owned(),
+                            rvas: outside,
+                        });
+                    }
+                    Some(resolved.into_iter().collect())
+                }
+                _ => None,
+            };
+            let targets = match (entry.min_function_size, &function_sizes) {
+                (Some(_), Some(sizes)) if sizes.is_empty() => {
+                    log::warn!(
+                        profile = profile.name.as_str(), pass = entry.pass.name();
+                        "Ignoring min_function_size for {} in profile `{}`, analysis reported no function sizes",
+                        entry.pass.name(),
+                        profile.name
+                    );
+                    targets
+                }
+                (Some(min_size), Some(sizes)) => {
+                    let candidates = targets.unwrap_or_else(|| symbols.iter().copied().collect());
+                    let total = candidates.len();
+                    let kept: HashSet<u64> = candidates
+                        .into_iter()
+                        .filter(|rva| sizes.get(rva).is_none_or(|&size| size >= min_size))
+                        .collect();
+                    log::info!(
+                        profile = profile.name.as_str(), pass = entry.pass.name();
+                        "{} in profile `{}` skips {} of {} function(s) smaller than {} bytes",
+                        entry.pass.name(),
+                        profile.name,
+                        total - kept.len(),
+                        total,
+                        min_size
+                    );
+                    Some(kept)
+                }
+                _ => targets,
+            };
+            passes.push((entry.pass.clone(), targets));
+        }
+        let split = CDProfile::split_by_pass_targets(
+            &profile.name,
+            passes,
+            compiler_settings.into_owned(),
+            symbols,
+            &mut taken,
+        );
+        if !options.auto_split {
+            profiles.extend(split);
+            continue;
+        }
+        let max_symbols = options.limits.max_symbols_per_profile;
+        for part in split {
+            let name = part.name.clone();
+            let parts = split_profile(part, max_symbols, &mut taken);
+            if parts.len() > 1 {
+                log::info!(
+                    profile = name.as_str();
+                    "Split profile `{}` into {} profiles of at most {} symbols",
+                    name,
+                    parts.len(),
+                    max_symbols.max(1)
+                );
+            }
+            profiles.extend(parts);
+        }
+    }
+
+    if skipped > 0 {
+        if profiles.is_empty() {
+            log::error!("All {} profiles failed to resolve", skipped);
+            return Err(UtilsError::NoProfilesResolved);
+        }
+        log::warn!(
+            "Skipped {} of {} profiles that failed to resolve",
+            skipped,
+            skipped + profiles.len()
+        );
+    }
+
+    // Profiles are told apart by name, e.g. by analysis macros and reports.
+    let mut names = HashSet::new();
+    if let Some(duplicate) = profiles.iter().find(|p| !names.insert(p.name.as_str())) {
+        log::error!(
+            profile = duplicate.name.as_str();
+            "Several profiles are named `{}`",
+            duplicate.name
+        );
+        return Err(UtilsError::DuplicateProfileName(duplicate.name.clone()));
+    }
+
+    Ok(CDConfig {
+        module_settings: config.module_settings.clone(),
+        profiles,
+    })
+}
+
+// Options changing how `check_config` treats validation problems.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct CheckOptions {
+    // Treat validation warnings as errors, e.g. in CI.
+    #[arg(long)]
+    pub fail_on_warning: bool,
+}
+
+// Run every static check on a config and log the problems found. Errors fail the
+// check; warnings only fail it with `fail_on_warning`.
+pub fn check_config(config: &YamlConfig, options: &CheckOptions) -> Result<(), UtilsError> {
+    let report = config.validation_report();
+    for issue in &report.issues {
+        match issue.severity {
+            Severity::Error => log::error!(path = issue.path.as_str(); "{}", issue),
+            Severity::Warning => log::warn!(path = issue.path.as_str(); "{}", issue),
+        }
+    }
+
+    let errors = report.errors().count();
+    let warnings = report.warnings().count();
+    let result = if options.fail_on_warning {
+        report.into_strict_result()
+    } else {
+        report.into_result()
+    };
+    result.map_err(|_| UtilsError::InvalidConfig { errors, warnings })
+}
+
+// Keep only the named profiles of a config, for running a subset of them without
+// editing the file. Empty `names` keeps every profile. Macros of dropped profiles
+// are ignored by `build_config`, since macros are only folded into profiles that
+// exist. Errors if a name matches no profile, listing the available names.
+pub fn filter_profiles(config: &YamlConfig, names: &[String]) -> Result<YamlConfig, UtilsError> {
+    if names.is_empty() {
+        return Ok(config.clone());
+    }
+
+    let available: Vec<&str> = config.profiles.iter().map(|p| p.name.as_str()).collect();
+    let unknown: Vec<&str> = names
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !available.contains(name))
+        .collect();
+    if !unknown.is_empty() {
+        log::error!(
+            "Unknown profile{} {}, available profiles: {}",
+            if unknown.len() == 1 { "" } else { "s" },
+            unknown.join(", "),
+            available.join(", ")
+        );
+        return Err(UtilsError::UnknownProfiles {
+            unknown: unknown.into_iter().map(str::to_owned).collect(),
+            available: available.into_iter().map(str::to_owned).collect(),
+        });
+    }
+
+    let mut filtered = config.clone();
+    filtered.profiles.retain(|p| names.contains(&p.name));
+    Ok(filtered)
+}
+
+// Resolve the symbols of each profile, returning one RVA list per profile in the
+// same order. Fails on the first profile that fails to resolve. With the
+// `parallel` feature, profiles are resolved concurrently against a shared index.
+pub fn resolve_profiles(
+    profiles: &[&YamlProfile],
+    analysis: &AnalysisResult,
+) -> Result<Vec<Vec<u64>>, UtilsError> {
+    resolve_each_profile(profiles, analysis)
+        .into_iter()
+        .map(|result| result.map(|resolved| resolved.symbols))
+        .collect()
+}
+
+// Resolve the symbols of each profile, returning every profile's result in the
+// same order.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "resolve_symbols",
+        skip_all,
+        fields(
+            profile_count = profiles.len(),
+            function_count = analysis.functions.len(),
+            symbol_count = profiles.iter().map(|p| p.symbols.len()).sum::<usize>(),
+        )
+    )
+)]
+fn resolve_each_profile(
+    profiles: &[&YamlProfile],
+    analysis: &AnalysisResult,
+) -> Vec<Result<ResolvedProfile, UtilsError>> {
+    let index = AnalysisIndex::new(analysis);
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+
+        profiles
+            .par_iter()
+            .map(|p| resolve_profile(p, &index))
+            .collect()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    profiles
+        .iter()
+        .map(|p| resolve_profile(p, &index))
+        .collect()
+}
+
+// A profile's resolved symbols, the RVAs its exclusions matched, and the
+// exclusions that matched nothing.
+struct ResolvedProfile {
+    symbols: Vec<u64>,
+    excluded: HashSet<u64>,
+    unmatched: Vec<YamlSymbol>,
+}
+
+// Resolve a profile's symbols and remove its exclusions. An exclusion that matches
+// nothing logs a warning and is returned in `unmatched`.
+fn resolve_profile(
+    profile: &YamlProfile,
+    index: &AnalysisIndex,
+) -> Result<ResolvedProfile, UtilsError> {
+    let mut resolved = resolve_symbols_indexed(&profile.symbols, index)?;
+    if profile.exclude.is_empty() {
+        return Ok(ResolvedProfile {
+            symbols: resolved,
+            excluded: HashSet::new(),
+            unmatched: Vec::new(),
+        });
+    }
+
+    let mut excluded = HashSet::new();
+    let mut unmatched = Vec::new();
+    for symbol in &profile.exclude {
+        let matched: Vec<u64> = match symbol {
+            YamlSymbol::Name(name) => index.rva_of(name).into_iter().collect(),
+            YamlSymbol::Rva(rva) => Some(*rva)
+                .filter(|rva| index.is_valid_rva(*rva))
+                .into_iter()
+                .collect(),
+            YamlSymbol::All => index.all_rvas().collect(),
+            YamlSymbol::Section(section) => index.section_rvas(section).unwrap_or_default(),
+            YamlSymbol::RefCountAtLeast(count) => index.rvas_with_ref_count_at_least(*count),
+        };
+        if matched.is_empty() {
+            log::warn!(
+                profile = profile.name.as_str(), symbol = symbol.to_string();
+                "Exclusion `{}` in profile `{}` matches nothing",
+                symbol,
+                profile.name
+            );
+            unmatched.push(symbol.clone());
+        }
+        excluded.extend(matched);
+    }
+
+    resolved.retain(|rva| !excluded.contains(rva));
+    Ok(ResolvedProfile {
+        symbols: resolved,
+        excluded,
+        unmatched,
+    })
+}
 
 // Resolve symbol names to RVA's. If a symbol is specified via RVA
 // then validate it before including it in the result.
 pub fn resolve_symbols(
     symbols: &[YamlSymbol],
     analysis: &AnalysisResult,
-) -> Result<Vec<u64>, Box<dyn std::error::Error>> {
+) -> Result<Vec<u64>, UtilsError> {
+    resolve_symbols_indexed(symbols, &AnalysisIndex::new(analysis))
+}
+
+// Same as `resolve_symbols`, but returns the RVAs as a set for callers doing set
+// math (exclusions, overlap checks) on the result.
+pub fn resolve_symbols_set(
+    symbols: &[YamlSymbol],
+    analysis: &AnalysisResult,
+) -> Result<HashSet<u64>, UtilsError> {
+    Ok(resolve_symbols(symbols, analysis)?.into_iter().collect())
+}
+
+// Same as `resolve_symbols`, using a prebuilt index. Prefer this when resolving
+// several symbol lists against the same analysis.
+pub fn resolve_symbols_indexed(
+    symbols: &[YamlSymbol],
+    index: &AnalysisIndex,
+) -> Result<Vec<u64>, UtilsError> {
     let mut resolved = Vec::new();
 
     for symbol in symbols {
-        match symbol {
-            YamlSymbol::Name(name) => {
-                // Search in returned functions and rejects for symbol by name.
-                // If it was rejected for "ReadWriteToCode" we will force resolve it.
-                let rva = analysis
-                    .functions
-                    .iter()
-                    .find(|f| f.symbol == *name)
-                    .map(|e| e.rva)
-                    .or_else(|| {
-                        analysis
-                            .rejects
-                            .iter()
-                            .find(|r| r.symbol == *name && r.ty == "ReadWriteToCode")
-                            .map(|e| e.rva)
-                    });
-
-                match rva {
-                    Some(rva) => resolved.push(rva),
-                    None => {
-                        log::error!("Symbol `{}` not found in analysis result", name);
-                        return Err("Missing symbol".into());
+        match resolve_one_indexed(symbol, index) {
+            ResolveOutcome::Function(rva) => resolved.push(rva),
+            ResolveOutcome::Reject {
+                reject,
+                forced: true,
+            } => resolved.push(reject.rva),
+            ResolveOutcome::Functions(rvas) => {
+                if rvas.is_empty() {
+                    match symbol {
+                        YamlSymbol::Section(section) => {
+                            log::warn!(section = section.as_str(); "Section `{}` contains no analyzed functions", section)
+                        }
+                        YamlSymbol::RefCountAtLeast(count) => {
+                            log::warn!(ref_count = *count; "No analyzed function is referenced at least {} times", count)
+                        }
+                        _ => {}
                     }
                 }
+                resolved.extend(rvas);
             }
-            YamlSymbol::Rva(rva) => {
-                if !is_valid_rva(*rva, analysis) {
-                    log::error!("RVA {:X} not found in analysis", rva);
-                    return Err("Invalid RVA".into());
-                }
-                resolved.push(*rva);
+            ResolveOutcome::Reject {
+                reject,
+                forced: false,
+            } => {
+                log::error!(
+                    symbol = symbol.to_string(), reason = reject.reason.as_str();
+                    "Symbol `{}` was rejected by analysis: {}",
+                    symbol,
+                    reject.reason
+                );
+                return Err(UtilsError::RejectedSymbol {
+                    symbol: symbol.to_string(),
+                    reason: reject.reason.clone(),
+                });
+            }
+            ResolveOutcome::NotFound => {
+                log::error!(symbol = symbol.to_string(); "Symbol `{}` not found in analysis result", symbol);
+                return Err(UtilsError::MissingSymbol(symbol.to_string()));
+            }
+            ResolveOutcome::InvalidRva(rva) => {
+                log::error!(rva = rva; "RVA {:X} not found in analysis", rva);
+                return Err(UtilsError::InvalidRva(rva));
+            }
+            ResolveOutcome::MissingSectionInfo => {
+                log::error!(
+                    symbol = symbol.to_string();
+                    "Cannot target {}, analysis did not report function sections",
+                    symbol
+                );
+                return Err(UtilsError::MissingSectionInfo(symbol.to_string()));
             }
         }
     }
 
+    dedup_rvas(&mut resolved);
     Ok(resolved)
 }
 
+// What resolving a single symbol against an analysis found.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolveOutcome {
+    // The symbol matched an analyzed function at this RVA.
+    Function(u64),
+    // The symbol matched a function rejected by analysis. Rejects that can still
+    // be targeted (`AnalysisReject::is_force_resolvable`) are `forced` and resolve;
+    // others don't.
+    Reject {
+        reject: AnalysisReject,
+        forced: bool,
+    },
+    // The symbol matches a group of functions (`All`, `Section`, `RefCountAtLeast`),
+    // possibly none.
+    Functions(Vec<u64>),
+    // No function or reject has this name.
+    NotFound,
+    // No function or reject starts at this RVA.
+    InvalidRva(u64),
+    // A section was targeted, but analysis didn't report function sections.
+    MissingSectionInfo,
+}
+
+impl ResolveOutcome {
+    // RVAs the symbol resolves to, or `None` if it doesn't resolve.
+    pub fn rvas(&self) -> Option<Vec<u64>> {
+        match self {
+            ResolveOutcome::Function(rva) => Some(vec![*rva]),
+            ResolveOutcome::Reject {
+                reject,
+                forced: true,
+            } => Some(vec![reject.rva]),
+            ResolveOutcome::Functions(rvas) => Some(rvas.clone()),
+            _ => None,
+        }
+    }
+}
+
+// Resolve a single symbol and report exactly what it matched, for diagnostics.
+pub fn resolve_one(symbol: &YamlSymbol, analysis: &AnalysisResult) -> ResolveOutcome {
+    resolve_one_indexed(symbol, &AnalysisIndex::new(analysis))
+}
+
+// Same as `resolve_one`, using a prebuilt index.
+pub fn resolve_one_indexed(symbol: &YamlSymbol, index: &AnalysisIndex) -> ResolveOutcome {
+    let reject_outcome = |reject: &AnalysisReject| ResolveOutcome::Reject {
+        reject: reject.clone(),
+        forced: reject.is_force_resolvable(),
+    };
+
+    match symbol {
+        // A function takes precedence over a reject of the same name, then a
+        // force-resolvable reject over any other reject.
+        YamlSymbol::Name(name) => match index.rva_of(name) {
+            Some(rva) if index.is_function(rva) => ResolveOutcome::Function(rva),
+            Some(rva) => index
+                .reject_at(rva)
+                .map_or(ResolveOutcome::Function(rva), reject_outcome),
+            None => index
+                .reject_named(name)
+                .map_or(ResolveOutcome::NotFound, reject_outcome),
+        },
+        YamlSymbol::Rva(rva) if index.is_function(*rva) => ResolveOutcome::Function(*rva),
+        YamlSymbol::Rva(rva) => index
+            .reject_at(*rva)
+            .map_or(ResolveOutcome::InvalidRva(*rva), reject_outcome),
+        YamlSymbol::All => ResolveOutcome::Functions(index.all_rvas().collect()),
+        YamlSymbol::Section(section) => index.section_rvas(section).map_or(
+            ResolveOutcome::MissingSectionInfo,
+            ResolveOutcome::Functions,
+        ),
+        YamlSymbol::RefCountAtLeast(count) => {
+            ResolveOutcome::Functions(index.rvas_with_ref_count_at_least(*count))
+        }
+    }
+}
+
+// Map function RVAs to the sizes reported by analysis. Functions without a size
+// are left out.
+fn function_sizes(analysis: &AnalysisResult) -> HashMap<u64, u64> {
+    analysis
+        .functions
+        .iter()
+        .filter_map(|function| Some((function.rva, function.size?)))
+        .collect()
+}
+
+// Remove duplicate RVAs, keeping the first occurrence of each.
+fn dedup_rvas(rvas: &mut Vec<u64>) {
+    let mut seen = HashSet::with_capacity(rvas.len());
+    let before = rvas.len();
+    rvas.retain(|rva| seen.insert(*rva));
+    if rvas.len() != before {
+        log::debug!("Removed {} duplicate RVA(s)", before - rvas.len());
+    }
+}
+
 pub fn is_valid_rva(rva: u64, analysis: &AnalysisResult) -> bool {
     analysis.functions.iter().any(|f| f.rva == rva)
         || analysis
             .rejects
             .iter()
-            .any(|r| r.rva == rva && r.ty == "ReadWriteToCode")
+            .any(|r| r.rva == rva && r.is_force_resolvable())
 }
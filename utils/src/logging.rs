@@ -0,0 +1,55 @@
+use log::kv::{Key, Value, VisitSource};
+use std::io::Write;
+
+// Output format of log lines written to stderr.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    // Human readable `env_logger` output.
+    #[default]
+    Text,
+    // One JSON object per line with `timestamp`, `level`, `target`, `message`, and any
+    // structured fields attached to the record (`log::info!(profile = name; "...")`).
+    Json,
+}
+
+// Install the global logger, writing to stderr. Filtering is controlled by `RUST_LOG` and
+// defaults to `info`.
+pub fn init_logger(format: LogFormat) {
+    let logger = build_logger(format, env_logger::Target::Stderr);
+    log::set_max_level(logger.filter());
+    log::set_boxed_logger(Box::new(logger)).expect("Failed to install the logger");
+}
+
+// Build the logger `init_logger` installs, writing to `target` instead of stderr.
+pub fn build_logger(format: LogFormat, target: env_logger::Target) -> env_logger::Logger {
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+    builder.target(target);
+
+    if format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let mut line = serde_json::Map::new();
+            line.insert("timestamp".into(), buf.timestamp().to_string().into());
+            line.insert("level".into(), record.level().as_str().into());
+            line.insert("target".into(), record.target().into());
+            line.insert("message".into(), record.args().to_string().into());
+            let _ = record.key_values().visit(&mut FieldCollector(&mut line));
+            writeln!(buf, "{}", serde_json::Value::Object(line))
+        });
+    }
+
+    builder.build()
+}
+
+struct FieldCollector<'a>(&'a mut serde_json::Map<String, serde_json::Value>);
+
+impl<'kvs> VisitSource<'kvs> for FieldCollector<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+        let value = match value.to_u64() {
+            Some(n) => n.into(),
+            None => value.to_string().into(),
+        };
+        self.0.insert(key.as_str().to_owned(), value);
+        Ok(())
+    }
+}
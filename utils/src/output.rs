@@ -0,0 +1,50 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+use zip::result::ZipResult;
+
+// Write the downloaded bytes to `path`, or to `stdout` when `path` is `-`. Callers pass
+// `io::stdout().lock()`; logs go to stderr (see `init_logger`), so they never end up in
+// the piped output.
+pub fn write_output(path: &Path, bytes: &[u8], mut stdout: impl Write) -> io::Result<()> {
+    if path == Path::new("-") {
+        stdout.write_all(bytes)?;
+        stdout.flush()?;
+        log::info!("Wrote {} bytes to stdout", bytes.len());
+    } else {
+        std::fs::write(path, bytes)?;
+        log::info!("Wrote {} bytes to {}", bytes.len(), path.display());
+    }
+    Ok(())
+}
+
+// Extract the downloaded zip archive into `dir`, creating it if needed, and return the
+// paths of the extracted files. Entries whose path would escape `dir` (absolute paths or
+// `..` components) are skipped with a warning.
+pub fn extract_archive(bytes: &[u8], dir: &Path) -> ZipResult<Vec<PathBuf>> {
+    let mut archive = ZipArchive::new(io::Cursor::new(bytes))?;
+    let mut extracted = Vec::with_capacity(archive.len());
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let Some(relative_path) = entry.enclosed_name() else {
+            log::warn!("Skipping archive entry with unsafe path `{}`", entry.name());
+            continue;
+        };
+        let path = dir.join(relative_path);
+
+        if entry.is_dir() {
+            std::fs::create_dir_all(&path)?;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut entry, &mut std::fs::File::create(&path)?)?;
+
+        log::info!("Extracted {}", path.display());
+        extracted.push(path);
+    }
+
+    Ok(extracted)
+}
@@ -0,0 +1,46 @@
+use codedefender_config::CDConfig;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+// Summary of a profile that was submitted for obfuscation.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileReport {
+    pub name: String,
+    pub pass_count: usize,
+    pub symbol_count: usize,
+}
+
+// Machine-readable summary of a successful obfuscation run.
+#[derive(Debug, Clone, Serialize)]
+pub struct ObfuscationReport {
+    pub profiles: Vec<ProfileReport>,
+    // Size of the downloaded archive in bytes.
+    pub output_size: usize,
+    // Wall-clock time from start to download, in seconds.
+    pub elapsed_secs: f64,
+}
+
+impl ObfuscationReport {
+    pub fn new(config: &CDConfig, output_size: usize, elapsed: Duration) -> Self {
+        Self {
+            profiles: config
+                .profiles
+                .iter()
+                .map(|p| ProfileReport {
+                    name: p.name.clone(),
+                    pass_count: p.passes.len(),
+                    symbol_count: p.symbols.len(),
+                })
+                .collect(),
+            output_size,
+            elapsed_secs: elapsed.as_secs_f64(),
+        }
+    }
+
+    // Write the report as pretty-printed JSON.
+    pub fn write_json(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+    }
+}
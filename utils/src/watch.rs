@@ -0,0 +1,189 @@
+use crate::UtilsError;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+// Call `run` every time one of `paths` changes, until it returns `false`. Changes
+// are debounced: `run` is called once no further change happened for `debounce`,
+// with every path that changed in the meantime. Parent directories are watched
+// rather than the files, so editors that save by replacing the file still
+// trigger a run. Pair with an `AnalysisCache` so an unchanged binary isn't
+// analyzed again when only the config changed.
+pub fn watch_files(
+    paths: &[PathBuf],
+    debounce: Duration,
+    run: impl FnMut(&[PathBuf]) -> bool,
+) -> Result<(), UtilsError> {
+    let watched: Vec<PathBuf> = paths.iter().map(|p| absolute(p)).collect();
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+    let dirs: BTreeSet<&Path> = watched.iter().filter_map(|p| p.parent()).collect();
+    for dir in dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(watch_error)?;
+    }
+
+    run_on_changes(&rx, &watched, debounce, run)
+}
+
+// The debounce loop of `watch_files`, reading events from `rx`. Returns when `run`
+// returns `false` or the sender is gone.
+fn run_on_changes(
+    rx: &Receiver<notify::Result<Event>>,
+    watched: &[PathBuf],
+    debounce: Duration,
+    mut run: impl FnMut(&[PathBuf]) -> bool,
+) -> Result<(), UtilsError> {
+    let mut changed = BTreeSet::new();
+    loop {
+        let event = if changed.is_empty() {
+            match rx.recv() {
+                Ok(event) => event,
+                Err(_) => return Ok(()),
+            }
+        } else {
+            match rx.recv_timeout(debounce) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => {
+                    let batch: Vec<PathBuf> = std::mem::take(&mut changed).into_iter().collect();
+                    log::info!("Change detected in {}, re-running", display_paths(&batch));
+                    if !run(&batch) {
+                        return Ok(());
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        };
+
+        let event = event.map_err(watch_error)?;
+        if matches!(event.kind, EventKind::Access(_)) {
+            continue;
+        }
+        changed.extend(event.paths.into_iter().filter(|p| watched.contains(p)));
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn display_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn watch_error(e: notify::Error) -> UtilsError {
+    log::error!("Failed to watch files: {}", e);
+    UtilsError::Watch(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, DataChange, ModifyKind};
+    use std::sync::mpsc::Sender;
+
+    const DEBOUNCE: Duration = Duration::from_millis(20);
+
+    fn config() -> PathBuf {
+        PathBuf::from("/work/config.yaml")
+    }
+
+    fn binary() -> PathBuf {
+        PathBuf::from("/work/app.exe")
+    }
+
+    // Simulates `notify` reporting a change to `path`.
+    fn send_change(tx: &Sender<notify::Result<Event>>, path: PathBuf) {
+        let kind = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+        tx.send(Ok(Event::new(kind).add_path(path))).unwrap();
+    }
+
+    #[test]
+    fn change_to_a_watched_file_triggers_a_run() {
+        let (tx, rx) = mpsc::channel();
+        send_change(&tx, config());
+        send_change(&tx, PathBuf::from("/work/notes.txt"));
+
+        let mut runs = Vec::new();
+        run_on_changes(&rx, &[config(), binary()], DEBOUNCE, |changed| {
+            runs.push(changed.to_vec());
+            false
+        })
+        .unwrap();
+
+        assert_eq!(runs, [vec![config()]]);
+    }
+
+    #[test]
+    fn rapid_changes_are_debounced_into_one_run() {
+        let (tx, rx) = mpsc::channel();
+        send_change(&tx, config());
+        send_change(&tx, config());
+        send_change(&tx, binary());
+
+        let mut runs = Vec::new();
+        run_on_changes(&rx, &[config(), binary()], DEBOUNCE, |changed| {
+            runs.push(changed.to_vec());
+            false
+        })
+        .unwrap();
+
+        assert_eq!(runs, [vec![binary(), config()]]);
+    }
+
+    #[test]
+    fn later_changes_trigger_another_run() {
+        let (tx, rx) = mpsc::channel();
+        send_change(&tx, config());
+
+        let mut runs = Vec::new();
+        run_on_changes(&rx, &[config(), binary()], DEBOUNCE, |changed| {
+            runs.push(changed.to_vec());
+            // The binary is rebuilt after the first run.
+            if runs.len() == 1 {
+                send_change(&tx, binary());
+            }
+            runs.len() < 2
+        })
+        .unwrap();
+
+        assert_eq!(runs, [vec![config()], vec![binary()]]);
+    }
+
+    #[test]
+    fn reads_and_unwatched_files_do_not_trigger_a_run() {
+        let (tx, rx) = mpsc::channel();
+        let read = EventKind::Access(AccessKind::Read);
+        tx.send(Ok(Event::new(read).add_path(config()))).unwrap();
+        send_change(&tx, PathBuf::from("/work/notes.txt"));
+        drop(tx);
+
+        let mut runs = 0;
+        run_on_changes(&rx, &[config()], DEBOUNCE, |_| {
+            runs += 1;
+            true
+        })
+        .unwrap();
+
+        assert_eq!(runs, 0);
+    }
+
+    #[test]
+    fn watcher_errors_stop_watching() {
+        let (tx, rx) = mpsc::channel();
+        tx.send(Err(notify::Error::generic("inotify limit reached")))
+            .unwrap();
+
+        let result = run_on_changes(&rx, &[config()], DEBOUNCE, |_| true);
+
+        assert!(matches!(result, Err(UtilsError::Watch(_))));
+    }
+}
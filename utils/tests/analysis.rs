@@ -0,0 +1,164 @@
+use codedefender_config::{AnalysisResult, CDCompilerSettings, CDProfile};
+use codedefender_utils::{
+    LogFormat, UtilsError, analysis_to_csv, build_logger, check_analysis, log_rejects,
+    protected_functions,
+};
+use serde_json::json;
+use std::io::Write;
+use std::sync::{Arc, Mutex, OnceLock};
+
+// A writer whose contents the test can read back.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Captured {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// The global logger can only be installed once per test binary.
+fn logs() -> &'static Captured {
+    static LOGS: OnceLock<Captured> = OnceLock::new();
+    LOGS.get_or_init(|| {
+        let logs = Captured::default();
+        let logger = build_logger(
+            LogFormat::Text,
+            env_logger::Target::Pipe(Box::new(logs.clone())),
+        );
+        log::set_max_level(log::LevelFilter::Info);
+        log::set_boxed_logger(Box::new(logger)).unwrap();
+        logs
+    })
+}
+
+// `main` and `helper` are functions; `patcher`, `tiny` and `leaf` are rejected.
+fn analysis() -> AnalysisResult {
+    serde_json::from_value(json!({
+        "environment": "UserMode",
+        "functions": [
+            { "rva": 0x1000, "symbol": "main", "ref_count": 3, "section": ".text" },
+            { "rva": 0x2000, "symbol": "helper", "ref_count": 1 }
+        ],
+        "rejects": [
+            { "rva": 0x3000, "symbol": "tiny", "ty": "TooSmall", "reason": "function is too small" },
+            { "rva": 0x4000, "symbol": "patcher", "ty": "ReadWriteToCode", "reason": "writes its own code" },
+            { "rva": 0x5000, "symbol": "leaf", "ty": "TooSmall", "reason": "function is too small" }
+        ],
+        "macros": []
+    }))
+    .unwrap()
+}
+
+#[test]
+fn rejects_are_logged_by_type_with_their_reasons() {
+    let logs = logs();
+
+    log_rejects(&analysis());
+
+    let output = logs.contents();
+    let position = |text: &str| {
+        output
+            .find(text)
+            .unwrap_or_else(|| panic!("`{text}` missing from:\n{output}"))
+    };
+    let read_write = position("1 function(s) rejected for ReadWriteToCode");
+    let patcher = position("4000 patcher: writes its own code");
+    let too_small = position("2 function(s) rejected for TooSmall");
+    let tiny = position("3000 tiny: function is too small");
+    let leaf = position("5000 leaf: function is too small");
+    assert!(read_write < patcher && patcher < too_small, "{output}");
+    assert!(too_small < tiny && tiny < leaf, "{output}");
+    assert!(output.contains("hint: the function reads or writes its own code"));
+    assert!(output.contains("hint: the function is too small to protect"));
+}
+
+#[test]
+fn csv_lists_functions_then_rejects() {
+    let mut analysis = analysis();
+    analysis.functions[1].symbol = "operator,()".to_owned();
+    let mut csv = Vec::new();
+
+    analysis_to_csv(&analysis, &mut csv).unwrap();
+
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "rva,symbol,ref_count,section,kind,reject_type,reject_reason",
+            "0x1000,main,3,.text,function,,",
+            "0x2000,\"operator,()\",1,,function,,",
+            "0x3000,tiny,,,reject,TooSmall,function is too small",
+            "0x4000,patcher,,,reject,ReadWriteToCode,writes its own code",
+            "0x5000,leaf,,,reject,TooSmall,function is too small",
+        ]
+    );
+}
+
+#[test]
+fn protected_functions_follow_the_profile_symbols() {
+    let analysis = analysis();
+    // `patcher` (0x4000) is a force-resolved reject, not an analyzed function.
+    let profile = CDProfile {
+        name: "app".to_owned(),
+        passes: vec![],
+        compiler_settings: CDCompilerSettings::default(),
+        symbols: vec![0x1000, 0x2000, 0x4000],
+    };
+
+    let functions = protected_functions(&profile, &analysis);
+
+    let protected: Vec<(u64, &str)> = functions
+        .iter()
+        .map(|f| (f.rva, f.symbol.as_str()))
+        .collect();
+    assert_eq!(protected, [(0x1000, "main"), (0x2000, "helper")]);
+    assert!(std::ptr::eq(functions[0], &analysis.functions[0]));
+
+    let profile = CDProfile {
+        symbols: vec![0x2000],
+        ..profile
+    };
+    let protected: Vec<u64> = protected_functions(&profile, &analysis)
+        .iter()
+        .map(|f| f.rva)
+        .collect();
+    assert_eq!(protected, [0x2000]);
+}
+
+#[test]
+fn empty_analysis_is_a_specific_error() {
+    let logs = logs();
+    let mut analysis = analysis();
+    analysis.functions.clear();
+    analysis.rejects.clear();
+
+    let error = check_analysis(&analysis).unwrap_err();
+
+    assert_eq!(error, UtilsError::EmptyAnalysis);
+    assert!(error.to_string().contains("PDB may be missing"), "{error}");
+    assert!(
+        logs.contents().contains("Analysis found no functions"),
+        "{}",
+        logs.contents()
+    );
+}
+
+#[test]
+fn analysis_with_only_rejects_is_not_empty() {
+    let mut analysis = analysis();
+    analysis.functions.clear();
+
+    assert_eq!(check_analysis(&analysis), Ok(()));
+}
@@ -0,0 +1,263 @@
+#[path = "../../api/tests/common/mod.rs"]
+mod common;
+
+use codedefender_api::{ApiClient, IDEMPOTENCY_KEY_HEADER, PollStrategy};
+use codedefender_config::YamlConfig;
+use codedefender_utils::{DefendJob, JobError, UtilsError, defend_many};
+use common::{API_KEY, MockResponse, MockServer, Recorded};
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+const FILE_ID: &str = "11111111-2222-3333-4444-555555555555";
+const EXECUTION_ID: &str = "66666666-7777-8888-9999-aaaaaaaaaaaa";
+
+// Smallest input `check_input` accepts: the MZ magic and a PE signature at `e_lfanew`.
+fn pe_image() -> Vec<u8> {
+    let mut bytes = vec![0u8; 0x44];
+    bytes[..2].copy_from_slice(b"MZ");
+    bytes[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+    bytes[0x40..].copy_from_slice(b"PE\0\0");
+    bytes
+}
+
+fn job(name: &str) -> DefendJob {
+    let config = YamlConfig::from_yaml_str(
+        r#"
+version: "1.1.0"
+module_settings:
+  ida_crasher: false
+  import_protection: false
+  obscure_entry_point: false
+  clear_unwind_info: false
+  fake_pdb_string:
+    enabled: false
+    value: ""
+  custom_section_name:
+    enabled: false
+    value: ""
+profiles:
+  - name: main
+    compiler_preset: fast
+    passes:
+      - type: AntiEmulator
+    symbols:
+      - !Name main
+"#,
+    )
+    .unwrap();
+    DefendJob {
+        name: name.to_owned(),
+        binary: pe_image(),
+        pdb: None,
+        config,
+    }
+}
+
+// Answers every endpoint of a successful job.
+fn respond(request: &Recorded) -> MockResponse {
+    match request.path.as_str() {
+        "/api/upload" => MockResponse::new(200, FILE_ID),
+        "/api/verify" => MockResponse::json(200, &json!({ "valid": true })),
+        "/api/analyze" => MockResponse::json(
+            200,
+            &json!({
+                "environment": "UserMode",
+                "functions": [{ "rva": 4096, "symbol": "main", "ref_count": 1 }],
+                "rejects": [],
+                "macros": []
+            }),
+        ),
+        "/api/defend" => MockResponse::new(200, EXECUTION_ID),
+        "/api/download" => MockResponse::new(200, b"PK\x03\x04".to_vec()),
+        _ => MockResponse::new(404, "not found"),
+    }
+}
+
+fn fast_poll() -> PollStrategy {
+    PollStrategy::fixed(Duration::from_millis(10), Duration::from_secs(5))
+}
+
+#[test]
+fn every_job_is_submitted_with_its_own_idempotency_key() {
+    let server = MockServer::start(respond);
+
+    let results = defend_many(
+        vec![job("a.exe"), job("b.exe")],
+        2,
+        &fast_poll(),
+        &server.client(),
+        API_KEY,
+    );
+
+    assert!(results.iter().all(Result::is_ok), "{results:?}");
+    let keys: Vec<String> = server
+        .requests_to("/api/defend")
+        .iter()
+        .map(|request| request.header(IDEMPOTENCY_KEY_HEADER).unwrap().to_owned())
+        .collect();
+    assert_eq!(keys.len(), 2);
+    assert_ne!(keys[0], keys[1]);
+}
+
+#[test]
+fn timed_out_submission_is_retried_with_the_same_key() {
+    let defends = AtomicUsize::new(0);
+    let server = MockServer::start(move |request| {
+        let response = respond(request);
+        if request.path == "/api/defend" && defends.fetch_add(1, Ordering::SeqCst) == 0 {
+            response.with_delay(Duration::from_secs(2))
+        } else {
+            response
+        }
+    });
+    let http = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_millis(500))
+        .build()
+        .unwrap();
+    let client = ApiClient::new(http, server.url());
+
+    let results = defend_many(vec![job("a.exe")], 1, &fast_poll(), &client, API_KEY);
+
+    assert!(results[0].is_ok(), "{results:?}");
+    let submissions = server.requests_to("/api/defend");
+    assert_eq!(submissions.len(), 2);
+    assert_eq!(
+        submissions[0].header(IDEMPOTENCY_KEY_HEADER),
+        submissions[1].header(IDEMPOTENCY_KEY_HEADER)
+    );
+}
+
+#[test]
+fn non_pe_input_fails_before_uploading() {
+    let server = MockServer::start(respond);
+    let mut bad = job("bad.exe");
+    bad.binary = b"not a PE".to_vec();
+
+    let results = defend_many(vec![bad], 1, &fast_poll(), &server.client(), API_KEY);
+
+    assert!(matches!(results[0], Err(JobError::Build(_))));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn verified_upload_is_analyzed() {
+    let server = MockServer::start(respond);
+
+    let results = defend_many(
+        vec![job("a.exe")],
+        1,
+        &fast_poll(),
+        &server.client(),
+        API_KEY,
+    );
+
+    assert!(results[0].is_ok(), "{results:?}");
+    let verify = &server.requests_to("/api/verify")[0];
+    assert_eq!(verify.query("fileId"), Some(FILE_ID));
+    assert_eq!(server.requests_to("/api/analyze").len(), 1);
+}
+
+#[test]
+fn rejected_upload_fails_before_analysis() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/verify" => MockResponse::json(
+            200,
+            &json!({ "valid": false, "reason": "not a 64-bit image" }),
+        ),
+        _ => respond(request),
+    });
+
+    let results = defend_many(
+        vec![job("a.exe")],
+        1,
+        &fast_poll(),
+        &server.client(),
+        API_KEY,
+    );
+
+    match &results[0] {
+        Err(JobError::Build(UtilsError::UploadRejected(reason))) => {
+            assert_eq!(reason, "not a 64-bit image")
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert!(server.requests_to("/api/analyze").is_empty());
+}
+
+#[test]
+fn missing_verify_endpoint_is_skipped() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/verify" => MockResponse::new(404, "not found"),
+        _ => respond(request),
+    });
+
+    let results = defend_many(
+        vec![job("a.exe")],
+        1,
+        &fast_poll(),
+        &server.client(),
+        API_KEY,
+    );
+
+    assert!(results[0].is_ok(), "{results:?}");
+    assert_eq!(server.requests_to("/api/analyze").len(), 1);
+}
+
+#[test]
+fn failing_verify_endpoint_fails_the_job() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/verify" => MockResponse::new(500, "boom"),
+        _ => respond(request),
+    });
+
+    let results = defend_many(
+        vec![job("a.exe")],
+        1,
+        &fast_poll(),
+        &server.client(),
+        API_KEY,
+    );
+
+    assert!(matches!(results[0], Err(JobError::Api(_))), "{results:?}");
+}
+
+#[test]
+fn batch_respects_the_concurrency_cap_and_keeps_going_after_failures() {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let server = {
+        let in_flight = Arc::clone(&in_flight);
+        let max_in_flight = Arc::clone(&max_in_flight);
+        MockServer::start(move |request| {
+            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            max_in_flight.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            // The upload of the job whose binary ends with `X` is rejected.
+            if request.path == "/api/upload" && request.body.ends_with(b"X") {
+                MockResponse::new(500, "upload failed")
+            } else {
+                respond(request)
+            }
+        })
+    };
+    let mut jobs: Vec<DefendJob> = (0..5).map(|i| job(&format!("{i}.dll"))).collect();
+    jobs[1].binary.push(b'X');
+    jobs[3].binary = Vec::new();
+
+    let results = defend_many(jobs, 2, &fast_poll(), &server.client(), API_KEY);
+
+    assert_eq!(results.len(), 5);
+    assert!(results[0].is_ok(), "{results:?}");
+    assert!(matches!(results[1], Err(JobError::Api(_))), "{results:?}");
+    assert!(results[2].is_ok(), "{results:?}");
+    assert!(
+        matches!(results[3], Err(JobError::Build(UtilsError::EmptyInput))),
+        "{results:?}"
+    );
+    assert!(results[4].is_ok(), "{results:?}");
+    assert_eq!(server.requests_to("/api/defend").len(), 3);
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+}
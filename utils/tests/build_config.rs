@@ -0,0 +1,836 @@
+use codedefender_config::{
+    AnalysisResult, CDConfig, LimitError, Limits, ObfuscationPass, YamlConfig, YamlSymbol,
+    cdconfig_to_yaml, validate_config,
+};
+use codedefender_utils::{
+    BuildOptions, UtilsError, build_config, build_config_with, filter_profiles,
+};
+use serde_json::json;
+use std::collections::HashSet;
+
+const MODULE_SETTINGS: &str = r#"
+version: "1.1.0"
+module_settings:
+  ida_crasher: false
+  import_protection: false
+  obscure_entry_point: false
+  clear_unwind_info: false
+  fake_pdb_string:
+    enabled: false
+    value: ""
+  custom_section_name:
+    enabled: false
+    value: ""
+default_compiler_settings:
+  assembler_settings:
+    shuffle_basic_blocks: false
+    instruction_prefix: ""
+    random_prefix_chance: 0.0
+  optimization_settings:
+    constant_propagation: true
+    instruction_combine: true
+    dead_code_elim: true
+    prune_useless_block_params: true
+    iterations: 1
+  lifter_settings:
+    lift_calls: true
+    calling_convention: WindowsAbi
+    max_stack_copy_size: 1024
+    split_on_calls_fallback: true
+"#;
+
+// A config with the usual module settings and the given `profiles:` section.
+fn config(profiles: &str) -> YamlConfig {
+    YamlConfig::from_yaml_str(&format!("{}{}", MODULE_SETTINGS, profiles)).unwrap()
+}
+
+// An analysis reporting `main` at 0x1000 (400 bytes), `helper` at 0x2000 (12 bytes) and
+// `stub` at 0x3000 (4 bytes).
+fn analysis() -> AnalysisResult {
+    serde_json::from_value(json!({
+        "environment": "UserMode",
+        "functions": [
+            { "rva": 0x1000, "symbol": "main", "ref_count": 3, "size": 400 },
+            { "rva": 0x2000, "symbol": "helper", "ref_count": 2, "size": 12 },
+            { "rva": 0x3000, "symbol": "stub", "ref_count": 1, "size": 4 }
+        ],
+        "rejects": [],
+        "macros": []
+    }))
+    .unwrap()
+}
+
+fn profile_names(config: &CDConfig) -> Vec<&str> {
+    config.profiles.iter().map(|p| p.name.as_str()).collect()
+}
+
+#[test]
+fn pass_scoped_to_a_subset_only_applies_to_it() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+        targets: [!Name main]
+      - type: IDADecompilerCrasher
+    symbols: [!Name main, !Name helper]
+"#,
+    );
+
+    let built = build_config(&config, &analysis()).unwrap();
+
+    assert_eq!(profile_names(&built), ["app", "app_split_2"]);
+    assert_eq!(built.profiles[0].symbols, [0x1000]);
+    assert_eq!(
+        built.profiles[0].passes,
+        [
+            ObfuscationPass::AntiEmulator,
+            ObfuscationPass::IDADecompilerCrasher
+        ]
+    );
+    assert_eq!(built.profiles[1].symbols, [0x2000]);
+    assert_eq!(
+        built.profiles[1].passes,
+        [ObfuscationPass::IDADecompilerCrasher]
+    );
+}
+
+#[test]
+fn targets_outside_the_profile_are_rejected() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+        targets: [!Name stub]
+    symbols: [!Name main, !Name helper]
+"#,
+    );
+
+    match build_config(&config, &analysis()) {
+        Err(UtilsError::TargetsOutsideProfile {
+            profile,
+            pass,
+            rvas,
+        }) => {
+            assert_eq!(profile, "app");
+            assert_eq!(pass, "AntiEmulator");
+            assert_eq!(rvas, [0x3000]);
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn targets_are_compared_after_resolution() {
+    // `main` is listed by RVA in the profile and by name in the targets.
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+        targets: [!Name main]
+    symbols: [!Rva 0x1000, !Name helper]
+"#,
+    );
+
+    assert!(!validate_config(&config).has_errors());
+    let built = build_config(&config, &analysis()).unwrap();
+    assert_eq!(built.profiles[0].symbols, [0x1000]);
+}
+
+#[test]
+fn split_names_skip_names_of_existing_profiles() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+        targets: [!Name main]
+      - type: IDADecompilerCrasher
+    symbols: [!Name main, !Name helper]
+  - name: app_split_2
+    passes:
+      - type: AntiEmulator
+    symbols: [!Name stub]
+"#,
+    );
+
+    let built = build_config(&config, &analysis()).unwrap();
+
+    assert_eq!(profile_names(&built), ["app", "app_split_3", "app_split_2"]);
+    assert_eq!(built.profiles[2].symbols, [0x3000]);
+}
+
+#[test]
+fn auto_split_names_stay_unique_after_target_splits() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+        targets: [!Name main]
+      - type: IDADecompilerCrasher
+    symbols: [All]
+"#,
+    );
+    let options = BuildOptions {
+        auto_split: true,
+        limits: Limits {
+            max_symbols_per_profile: 1,
+            ..Limits::default()
+        },
+        ..BuildOptions::default()
+    };
+
+    let built = build_config_with(&config, &analysis(), &options).unwrap();
+
+    let names = profile_names(&built);
+    assert_eq!(names.len(), 3);
+    assert_eq!(names.iter().collect::<HashSet<_>>().len(), names.len());
+    assert_eq!(names, ["app", "app_split_2", "app_split_2_2"]);
+}
+
+#[test]
+fn duplicate_profile_names_are_rejected() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+    symbols: [!Name main]
+  - name: app
+    passes:
+      - type: AntiEmulator
+    symbols: [!Name helper]
+"#,
+    );
+
+    assert!(matches!(
+        build_config(&config, &analysis()),
+        Err(UtilsError::DuplicateProfileName(name)) if name == "app"
+    ));
+}
+
+#[test]
+fn small_functions_are_excluded_from_the_gated_pass() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+        min_function_size: 100
+      - type: IDADecompilerCrasher
+    symbols: [All]
+"#,
+    );
+
+    let built = build_config(&config, &analysis()).unwrap();
+
+    // `main` (400 bytes) keeps both passes, `helper` and `stub` only the ungated one.
+    assert_eq!(profile_names(&built), ["app", "app_split_2"]);
+    assert_eq!(built.profiles[0].symbols, [0x1000]);
+    assert_eq!(
+        built.profiles[0].passes,
+        [
+            ObfuscationPass::AntiEmulator,
+            ObfuscationPass::IDADecompilerCrasher
+        ]
+    );
+    assert_eq!(built.profiles[1].symbols, [0x2000, 0x3000]);
+    assert_eq!(
+        built.profiles[1].passes,
+        [ObfuscationPass::IDADecompilerCrasher]
+    );
+}
+
+#[test]
+fn size_threshold_combines_with_targets() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+        targets: [!Name main, !Name helper]
+        min_function_size: 10
+    symbols: [All]
+"#,
+    );
+
+    let built = build_config(&config, &analysis()).unwrap();
+
+    // `stub` is not targeted, and no pass is left for it.
+    assert_eq!(built.profiles.len(), 1);
+    assert_eq!(built.profiles[0].symbols, [0x1000, 0x2000]);
+}
+
+#[test]
+fn size_threshold_is_a_no_op_without_sizes() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+        min_function_size: 100
+    symbols: [All]
+"#,
+    );
+    let mut analysis = analysis();
+    for function in &mut analysis.functions {
+        function.size = None;
+    }
+
+    let built = build_config(&config, &analysis).unwrap();
+
+    assert_eq!(built.profiles.len(), 1);
+    assert_eq!(built.profiles[0].symbols, [0x1000, 0x2000, 0x3000]);
+}
+
+#[test]
+fn yaml_to_cdconfig_to_yaml_keeps_names_and_passes() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    compiler_preset: max
+    passes:
+      - type: AntiEmulator
+      - type: ObscureControlFlow
+        probability: 40
+    symbols: [!Name main, !Name helper]
+  - name: tiny
+    passes:
+      - type: IDADecompilerCrasher
+    symbols: [!Name stub]
+"#,
+    );
+    let built = build_config(&config, &analysis()).unwrap();
+
+    let yaml = cdconfig_to_yaml(&built, &analysis());
+    let reloaded = YamlConfig::from_yaml_str(&yaml.to_yaml_string().unwrap()).unwrap();
+
+    assert_eq!(reloaded.version, config.version);
+    assert_eq!(reloaded.module_settings, config.module_settings);
+    for (profile, original) in reloaded.profiles.iter().zip(&config.profiles) {
+        assert_eq!(profile.name, original.name);
+        assert_eq!(profile.passes, original.passes);
+        assert_eq!(profile.symbols, original.symbols);
+    }
+    assert_eq!(build_config(&reloaded, &analysis()).unwrap(), built);
+}
+
+#[test]
+fn rvas_without_a_unique_name_stay_rvas() {
+    let mut analysis = analysis();
+    analysis.functions[2].symbol = "main".to_owned();
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+    symbols: [!Rva 0x1000, !Rva 0x3000]
+"#,
+    );
+    let mut built = build_config(&config, &analysis).unwrap();
+    // An RVA analysis does not report, e.g. from a config built against older analysis.
+    built.profiles[0].symbols.push(0x4000);
+
+    let yaml = cdconfig_to_yaml(&built, &analysis);
+
+    assert_eq!(
+        yaml.profiles[0].symbols,
+        [
+            YamlSymbol::Name("main".to_owned()),
+            YamlSymbol::Rva(0x3000),
+            YamlSymbol::Rva(0x4000),
+        ]
+    );
+}
+
+#[test]
+fn auto_split_makes_oversized_profiles_fit_the_limits() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+    symbols: [All]
+"#,
+    );
+    let limits = Limits {
+        max_symbols_per_profile: 2,
+        ..Limits::default()
+    };
+
+    let unsplit = build_config(&config, &analysis()).unwrap();
+    assert!(matches!(
+        unsplit.check_limits(&limits),
+        Err(LimitError::TooManyProfileSymbols { actual: 3, .. })
+    ));
+
+    let options = BuildOptions {
+        auto_split: true,
+        limits,
+        ..BuildOptions::default()
+    };
+    let built = build_config_with(&config, &analysis(), &options).unwrap();
+
+    assert_eq!(built.check_limits(&limits), Ok(()));
+    assert_eq!(profile_names(&built), ["app", "app_2"]);
+    assert_eq!(built.profiles[0].symbols, [0x1000, 0x2000]);
+    assert_eq!(built.profiles[1].symbols, [0x3000]);
+    assert_eq!(built.profiles[1].passes, [ObfuscationPass::AntiEmulator]);
+}
+
+#[test]
+fn auto_split_leaves_profiles_within_the_limit_alone() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+    symbols: [All]
+"#,
+    );
+    let options = BuildOptions {
+        auto_split: true,
+        ..BuildOptions::default()
+    };
+
+    let built = build_config_with(&config, &analysis(), &options).unwrap();
+
+    assert_eq!(built, build_config(&config, &analysis()).unwrap());
+}
+
+#[test]
+fn profile_without_settings_or_default_is_an_error() {
+    let mut config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+    symbols: [!Name main]
+"#,
+    );
+    config.default_compiler_settings = None;
+
+    match build_config(&config, &analysis()) {
+        Err(UtilsError::MissingCompilerSettings(profile)) => assert_eq!(profile, "app"),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn profile_without_settings_inherits_the_default() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+    symbols: [!Name main]
+"#,
+    );
+
+    let built = build_config(&config, &analysis()).unwrap();
+
+    assert_eq!(
+        Some(&built.profiles[0].compiler_settings),
+        config.default_compiler_settings.as_ref()
+    );
+}
+
+#[test]
+fn excluded_symbols_are_removed_from_all() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+    symbols: [All]
+    exclude: [!Name helper, !Rva 0x3000]
+"#,
+    );
+
+    let built = build_config(&config, &analysis()).unwrap();
+
+    assert_eq!(built.profiles[0].symbols, [0x1000]);
+}
+
+#[test]
+fn exclusion_matching_nothing_is_not_an_error() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+    symbols: [All]
+    exclude: [!Name missing]
+"#,
+    );
+
+    let built = build_config(&config, &analysis()).unwrap();
+
+    assert_eq!(built.profiles[0].symbols, [0x1000, 0x2000, 0x3000]);
+}
+
+// The analysis with a macro adding `helper` back to the `app` profile.
+fn analysis_with_macro() -> AnalysisResult {
+    let mut analysis = analysis();
+    analysis.macros = serde_json::from_value(json!([{ "name": "app", "rvas": [0x2000] }])).unwrap();
+    analysis
+}
+
+const EXCLUDES_HELPER: &str = r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+    symbols: [!Name main]
+    exclude: [!Name helper]
+"#;
+
+#[test]
+fn excluded_symbols_are_removed_from_macro_expansions() {
+    let built = build_config(&config(EXCLUDES_HELPER), &analysis_with_macro()).unwrap();
+
+    assert_eq!(built.profiles[0].symbols, [0x1000]);
+}
+
+#[test]
+fn strict_exclusions_reject_excluded_macro_symbols() {
+    let options = BuildOptions {
+        strict_exclusions: true,
+        ..BuildOptions::default()
+    };
+
+    let error =
+        build_config_with(&config(EXCLUDES_HELPER), &analysis_with_macro(), &options).unwrap_err();
+
+    assert!(
+        matches!(
+            &error,
+            UtilsError::ExcludedMacroSymbol { profile, rva: 0x2000 } if profile == "app"
+        ),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn strict_exclusions_reject_exclusions_matching_nothing() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+    symbols: [All]
+    exclude: [!Name missing]
+"#,
+    );
+    let options = BuildOptions {
+        strict_exclusions: true,
+        ..BuildOptions::default()
+    };
+
+    let error = build_config_with(&config, &analysis(), &options).unwrap_err();
+
+    match error {
+        UtilsError::UnmatchedExclusion { profile, symbol } => {
+            assert_eq!(profile, "app");
+            assert!(symbol.contains("missing"), "{symbol}");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn macro_rvas_already_in_the_profile_are_not_duplicated() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: AntiEmulator
+    symbols: [!Name main, !Rva 0x1000, !Name helper]
+"#,
+    );
+    let mut analysis = analysis();
+    analysis.macros =
+        serde_json::from_value(json!([{ "name": "app", "rvas": [0x2000, 0x3000, 0x3000] }]))
+            .unwrap();
+
+    let built = build_config(&config, &analysis).unwrap();
+
+    assert_eq!(built.profiles[0].symbols, [0x1000, 0x2000, 0x3000]);
+}
+
+#[test]
+fn resolved_symbols_are_sorted_regardless_of_input_order() {
+    let config = config(
+        r#"
+profiles:
+  - name: named
+    passes: []
+    symbols: [!Name stub, !Rva 0x1000, !Name helper]
+  - name: everything
+    passes: []
+    symbols: [All]
+"#,
+    );
+    let mut analysis = analysis();
+    analysis.functions.reverse();
+
+    let built = build_config(&config, &analysis).unwrap();
+
+    assert_eq!(built.profiles[0].symbols, [0x1000, 0x2000, 0x3000]);
+    // `named` is applied first and claims every symbol `everything` also has.
+    assert_eq!(profile_names(&built), ["named", "everything"]);
+    assert!(built.profiles[1].symbols.is_empty());
+    assert_eq!(build_config(&config, &analysis).unwrap(), built);
+
+    let mut config = config;
+    config.profiles.swap(0, 1);
+    let built = build_config(&config, &analysis).unwrap();
+    assert_eq!(built.profiles[0].symbols, [0x1000, 0x2000, 0x3000]);
+}
+
+#[test]
+fn empty_analysis_fails_before_resolving_symbols() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes: []
+    symbols: [!Name main]
+"#,
+    );
+    let mut analysis = analysis();
+    analysis.functions.clear();
+
+    assert_eq!(
+        build_config(&config, &analysis),
+        Err(UtilsError::EmptyAnalysis)
+    );
+}
+
+#[test]
+fn profiles_are_ordered_by_priority_and_ties_keep_declaration_order() {
+    let config = config(
+        r#"
+profiles:
+  - name: first
+    passes: []
+    symbols: [!Name main]
+  - name: late
+    priority: 5
+    passes: []
+    symbols: [!Name helper]
+  - name: early
+    priority: -1
+    passes: []
+    symbols: [!Name stub]
+  - name: second
+    priority: 0
+    passes: []
+    symbols: [!Rva 0x3000]
+"#,
+    );
+
+    let built = build_config(&config, &analysis()).unwrap();
+
+    assert_eq!(profile_names(&built), ["early", "first", "second", "late"]);
+}
+
+#[test]
+fn higher_priority_profile_claims_a_shared_symbol() {
+    let config = config(
+        r#"
+profiles:
+  - name: broad
+    passes:
+      - type: AntiEmulator
+    symbols: [All]
+  - name: hot
+    priority: -1
+    passes:
+      - type: IDADecompilerCrasher
+    symbols: [!Name helper]
+"#,
+    );
+
+    let built = build_config(&config, &analysis()).unwrap();
+
+    assert_eq!(profile_names(&built), ["hot", "broad"]);
+    assert_eq!(built.profiles[0].symbols, [0x2000]);
+    assert_eq!(built.profiles[1].symbols, [0x1000, 0x3000]);
+}
+
+const THREE_PROFILES: &str = r#"
+profiles:
+  - name: first
+    passes: []
+    symbols: [!Name main]
+  - name: second
+    passes: []
+    symbols: [!Name helper]
+  - name: third
+    passes: []
+    symbols: [!Name stub]
+"#;
+
+#[test]
+fn filtered_config_only_builds_the_named_profiles() {
+    let names = ["third".to_owned(), "first".to_owned()];
+    let mut analysis = analysis();
+    analysis.macros =
+        serde_json::from_value(json!([{ "name": "second", "rvas": [0x2000] }])).unwrap();
+
+    let filtered = filter_profiles(&config(THREE_PROFILES), &names).unwrap();
+    let built = build_config(&filtered, &analysis).unwrap();
+
+    assert_eq!(profile_names(&built), ["first", "third"]);
+    assert_eq!(built.profiles[0].symbols, [0x1000]);
+    assert_eq!(built.profiles[1].symbols, [0x3000]);
+}
+
+#[test]
+fn no_names_keep_every_profile() {
+    let config = config(THREE_PROFILES);
+
+    assert_eq!(filter_profiles(&config, &[]).unwrap(), config);
+}
+
+#[test]
+fn unknown_profile_names_are_an_error_listing_the_available_ones() {
+    let names = ["second".to_owned(), "fourth".to_owned()];
+
+    let error = filter_profiles(&config(THREE_PROFILES), &names).unwrap_err();
+
+    match &error {
+        UtilsError::UnknownProfiles { unknown, available } => {
+            assert_eq!(unknown, &["fourth"]);
+            assert_eq!(available, &["first", "second", "third"]);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    assert_eq!(
+        error.to_string(),
+        "Unknown profile(s) fourth; available: first, second, third"
+    );
+}
+
+#[test]
+fn disabled_passes_are_left_out_of_the_built_profile() {
+    let config = config(
+        r#"
+profiles:
+  - name: app
+    passes:
+      - type: IDADecompilerCrasher
+        enabled: false
+      - type: AntiEmulator
+      - type: ObscureControlFlow
+        probability: 50
+        enabled: false
+        targets: [!Name stub]
+    symbols: [!Name main, !Name helper]
+"#,
+    );
+
+    let built = build_config(&config, &analysis()).unwrap();
+
+    assert_eq!(profile_names(&built), ["app"]);
+    assert_eq!(built.profiles[0].passes, [ObfuscationPass::AntiEmulator]);
+    assert_eq!(built.profiles[0].symbols, [0x1000, 0x2000]);
+    // The disabled passes keep their settings in the YAML config.
+    assert!(!config.profiles[0].passes[2].enabled);
+    assert_eq!(config.profiles[0].passes.len(), 3);
+}
+
+#[test]
+fn compiler_preset_with_compiler_settings_is_an_error() {
+    let mut config = config(
+        r#"
+profiles:
+  - name: app
+    compiler_preset: max
+    passes: []
+    symbols: [!Name main]
+"#,
+    );
+    config.profiles[0].compiler_settings = config.default_compiler_settings.clone();
+
+    let error = build_config(&config, &analysis()).unwrap_err();
+
+    assert!(
+        matches!(&error, UtilsError::ConflictingCompilerSettings(name) if name == "app"),
+        "{error:?}"
+    );
+}
+
+const ONE_PROFILE_FAILS: &str = r#"
+profiles:
+  - name: good
+    passes: []
+    symbols: [!Name main]
+  - name: broken
+    passes: []
+    symbols: [!Name missing]
+  - name: also_good
+    passes: []
+    symbols: [!Name stub]
+"#;
+
+#[test]
+fn failed_profile_fails_the_build_by_default() {
+    assert!(build_config(&config(ONE_PROFILE_FAILS), &analysis()).is_err());
+}
+
+#[test]
+fn skip_failed_profiles_builds_the_profiles_that_resolved() {
+    let options = BuildOptions {
+        skip_failed_profiles: true,
+        ..BuildOptions::default()
+    };
+
+    let built = build_config_with(&config(ONE_PROFILE_FAILS), &analysis(), &options).unwrap();
+
+    assert_eq!(profile_names(&built), ["good", "also_good"]);
+    assert_eq!(built.profiles[1].symbols, [0x3000]);
+}
+
+#[test]
+fn skip_failed_profiles_still_fails_when_nothing_resolved() {
+    let config = config(
+        r#"
+profiles:
+  - name: broken
+    passes: []
+    symbols: [!Name missing]
+"#,
+    );
+    let options = BuildOptions {
+        skip_failed_profiles: true,
+        ..BuildOptions::default()
+    };
+
+    let error = build_config_with(&config, &analysis(), &options).unwrap_err();
+
+    assert!(matches!(error, UtilsError::NoProfilesResolved), "{error:?}");
+}
@@ -0,0 +1,135 @@
+#[path = "../../api/tests/common/mod.rs"]
+mod common;
+
+use codedefender_api::{AnalyzeOptions, analyze_program};
+use codedefender_config::AnalysisResult;
+use codedefender_utils::{AnalysisCache, CacheOptions, analyze_cached};
+use common::{API_KEY, MockResponse, MockServer};
+use std::cell::Cell;
+use std::convert::Infallible;
+
+fn analysis(symbol: &str) -> AnalysisResult {
+    serde_json::from_value(serde_json::json!({
+        "environment": "UserMode",
+        "functions": [{ "rva": 4096, "symbol": symbol, "ref_count": 1 }],
+        "rejects": [],
+        "macros": []
+    }))
+    .unwrap()
+}
+
+#[test]
+fn key_depends_on_analyze_options() {
+    let default = AnalysisCache::key(b"MZ", None, &AnalyzeOptions::default());
+    let thunks = AnalysisCache::key(
+        b"MZ",
+        None,
+        &AnalyzeOptions {
+            include_thunks: true,
+            ..AnalyzeOptions::default()
+        },
+    );
+    let sections = AnalysisCache::key(
+        b"MZ",
+        None,
+        &AnalyzeOptions {
+            sections: vec![".text".to_owned()],
+            ..AnalyzeOptions::default()
+        },
+    );
+
+    assert_ne!(default, thunks);
+    assert_ne!(default, sections);
+    assert_ne!(thunks, sections);
+    assert_eq!(
+        default,
+        AnalysisCache::key(b"MZ", None, &AnalyzeOptions::default())
+    );
+}
+
+#[test]
+fn different_options_miss_the_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = AnalysisCache::new(dir.path());
+    let calls = Cell::new(0);
+    let analyze = |symbol: &'static str| {
+        let calls = &calls;
+        move || {
+            calls.set(calls.get() + 1);
+            Ok::<_, Infallible>(analysis(symbol))
+        }
+    };
+    let with_thunks = AnalyzeOptions {
+        include_thunks: true,
+        ..AnalyzeOptions::default()
+    };
+
+    let first = analyze_cached(
+        Some(&cache),
+        b"MZ",
+        None,
+        &AnalyzeOptions::default(),
+        analyze("plain"),
+    );
+    let second = analyze_cached(Some(&cache), b"MZ", None, &with_thunks, analyze("thunks"));
+
+    assert_eq!(calls.get(), 2);
+    assert_eq!(first.unwrap().functions[0].symbol, "plain");
+    assert_eq!(second.unwrap().functions[0].symbol, "thunks");
+}
+
+#[test]
+fn miss_writes_the_cache_and_hit_skips_the_server() {
+    let server = MockServer::start(|_| {
+        MockResponse::json(200, &serde_json::to_value(analysis("main")).unwrap())
+    });
+    let client = server.client();
+    let dir = tempfile::tempdir().unwrap();
+    let cache = AnalysisCache::new(dir.path().join("cache"));
+    let options = AnalyzeOptions::default();
+    let run = |binary: &[u8]| {
+        analyze_cached(Some(&cache), binary, None, &options, || {
+            analyze_program(
+                "11111111-2222-3333-4444-555555555555".parse().unwrap(),
+                None,
+                &client,
+                API_KEY,
+            )
+        })
+        .unwrap()
+    };
+
+    let first = run(b"MZ one");
+    assert_eq!(server.requests_to("/api/analyze").len(), 1);
+    let key = AnalysisCache::key(b"MZ one", None, &options);
+    assert_eq!(cache.load(&key), Some(first.clone()));
+
+    let second = run(b"MZ one");
+    assert_eq!(second, first);
+    assert_eq!(server.requests().len(), 1);
+
+    // A changed binary is a miss.
+    run(b"MZ two");
+    assert_eq!(server.requests_to("/api/analyze").len(), 2);
+}
+
+#[test]
+fn no_cache_disables_the_cache_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let options = CacheOptions {
+        analysis_cache: Some(dir.path().to_path_buf()),
+        no_cache: true,
+    };
+
+    assert!(options.cache().is_none());
+    assert_eq!(
+        CacheOptions {
+            no_cache: false,
+            ..options
+        }
+        .cache()
+        .unwrap()
+        .dir(),
+        dir.path()
+    );
+}
@@ -0,0 +1,73 @@
+use codedefender_config::YamlConfig;
+use codedefender_utils::{CheckOptions, UtilsError, check_config};
+
+// A valid config with the given version and `clear_unwind_info` setting.
+fn config(version: &str, clear_unwind_info: bool) -> YamlConfig {
+    YamlConfig::from_yaml_str(&format!(
+        r#"
+version: "{version}"
+module_settings:
+  ida_crasher: false
+  import_protection: false
+  obscure_entry_point: false
+  clear_unwind_info: {clear_unwind_info}
+  fake_pdb_string:
+    enabled: false
+    value: ""
+  custom_section_name:
+    enabled: false
+    value: ""
+profiles:
+  - name: main
+    compiler_preset: fast
+    passes:
+      - type: AntiEmulator
+    symbols: [!Name main]
+"#
+    ))
+    .unwrap()
+}
+
+fn strict() -> CheckOptions {
+    CheckOptions {
+        fail_on_warning: true,
+    }
+}
+
+#[test]
+fn clean_config_passes_in_both_modes() {
+    let config = config("1.1.0", false);
+
+    assert!(check_config(&config, &CheckOptions::default()).is_ok());
+    assert!(check_config(&config, &strict()).is_ok());
+}
+
+#[test]
+fn warning_only_config_fails_only_with_fail_on_warning() {
+    // Clearing unwind info is allowed, but warned about.
+    let config = config("1.1.0", true);
+
+    assert!(check_config(&config, &CheckOptions::default()).is_ok());
+    assert!(matches!(
+        check_config(&config, &strict()),
+        Err(UtilsError::InvalidConfig {
+            errors: 0,
+            warnings: 1
+        })
+    ));
+}
+
+#[test]
+fn errors_fail_in_both_modes() {
+    let config = config("99.0.0", true);
+
+    for options in [CheckOptions::default(), strict()] {
+        assert!(matches!(
+            check_config(&config, &options),
+            Err(UtilsError::InvalidConfig {
+                errors: 1,
+                warnings: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,172 @@
+#[path = "../../api/tests/common/mod.rs"]
+mod common;
+
+use codedefender_api::{ApiError, DefendError, DefendErrorCode, PollError, PollStrategy};
+use codedefender_config::YamlConfig;
+use codedefender_utils::{DefendJob, FailureCode, JobError, UtilsError, defend_many};
+use common::{API_KEY, MockResponse, MockServer};
+use reqwest::StatusCode;
+use serde_json::json;
+use std::process::ExitCode;
+use std::time::Duration;
+
+fn status(code: u16) -> ApiError {
+    ApiError::Status {
+        status: StatusCode::from_u16(code).unwrap(),
+        body: String::new(),
+    }
+}
+
+fn defend_failure() -> ApiError {
+    ApiError::Defend {
+        status: StatusCode::UNPROCESSABLE_ENTITY,
+        error: DefendError {
+            code: DefendErrorCode::UnsupportedInstruction,
+            message: "vmcall in main".to_owned(),
+        },
+    }
+}
+
+#[test]
+fn codes_are_distinct_and_non_zero() {
+    let codes = [
+        FailureCode::Other,
+        FailureCode::Config,
+        FailureCode::Network,
+        FailureCode::Obfuscation,
+        FailureCode::Timeout,
+        FailureCode::Interrupted,
+    ]
+    .map(FailureCode::code);
+
+    assert_eq!(codes, [1, 2, 3, 4, 5, 130]);
+    assert_eq!(ExitCode::from(FailureCode::Timeout), ExitCode::from(5));
+}
+
+#[test]
+fn config_and_input_errors_are_config_failures() {
+    let errors = [
+        UtilsError::MissingSymbol("main".to_owned()),
+        UtilsError::InvalidRva(0x1000),
+        UtilsError::InvalidConfig {
+            errors: 1,
+            warnings: 0,
+        },
+        UtilsError::DuplicateProfileName("app".to_owned()),
+        UtilsError::NotPe,
+        UtilsError::EmptyInput,
+    ];
+
+    for error in &errors {
+        assert_eq!(error.failure_code(), FailureCode::Config, "{error}");
+        assert_eq!(
+            JobError::Build(error.clone()).failure_code(),
+            FailureCode::Config
+        );
+    }
+}
+
+#[test]
+fn server_and_connection_errors_are_network_failures() {
+    // Nothing listens on port 1, so the connection is refused.
+    let refused = reqwest::blocking::get("http://127.0.0.1:1").unwrap_err();
+
+    for error in [ApiError::Http(refused), status(401), status(503)] {
+        assert_eq!(JobError::Api(error).failure_code(), FailureCode::Network);
+    }
+    assert_eq!(
+        JobError::Poll(PollError::Failed(status(500))).failure_code(),
+        FailureCode::Network
+    );
+}
+
+#[test]
+fn rejected_obfuscations_are_obfuscation_failures() {
+    assert_eq!(
+        JobError::Api(defend_failure()).failure_code(),
+        FailureCode::Obfuscation
+    );
+    assert_eq!(
+        JobError::Poll(PollError::Failed(defend_failure())).failure_code(),
+        FailureCode::Obfuscation
+    );
+}
+
+#[test]
+fn timed_out_downloads_are_timeouts() {
+    let error = JobError::Poll(PollError::TimedOut(Duration::from_secs(300)));
+
+    assert_eq!(error.failure_code(), FailureCode::Timeout);
+}
+
+#[test]
+fn oversized_uploads_are_config_failures() {
+    let error = ApiError::TooLarge {
+        size: 2 << 30,
+        limit: 1 << 30,
+    };
+
+    assert_eq!(JobError::Api(error).failure_code(), FailureCode::Config);
+}
+
+#[test]
+fn failed_batch_job_reports_the_obfuscation_code() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/upload" => MockResponse::new(200, "11111111-2222-3333-4444-555555555555"),
+        "/api/analyze" => MockResponse::json(
+            200,
+            &json!({
+                "environment": "UserMode",
+                "functions": [{ "rva": 4096, "symbol": "main", "ref_count": 1 }],
+                "rejects": [],
+                "macros": []
+            }),
+        ),
+        "/api/defend" => MockResponse::json(
+            422,
+            &json!({ "code": "unsupported_instruction", "message": "vmcall in main" }),
+        ),
+        _ => MockResponse::new(404, "not found"),
+    });
+    let config = YamlConfig::from_yaml_str(
+        r#"
+version: "1.1.0"
+module_settings:
+  ida_crasher: false
+  import_protection: false
+  obscure_entry_point: false
+  clear_unwind_info: false
+  fake_pdb_string:
+    enabled: false
+    value: ""
+  custom_section_name:
+    enabled: false
+    value: ""
+profiles:
+  - name: main
+    compiler_preset: fast
+    passes:
+      - type: AntiEmulator
+    symbols:
+      - !Name main
+"#,
+    )
+    .unwrap();
+    // Smallest input `check_input` accepts: the MZ magic and a PE signature at `e_lfanew`.
+    let mut binary = vec![0u8; 0x44];
+    binary[..2].copy_from_slice(b"MZ");
+    binary[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+    binary[0x40..].copy_from_slice(b"PE\0\0");
+    let job = DefendJob {
+        name: "a.exe".to_owned(),
+        binary,
+        pdb: None,
+        config,
+    };
+    let poll = PollStrategy::fixed(Duration::from_millis(10), Duration::from_secs(5));
+
+    let results = defend_many(vec![job], 1, &poll, &server.client(), API_KEY);
+
+    let error = results[0].as_ref().unwrap_err();
+    assert_eq!(error.failure_code(), FailureCode::Obfuscation, "{error}");
+}
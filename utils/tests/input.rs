@@ -0,0 +1,204 @@
+use codedefender_utils::{
+    ConfigFormat, UtilsError, check_input, find_adjacent_pdb, load_config, looks_like_pe,
+    resolve_pdb_path,
+};
+use std::fs;
+use std::path::Path;
+
+const YAML: &str = r#"
+version: "1.1.0"
+module_settings:
+  ida_crasher: true
+  import_protection: false
+  obscure_entry_point: false
+  clear_unwind_info: false
+  fake_pdb_string:
+    enabled: false
+    value: ""
+  custom_section_name:
+    enabled: true
+    value: ".cdx"
+profiles:
+  - name: main
+    compiler_preset: fast
+    passes:
+      - type: AntiEmulator
+      - type: ObscureControlFlow
+        probability: 50
+        enabled: false
+    symbols:
+      - !Name main
+      - !Rva 0x2000
+      - All
+"#;
+
+const JSON: &str = r#"{
+  "version": "1.1.0",
+  "module_settings": {
+    "ida_crasher": true,
+    "import_protection": false,
+    "obscure_entry_point": false,
+    "clear_unwind_info": false,
+    "fake_pdb_string": { "enabled": false, "value": "" },
+    "custom_section_name": { "enabled": true, "value": ".cdx" }
+  },
+  "profiles": [
+    {
+      "name": "main",
+      "compiler_preset": "fast",
+      "passes": [
+        { "type": "AntiEmulator" },
+        { "type": "ObscureControlFlow", "probability": 50, "enabled": false }
+      ],
+      "symbols": [{ "Name": "main" }, { "Rva": 8192 }, "All"]
+    }
+  ]
+}"#;
+
+fn write(dir: &Path, name: &str, contents: &str) -> std::path::PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn json_and_yaml_files_load_the_same_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let yaml = write(dir.path(), "config.yaml", YAML);
+    let yml = write(dir.path(), "config.yml", YAML);
+    let json = write(dir.path(), "config.json", JSON);
+
+    let from_yaml = load_config(&yaml, ConfigFormat::Auto).unwrap();
+
+    assert_eq!(load_config(&yml, ConfigFormat::Auto).unwrap(), from_yaml);
+    assert_eq!(load_config(&json, ConfigFormat::Auto).unwrap(), from_yaml);
+    assert_eq!(from_yaml.profiles[0].passes.len(), 2);
+}
+
+#[test]
+fn auto_detects_json_by_extension_in_any_case() {
+    let dir = tempfile::tempdir().unwrap();
+    let json = write(dir.path(), "CONFIG.JSON", JSON);
+
+    assert!(load_config(&json, ConfigFormat::Auto).is_ok());
+}
+
+#[test]
+fn explicit_format_overrides_the_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    let json = write(dir.path(), "config.txt", JSON);
+    let yaml = write(dir.path(), "config.json", YAML);
+
+    assert!(load_config(&json, ConfigFormat::Json).is_ok());
+    assert!(load_config(&yaml, ConfigFormat::Yaml).is_ok());
+    assert!(matches!(
+        load_config(&yaml, ConfigFormat::Auto),
+        Err(UtilsError::ParseConfig { path, .. }) if path == yaml
+    ));
+}
+
+#[test]
+fn missing_file_is_a_read_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing.yaml");
+
+    assert!(matches!(
+        load_config(&path, ConfigFormat::Auto),
+        Err(UtilsError::ReadConfig { path: p, .. }) if p == path
+    ));
+}
+
+#[test]
+fn pdb_next_to_the_binary_is_found() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("app.exe");
+    fs::write(&input, b"MZ").unwrap();
+    fs::write(dir.path().join("app.pdb"), b"pdb").unwrap();
+
+    assert_eq!(find_adjacent_pdb(&input), Some(dir.path().join("app.pdb")));
+}
+
+#[test]
+fn missing_or_misnamed_pdb_is_not_found() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("app.exe");
+    fs::write(&input, b"MZ").unwrap();
+    fs::write(dir.path().join("other.pdb"), b"pdb").unwrap();
+    // A directory with the PDB's name is not a PDB.
+    fs::create_dir(dir.path().join("app.pdb")).unwrap();
+
+    assert_eq!(find_adjacent_pdb(&input), None);
+}
+
+#[test]
+fn explicit_pdb_wins_over_the_adjacent_one() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("app.exe");
+    let adjacent = dir.path().join("app.pdb");
+    fs::write(&adjacent, b"pdb").unwrap();
+    let explicit = Path::new("/symbols/app.pdb");
+
+    assert_eq!(
+        resolve_pdb_path(&input, Some(explicit), true),
+        Some(explicit.to_path_buf())
+    );
+    assert_eq!(resolve_pdb_path(&input, None, true), Some(adjacent));
+    assert_eq!(resolve_pdb_path(&input, None, false), None);
+}
+
+// A minimal PE: the MZ magic, `e_lfanew` pointing at 0x40, and the PE signature there.
+fn minimal_pe() -> Vec<u8> {
+    let mut bytes = vec![0; 0x44];
+    bytes[..2].copy_from_slice(b"MZ");
+    bytes[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+    bytes[0x40..].copy_from_slice(b"PE\0\0");
+    bytes
+}
+
+#[test]
+fn minimal_pe_is_accepted() {
+    assert!(looks_like_pe(&minimal_pe()));
+    assert!(check_input(&minimal_pe()).is_ok());
+}
+
+#[test]
+fn empty_input_is_rejected() {
+    assert!(!looks_like_pe(&[]));
+    assert!(matches!(check_input(&[]), Err(UtilsError::EmptyInput)));
+}
+
+#[test]
+fn mz_magic_alone_is_not_a_pe() {
+    assert!(!looks_like_pe(b"MZ"));
+    assert!(matches!(check_input(b"MZ"), Err(UtilsError::NotPe)));
+}
+
+#[test]
+fn truncated_e_lfanew_is_not_a_pe() {
+    let mut bytes = minimal_pe();
+    bytes.truncate(0x3E);
+
+    assert!(!looks_like_pe(&bytes));
+    assert!(matches!(check_input(&bytes), Err(UtilsError::NotPe)));
+}
+
+#[test]
+fn e_lfanew_out_of_range_is_not_a_pe() {
+    for offset in [0x44, 0x1000, u32::MAX] {
+        let mut bytes = minimal_pe();
+        bytes[0x3C..0x40].copy_from_slice(&offset.to_le_bytes());
+
+        assert!(!looks_like_pe(&bytes), "{offset:#x}");
+    }
+}
+
+#[test]
+fn wrong_signatures_are_not_a_pe() {
+    let mut not_mz = minimal_pe();
+    not_mz[..2].copy_from_slice(b"ZM");
+    let mut not_pe = minimal_pe();
+    not_pe[0x40..].copy_from_slice(b"NE\0\0");
+
+    assert!(!looks_like_pe(&not_mz));
+    assert!(!looks_like_pe(&not_pe));
+}
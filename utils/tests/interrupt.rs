@@ -0,0 +1,109 @@
+// Kept in its own test binary: the interrupt flag is process-wide, and the signal would
+// stop the jobs of any test running alongside.
+#![cfg(unix)]
+
+#[path = "../../api/tests/common/mod.rs"]
+mod common;
+
+use codedefender_api::PollStrategy;
+use codedefender_config::YamlConfig;
+use codedefender_utils::{
+    DefendJob, FailureCode, JobError, defend_many, install_interrupt_handler,
+};
+use common::{API_KEY, MockResponse, MockServer, Recorded};
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+const FILE_ID: &str = "11111111-2222-3333-4444-555555555555";
+const EXECUTION_ID: &str = "66666666-7777-8888-9999-aaaaaaaaaaaa";
+
+// Smallest input `check_input` accepts: the MZ magic and a PE signature at `e_lfanew`.
+fn pe_image() -> Vec<u8> {
+    let mut bytes = vec![0u8; 0x44];
+    bytes[..2].copy_from_slice(b"MZ");
+    bytes[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+    bytes[0x40..].copy_from_slice(b"PE\0\0");
+    bytes
+}
+
+fn job() -> DefendJob {
+    let config = YamlConfig::from_yaml_str(
+        r#"
+version: "1.1.0"
+module_settings: {}
+profiles:
+  - name: main
+    compiler_preset: fast
+    passes:
+      - type: AntiEmulator
+    symbols:
+      - !Name main
+"#,
+    )
+    .unwrap();
+    DefendJob {
+        name: "app.exe".to_owned(),
+        binary: pe_image(),
+        pdb: None,
+        config,
+    }
+}
+
+// Answers every endpoint, with a download that never finishes.
+fn respond(request: &Recorded) -> MockResponse {
+    match request.path.as_str() {
+        "/api/upload" => MockResponse::new(200, FILE_ID),
+        "/api/verify" => MockResponse::json(200, &json!({ "valid": true })),
+        "/api/analyze" => MockResponse::json(
+            200,
+            &json!({
+                "environment": "UserMode",
+                "functions": [{ "rva": 4096, "symbol": "main", "ref_count": 1 }],
+                "rejects": [],
+                "macros": []
+            }),
+        ),
+        "/api/defend" => MockResponse::new(200, EXECUTION_ID),
+        "/api/download" => MockResponse::new(202, ""),
+        "/api/cancel" => MockResponse::new(200, ""),
+        _ => MockResponse::new(404, "not found"),
+    }
+}
+
+#[test]
+fn ctrl_c_cancels_the_running_execution() {
+    install_interrupt_handler().unwrap();
+    let signalled = AtomicBool::new(false);
+    let server = MockServer::start(move |request| {
+        // Press Ctrl-C once the job is polling for its download.
+        if request.path == "/api/download" && !signalled.swap(true, Ordering::SeqCst) {
+            let status = std::process::Command::new("kill")
+                .args(["-INT", &std::process::id().to_string()])
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+        respond(request)
+    });
+    let poll = PollStrategy::fixed(Duration::from_millis(10), Duration::from_secs(10));
+
+    let results = defend_many(vec![job()], 1, &poll, &server.client(), API_KEY);
+
+    assert!(
+        matches!(results[..], [Err(JobError::Interrupted)]),
+        "{results:?}"
+    );
+    assert_eq!(
+        results[0].as_ref().unwrap_err().failure_code(),
+        FailureCode::Interrupted
+    );
+    let cancels = server.requests_to("/api/cancel");
+    assert_eq!(cancels.len(), 1);
+    assert_eq!(cancels[0].method, "POST");
+    assert_eq!(cancels[0].query("executionId"), Some(EXECUTION_ID));
+    assert_eq!(
+        cancels[0].header("Authorization"),
+        Some(format!("ApiKey {}", API_KEY).as_str())
+    );
+}
@@ -0,0 +1,86 @@
+use codedefender_utils::{LogFormat, build_logger};
+use log::Log;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+// A writer whose contents the test can read back.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// A record to log: its level, message and structured fields.
+type TestRecord<'a> = (log::Level, &'a str, &'a [(&'a str, &'a str)]);
+
+// Logs `records` with the given format and returns the output.
+fn log_lines(format: LogFormat, records: &[TestRecord]) -> String {
+    let captured = Captured::default();
+    let logger = build_logger(format, env_logger::Target::Pipe(Box::new(captured.clone())));
+
+    for &(level, message, fields) in records {
+        logger.log(
+            &log::Record::builder()
+                .level(level)
+                .target("codedefender")
+                .args(format_args!("{}", message))
+                .key_values(&fields)
+                .build(),
+        );
+    }
+    logger.flush();
+
+    String::from_utf8(captured.0.lock().unwrap().clone()).unwrap()
+}
+
+#[test]
+fn json_lines_carry_the_structured_fields() {
+    let output = log_lines(
+        LogFormat::Json,
+        &[
+            (log::Level::Info, "Obfuscated app", &[("job", "app")]),
+            (
+                log::Level::Warn,
+                "Ignoring corrupt analysis cache entry",
+                &[("path", "/tmp/cache/abc.json")],
+            ),
+            (
+                log::Level::Error,
+                "Failed to write analysis cache",
+                &[("key", "abc")],
+            ),
+        ],
+    );
+
+    let lines: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3, "{output}");
+    assert_eq!(lines[0]["level"], "INFO");
+    assert_eq!(lines[0]["target"], "codedefender");
+    assert_eq!(lines[0]["message"], "Obfuscated app");
+    assert_eq!(lines[0]["job"], "app");
+    assert_eq!(lines[1]["level"], "WARN");
+    assert_eq!(lines[1]["path"], "/tmp/cache/abc.json");
+    assert_eq!(lines[2]["key"], "abc");
+    assert!(lines.iter().all(|line| line["timestamp"].is_string()));
+}
+
+#[test]
+fn text_lines_are_not_json() {
+    let output = log_lines(
+        LogFormat::Text,
+        &[(log::Level::Info, "Obfuscated app", &[("job", "app")])],
+    );
+
+    assert!(output.contains("Obfuscated app"), "{output}");
+    assert!(serde_json::from_str::<serde_json::Value>(output.trim()).is_err());
+}
@@ -0,0 +1,118 @@
+use codedefender_utils::{LogFormat, build_logger, extract_archive, write_output};
+use std::io::{Cursor, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+
+// A writer whose contents the test can read back.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Captured {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// The global logger can only be installed once per test binary.
+fn logs() -> &'static Captured {
+    static LOGS: OnceLock<Captured> = OnceLock::new();
+    LOGS.get_or_init(|| {
+        let logs = Captured::default();
+        let logger = build_logger(
+            LogFormat::Text,
+            env_logger::Target::Pipe(Box::new(logs.clone())),
+        );
+        log::set_max_level(log::LevelFilter::Info);
+        log::set_boxed_logger(Box::new(logger)).unwrap();
+        logs
+    })
+}
+
+fn zip_of(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, contents) in entries {
+        writer
+            .start_file(*name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(contents).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+fn zip_bytes() -> Vec<u8> {
+    zip_of(&[("app.exe", b"MZ protected")])
+}
+
+#[test]
+fn dash_writes_the_zip_to_stdout_and_logs_elsewhere() {
+    let logs = logs();
+    let bytes = zip_bytes();
+    let mut stdout = Vec::new();
+
+    write_output(Path::new("-"), &bytes, &mut stdout).unwrap();
+
+    assert_eq!(stdout, bytes);
+    assert!(
+        logs.contents()
+            .contains(&format!("Wrote {} bytes to stdout", bytes.len())),
+        "{}",
+        logs.contents()
+    );
+}
+
+#[test]
+fn file_path_does_not_touch_stdout() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("out.zip");
+    let bytes = zip_bytes();
+    let mut stdout = Vec::new();
+
+    write_output(&path, &bytes, &mut stdout).unwrap();
+
+    assert!(stdout.is_empty());
+    assert_eq!(std::fs::read(&path).unwrap(), bytes);
+}
+
+#[test]
+fn archive_is_extracted_into_the_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("out");
+    let bytes = zip_of(&[("app.exe", b"MZ protected"), ("maps/app.map", b"map")]);
+
+    let extracted = extract_archive(&bytes, &out).unwrap();
+
+    assert_eq!(extracted, [out.join("app.exe"), out.join("maps/app.map")]);
+    assert_eq!(std::fs::read(out.join("app.exe")).unwrap(), b"MZ protected");
+    assert_eq!(std::fs::read(out.join("maps/app.map")).unwrap(), b"map");
+}
+
+#[test]
+fn entries_escaping_the_directory_are_skipped() {
+    let logs = logs();
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("out");
+    let bytes = zip_of(&[("../evil", b"owned"), ("app.exe", b"MZ protected")]);
+
+    let extracted = extract_archive(&bytes, &out).unwrap();
+
+    assert_eq!(extracted, [out.join("app.exe")]);
+    assert!(!dir.path().join("evil").exists());
+    assert!(logs.contents().contains("`../evil`"), "{}", logs.contents());
+}
+
+#[test]
+fn invalid_archive_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+
+    assert!(extract_archive(b"not a zip", dir.path()).is_err());
+}
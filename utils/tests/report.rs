@@ -0,0 +1,50 @@
+use codedefender_config::YamlConfig;
+use codedefender_utils::ObfuscationReport;
+use std::time::Duration;
+
+const CONFIG: &str = r#"
+version: "1.1.0"
+module_settings: {}
+profiles:
+  - name: hot
+    compiler_preset: max
+    passes:
+      - type: AntiEmulator
+      - type: IDADecompilerCrasher
+    symbols: [!Rva 0x1000, !Rva 0x2000, !Rva 0x3000]
+  - name: cold
+    compiler_preset: fast
+    passes: []
+    symbols: [!Rva 0x4000]
+"#;
+
+#[test]
+fn report_lists_each_profile_and_writes_json() {
+    let config = YamlConfig::from_yaml_str(CONFIG)
+        .unwrap()
+        .try_into_cdconfig()
+        .unwrap();
+
+    let report = ObfuscationReport::new(&config, 4096, Duration::from_millis(1500));
+
+    assert_eq!(report.output_size, 4096);
+    assert_eq!(report.elapsed_secs, 1.5);
+    let profiles: Vec<_> = report
+        .profiles
+        .iter()
+        .map(|p| (p.name.as_str(), p.pass_count, p.symbol_count))
+        .collect();
+    assert_eq!(profiles, [("hot", 2, 3), ("cold", 0, 1)]);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("report.json");
+    report.write_json(&path).unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(json["output_size"], 4096);
+    assert_eq!(json["elapsed_secs"], 1.5);
+    assert_eq!(json["profiles"][0]["name"], "hot");
+    assert_eq!(json["profiles"][0]["pass_count"], 2);
+    assert_eq!(json["profiles"][1]["symbol_count"], 1);
+}
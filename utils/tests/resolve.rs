@@ -0,0 +1,393 @@
+use codedefender_config::{
+    AnalysisFunction, AnalysisReject, AnalysisResult, PeEnvironment, YamlSymbol,
+};
+use codedefender_utils::{
+    AnalysisIndex, ResolveOutcome, UtilsError, is_valid_rva, resolve_one, resolve_symbols,
+    resolve_symbols_indexed, resolve_symbols_set,
+};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+
+// `main` is a function, `patcher` a force-resolvable reject and `tiny` another reject.
+fn analysis() -> AnalysisResult {
+    serde_json::from_value(json!({
+        "environment": "UserMode",
+        "functions": [{ "rva": 0x1000, "symbol": "main", "ref_count": 1 }],
+        "rejects": [
+            { "rva": 0x2000, "symbol": "patcher", "ty": "ReadWriteToCode", "reason": "writes its own code" },
+            { "rva": 0x3000, "symbol": "tiny", "ty": "TooSmall", "reason": "function is too small" }
+        ],
+        "macros": []
+    }))
+    .unwrap()
+}
+
+fn name(name: &str) -> YamlSymbol {
+    YamlSymbol::Name(name.to_owned())
+}
+
+#[test]
+fn force_resolvable_rejects_resolve() {
+    let analysis = analysis();
+
+    match resolve_one(&name("patcher"), &analysis) {
+        ResolveOutcome::Reject { reject, forced } => {
+            assert_eq!(reject.rva, 0x2000);
+            assert!(forced);
+        }
+        other => panic!("unexpected outcome: {other:?}"),
+    }
+    assert!(is_valid_rva(0x2000, &analysis));
+    assert_eq!(
+        resolve_symbols(&[name("main"), YamlSymbol::Rva(0x2000)], &analysis).unwrap(),
+        [0x1000, 0x2000]
+    );
+}
+
+#[test]
+fn other_rejects_do_not_resolve() {
+    let analysis = analysis();
+
+    assert!(matches!(
+        resolve_one(&name("tiny"), &analysis),
+        ResolveOutcome::Reject { forced: false, .. }
+    ));
+    assert!(!is_valid_rva(0x3000, &analysis));
+    assert!(resolve_symbols(&[name("tiny")], &analysis).is_err());
+}
+
+#[test]
+fn each_failure_is_its_own_error() {
+    let analysis = analysis();
+    let section = || YamlSymbol::Section(".text".to_owned());
+
+    assert_eq!(
+        resolve_symbols(&[name("main"), name("missing")], &analysis),
+        Err(UtilsError::MissingSymbol(name("missing").to_string()))
+    );
+    assert_eq!(
+        resolve_symbols(&[YamlSymbol::Rva(0x4000)], &analysis),
+        Err(UtilsError::InvalidRva(0x4000))
+    );
+    assert_eq!(
+        resolve_symbols(&[name("tiny")], &analysis),
+        Err(UtilsError::RejectedSymbol {
+            symbol: name("tiny").to_string(),
+            reason: "function is too small".to_owned(),
+        })
+    );
+    assert_eq!(
+        resolve_symbols(&[section()], &analysis),
+        Err(UtilsError::MissingSectionInfo(section().to_string()))
+    );
+    assert_eq!(
+        UtilsError::InvalidRva(0x4000).to_string(),
+        "Invalid RVA 4000"
+    );
+}
+
+#[test]
+fn every_outcome_is_reported_with_its_rvas() {
+    let analysis = analysis();
+    let mut with_sections = analysis.clone();
+    with_sections.functions[0].section = Some(".text".to_owned());
+
+    let cases = [
+        (name("main"), &analysis, ResolveOutcome::Function(0x1000)),
+        (
+            YamlSymbol::Rva(0x1000),
+            &analysis,
+            ResolveOutcome::Function(0x1000),
+        ),
+        (name("missing"), &analysis, ResolveOutcome::NotFound),
+        (
+            YamlSymbol::Rva(0x4000),
+            &analysis,
+            ResolveOutcome::InvalidRva(0x4000),
+        ),
+        // `All` includes the force-resolvable `patcher`.
+        (
+            YamlSymbol::All,
+            &analysis,
+            ResolveOutcome::Functions(vec![0x1000, 0x2000]),
+        ),
+        (
+            YamlSymbol::RefCountAtLeast(2),
+            &analysis,
+            ResolveOutcome::Functions(vec![]),
+        ),
+        (
+            YamlSymbol::Section(".text".to_owned()),
+            &analysis,
+            ResolveOutcome::MissingSectionInfo,
+        ),
+        (
+            YamlSymbol::Section(".text".to_owned()),
+            &with_sections,
+            ResolveOutcome::Functions(vec![0x1000]),
+        ),
+    ];
+
+    for (symbol, analysis, expected) in cases {
+        assert_eq!(resolve_one(&symbol, analysis), expected, "{symbol}");
+    }
+    assert_eq!(ResolveOutcome::Function(0x1000).rvas(), Some(vec![0x1000]));
+    assert_eq!(ResolveOutcome::Functions(vec![]).rvas(), Some(vec![]));
+    assert_eq!(ResolveOutcome::NotFound.rvas(), None);
+    assert_eq!(ResolveOutcome::InvalidRva(0x4000).rvas(), None);
+    assert_eq!(ResolveOutcome::MissingSectionInfo.rvas(), None);
+    assert_eq!(
+        resolve_one(&name("patcher"), &analysis).rvas(),
+        Some(vec![0x2000])
+    );
+    assert_eq!(resolve_one(&name("tiny"), &analysis).rvas(), None);
+}
+
+#[test]
+fn ref_count_threshold_is_inclusive() {
+    let analysis: AnalysisResult = serde_json::from_value(json!({
+        "environment": "UserMode",
+        "functions": [
+            { "rva": 0x1000, "symbol": "hot", "ref_count": 10 },
+            { "rva": 0x2000, "symbol": "warm", "ref_count": 9 },
+            { "rva": 0x3000, "symbol": "cold", "ref_count": 0 }
+        ],
+        "rejects": [
+            { "rva": 0x4000, "symbol": "patcher", "ty": "ReadWriteToCode", "reason": "writes its own code" }
+        ],
+        "macros": []
+    }))
+    .unwrap();
+    let at_least =
+        |count| resolve_symbols(&[YamlSymbol::RefCountAtLeast(count)], &analysis).unwrap();
+
+    // Only analyzed functions have a reference count, so `patcher` never matches.
+    assert_eq!(at_least(0), [0x1000, 0x2000, 0x3000]);
+    assert_eq!(at_least(9), [0x1000, 0x2000]);
+    assert_eq!(at_least(10), [0x1000]);
+    assert!(at_least(11).is_empty());
+}
+
+#[test]
+fn duplicate_symbols_resolve_once_in_first_seen_order() {
+    let analysis = analysis();
+
+    let resolved = resolve_symbols(
+        &[
+            YamlSymbol::Rva(0x2000),
+            name("main"),
+            YamlSymbol::Rva(0x1000),
+            name("patcher"),
+            name("main"),
+        ],
+        &analysis,
+    )
+    .unwrap();
+
+    assert_eq!(resolved, [0x2000, 0x1000]);
+}
+
+#[test]
+fn resolved_sets_can_be_keyed_by_environment() {
+    let user = analysis();
+    let mut kernel = analysis();
+    kernel.environment = PeEnvironment::KernelMode;
+    let symbols = [name("main"), YamlSymbol::Rva(0x2000), name("main")];
+
+    let mut by_environment: HashMap<PeEnvironment, HashSet<u64>> = HashMap::new();
+    for analysis in [&user, &kernel] {
+        let resolved = resolve_symbols_set(&symbols, analysis).unwrap();
+        by_environment
+            .entry(analysis.environment)
+            .or_default()
+            .extend(resolved);
+    }
+
+    assert_eq!(by_environment.len(), 2);
+    assert_eq!(
+        by_environment[&PeEnvironment::UserMode],
+        HashSet::from([0x1000, 0x2000])
+    );
+    assert_eq!(
+        by_environment[&PeEnvironment::KernelMode],
+        by_environment[&PeEnvironment::UserMode]
+    );
+    assert!(!by_environment.contains_key(&PeEnvironment::UEFI));
+    assert!(resolve_symbols_set(&[name("missing")], &user).is_err());
+}
+
+// Straightforward resolution scanning the whole analysis for every symbol, as the
+// resolver did before it was indexed. `None` where resolution fails.
+fn resolve_linear(symbols: &[YamlSymbol], analysis: &AnalysisResult) -> Option<Vec<u64>> {
+    let forced = |r: &&AnalysisReject| r.is_force_resolvable();
+    let mut resolved = Vec::new();
+    for symbol in symbols {
+        match symbol {
+            YamlSymbol::Name(name) => resolved.push(
+                analysis
+                    .functions
+                    .iter()
+                    .find(|f| f.symbol == *name)
+                    .map(|f| f.rva)
+                    .or_else(|| {
+                        analysis
+                            .rejects
+                            .iter()
+                            .filter(forced)
+                            .find(|r| r.symbol == *name)
+                            .map(|r| r.rva)
+                    })?,
+            ),
+            YamlSymbol::Rva(rva) => {
+                let valid = analysis.functions.iter().any(|f| f.rva == *rva)
+                    || analysis
+                        .rejects
+                        .iter()
+                        .filter(forced)
+                        .any(|r| r.rva == *rva);
+                if !valid {
+                    return None;
+                }
+                resolved.push(*rva);
+            }
+            YamlSymbol::All => {
+                resolved.extend(analysis.functions.iter().map(|f| f.rva));
+                resolved.extend(analysis.rejects.iter().filter(forced).map(|r| r.rva));
+            }
+            YamlSymbol::Section(section) => {
+                if analysis.functions.iter().all(|f| f.section.is_none()) {
+                    return None;
+                }
+                resolved.extend(
+                    analysis
+                        .functions
+                        .iter()
+                        .filter(|f| f.section.as_deref() == Some(section.as_str()))
+                        .map(|f| f.rva),
+                );
+            }
+            YamlSymbol::RefCountAtLeast(count) => resolved.extend(
+                analysis
+                    .functions
+                    .iter()
+                    .filter(|f| f.ref_count >= *count)
+                    .map(|f| f.rva),
+            ),
+        }
+    }
+    let mut seen = HashSet::new();
+    resolved.retain(|rva| seen.insert(*rva));
+    Some(resolved)
+}
+
+// Small deterministic generator, so failures reproduce.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % bound
+    }
+}
+
+// 5000 functions with repeated names, rejects of both kinds (some sharing names and RVAs
+// with functions or each other), spread over a few sections.
+fn large_analysis(rng: &mut Lcg) -> AnalysisResult {
+    let sections = [".text", ".code", ".crypto"];
+    let functions = (0..5000u64)
+        .map(|i| AnalysisFunction {
+            rva: 0x1000 + i * 0x10,
+            symbol: format!("f{}", rng.next(4000)),
+            ref_count: rng.next(20) as usize,
+            section: Some(sections[rng.next(3) as usize].to_owned()),
+            size: None,
+        })
+        .collect();
+    let rejects = (0..500u64)
+        .map(|i| AnalysisReject {
+            rva: if i % 5 == 0 {
+                0x1000 + rng.next(5000) * 0x10
+            } else {
+                0x100000 + rng.next(400) * 0x10
+            },
+            symbol: format!("{}{}", if i % 3 == 0 { "f" } else { "r" }, rng.next(4500)),
+            ty: if i % 2 == 0 {
+                "ReadWriteToCode"
+            } else {
+                "TooSmall"
+            }
+            .to_owned(),
+            reason: "rejected".to_owned(),
+        })
+        .collect();
+    AnalysisResult {
+        environment: codedefender_config::PeEnvironment::UserMode,
+        functions,
+        rejects,
+        macros: Vec::new(),
+    }
+}
+
+fn random_symbol(rng: &mut Lcg) -> YamlSymbol {
+    match rng.next(10) {
+        0..=3 => YamlSymbol::Name(format!(
+            "{}{}",
+            ["f", "r", "x"][rng.next(3) as usize],
+            rng.next(4500)
+        )),
+        4..=6 => YamlSymbol::Rva(match rng.next(3) {
+            0 => 0x1000 + rng.next(5000) * 0x10,
+            1 => 0x100000 + rng.next(400) * 0x10,
+            _ => rng.next(0x200000),
+        }),
+        7 => YamlSymbol::Section([".text", ".crypto", ".none"][rng.next(3) as usize].to_owned()),
+        8 => YamlSymbol::RefCountAtLeast(rng.next(25) as usize),
+        _ => YamlSymbol::All,
+    }
+}
+
+#[test]
+fn indexed_resolution_matches_linear_resolution() {
+    let mut rng = Lcg(0x5eed);
+    let analysis = large_analysis(&mut rng);
+    let index = AnalysisIndex::new(&analysis);
+
+    let (mut resolved, mut failed) = (0, 0);
+    for _ in 0..300 {
+        let len = 1 + rng.next(8) as usize;
+        let symbols: Vec<YamlSymbol> = (0..len).map(|_| random_symbol(&mut rng)).collect();
+
+        let indexed = resolve_symbols_indexed(&symbols, &index).ok();
+
+        assert_eq!(indexed, resolve_linear(&symbols, &analysis), "{symbols:?}");
+        match indexed {
+            Some(_) => resolved += 1,
+            None => failed += 1,
+        }
+    }
+    // Both outcomes are exercised.
+    assert!(
+        resolved > 30 && failed > 30,
+        "{resolved} resolved, {failed} failed"
+    );
+}
+
+#[test]
+fn linear_resolution_agrees_without_section_info() {
+    let mut rng = Lcg(7);
+    let mut analysis = large_analysis(&mut rng);
+    for function in &mut analysis.functions {
+        function.section = None;
+    }
+    let symbols = [YamlSymbol::All, YamlSymbol::Section(".text".to_owned())];
+
+    assert!(resolve_symbols(&symbols, &analysis).is_err());
+    assert_eq!(resolve_linear(&symbols, &analysis), None);
+    assert_eq!(
+        resolve_symbols(&symbols[..1], &analysis).ok(),
+        resolve_linear(&symbols[..1], &analysis)
+    );
+}
@@ -0,0 +1,81 @@
+use codedefender_config::{
+    AnalysisResult, CompilerPreset, StarterOptions, YamlConfig, YamlSymbol, validate_config,
+    validate_environment,
+};
+use codedefender_utils::build_config;
+use serde_json::json;
+
+// A kernel-mode analysis where `dup` names two functions and one function has no name.
+fn analysis() -> AnalysisResult {
+    serde_json::from_value(json!({
+        "environment": "KernelMode",
+        "functions": [
+            { "rva": 0x1000, "symbol": "entry", "ref_count": 1 },
+            { "rva": 0x2000, "symbol": "dup", "ref_count": 5 },
+            { "rva": 0x3000, "symbol": "dispatch", "ref_count": 3 },
+            { "rva": 0x4000, "symbol": "dup", "ref_count": 4 },
+            { "rva": 0x5000, "symbol": "", "ref_count": 0 }
+        ],
+        "rejects": [],
+        "macros": []
+    }))
+    .unwrap()
+}
+
+#[test]
+fn starter_config_validates_for_the_analyzed_environment() {
+    let analysis = analysis();
+
+    let config = YamlConfig::from_analysis(&analysis, StarterOptions::default());
+
+    let report = validate_config(&config);
+    assert!(!report.has_errors(), "{report:?}");
+    let report = validate_environment(&config, analysis.environment);
+    assert!(!report.has_errors(), "{report:?}");
+    assert_eq!(config.profiles.len(), 1);
+    assert_eq!(
+        config.profiles[0].compiler_preset,
+        Some(CompilerPreset::Balanced)
+    );
+}
+
+#[test]
+fn starter_config_targets_every_function_by_default() {
+    let analysis = analysis();
+
+    let config = YamlConfig::from_analysis(&analysis, StarterOptions::default());
+
+    assert_eq!(
+        config.profiles[0].symbols,
+        [
+            YamlSymbol::Name("entry".to_owned()),
+            YamlSymbol::Name("dup".to_owned()),
+            YamlSymbol::Name("dispatch".to_owned()),
+            YamlSymbol::Rva(0x4000),
+            YamlSymbol::Rva(0x5000),
+        ]
+    );
+    let built = build_config(&config, &analysis).unwrap();
+    assert_eq!(
+        built.profiles[0].symbols,
+        [0x1000, 0x2000, 0x3000, 0x4000, 0x5000]
+    );
+}
+
+#[test]
+fn starter_config_can_target_the_most_referenced_functions() {
+    let analysis = analysis();
+
+    let config = YamlConfig::from_analysis(&analysis, StarterOptions { top_n: Some(3) });
+
+    assert_eq!(
+        config.profiles[0].symbols,
+        [
+            YamlSymbol::Name("dup".to_owned()),
+            YamlSymbol::Rva(0x4000),
+            YamlSymbol::Name("dispatch".to_owned()),
+        ]
+    );
+    let built = build_config(&config, &analysis).unwrap();
+    assert_eq!(built.profiles[0].symbols, [0x2000, 0x3000, 0x4000]);
+}
@@ -0,0 +1,140 @@
+#![cfg(feature = "tracing")]
+
+#[path = "../../api/tests/common/mod.rs"]
+mod common;
+
+use codedefender_api::{DownloadStatus, analyze_program, defend, download, upload_file};
+use codedefender_config::YamlConfig;
+use codedefender_utils::build_config;
+use common::{API_KEY, MockResponse, MockServer};
+use serde_json::json;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+// A span as it was created: its name and the fields recorded with it.
+#[derive(Debug)]
+struct Span {
+    name: &'static str,
+    fields: Vec<(&'static str, String)>,
+}
+
+impl Span {
+    fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+impl Visit for Span {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.fields.push((field.name(), format!("{:?}", value)));
+    }
+}
+
+// Records every span created while it is the default subscriber.
+#[derive(Clone, Default)]
+struct SpanRecorder {
+    spans: Arc<Mutex<Vec<Span>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Subscriber for SpanRecorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let mut span = Span {
+            name: attributes.metadata().name(),
+            fields: Vec::new(),
+        };
+        attributes.record(&mut span);
+        self.spans.lock().unwrap().push(span);
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+const CONFIG: &str = r#"
+version: "1.1.0"
+module_settings:
+  ida_crasher: false
+  import_protection: false
+  obscure_entry_point: false
+  clear_unwind_info: false
+  fake_pdb_string:
+    enabled: false
+    value: ""
+  custom_section_name:
+    enabled: false
+    value: ""
+profiles:
+  - name: main
+    compiler_preset: fast
+    passes:
+      - type: AntiEmulator
+    symbols: [!Name main, !Name helper]
+"#;
+
+#[test]
+fn pipeline_stages_emit_spans_in_order() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/api/upload" => MockResponse::new(200, "11111111-2222-3333-4444-555555555555"),
+        "/api/analyze" => MockResponse::json(
+            200,
+            &json!({
+                "environment": "UserMode",
+                "functions": [
+                    { "rva": 4096, "symbol": "main", "ref_count": 1 },
+                    { "rva": 8192, "symbol": "helper", "ref_count": 1 },
+                    { "rva": 12288, "symbol": "stub", "ref_count": 1 }
+                ],
+                "rejects": [],
+                "macros": []
+            }),
+        ),
+        "/api/defend" => MockResponse::new(200, "66666666-7777-8888-9999-aaaaaaaaaaaa"),
+        "/api/download" => MockResponse::new(200, b"PK\x03\x04".to_vec()),
+        _ => MockResponse::new(404, "not found"),
+    });
+    let client = server.client();
+    let config = YamlConfig::from_yaml_str(CONFIG).unwrap();
+    let recorder = SpanRecorder::default();
+
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let file_id = upload_file(b"MZ binary".to_vec(), &client, API_KEY).unwrap();
+        let analysis = analyze_program(file_id.clone(), None, &client, API_KEY).unwrap();
+        let built = build_config(&config, &analysis).unwrap();
+        let execution_id = defend(file_id, built, &client, API_KEY).unwrap();
+        assert!(matches!(
+            download(execution_id, &client, API_KEY),
+            DownloadStatus::Ready { .. }
+        ));
+    });
+
+    let spans = recorder.spans.lock().unwrap();
+    let names: Vec<&str> = spans.iter().map(|span| span.name).collect();
+    assert_eq!(
+        names,
+        ["upload", "analyze", "resolve_symbols", "defend", "download"]
+    );
+    assert_eq!(spans[0].field("file_size"), Some("9"));
+    assert_eq!(spans[2].field("function_count"), Some("3"));
+    assert_eq!(spans[2].field("symbol_count"), Some("2"));
+    assert_eq!(spans[3].field("symbol_count"), Some("2"));
+}